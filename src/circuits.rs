@@ -0,0 +1,196 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::data::index::{canonical_key, race_name_alias};
+use crate::models::Circuit;
+use crate::utils::normalize_gp_name;
+
+/// The circuit catalogue shipped with the simulator
+const BUNDLED_CIRCUITS: &str = include_str!("../data/circuits.json");
+
+/// Length of a lap at a circuit missing from the catalogue
+const UNKNOWN_LENGTH_KM: f64 = 5.0;
+
+/// Minimum distance of a grand prix in kilometres
+const RACE_DISTANCE_KM: f64 = 305.0;
+
+/// Characteristics of a circuit missing from the catalogue: an average track
+const UNKNOWN_CORNERS: u32 = 16;
+const UNKNOWN_DRS_ZONES: u32 = 2;
+const UNKNOWN_OVERTAKING_DIFFICULTY: f64 = 0.5;
+const UNKNOWN_PIT_LANE_LOSS_SECS: f64 = 21.0;
+const UNKNOWN_WEATHER_RISK: f64 = 0.15;
+const UNKNOWN_TYRE_WEAR: f64 = 1.0;
+const UNKNOWN_SAFETY_CAR_CHANCE: f64 = 0.35;
+const UNKNOWN_VIRTUAL_SAFETY_CAR_CHANCE: f64 = 0.4;
+const UNKNOWN_RED_FLAG_CHANCE: f64 = 0.05;
+
+/// A circuit in the catalogue, with the names its races go by
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircuitEntry {
+    /// Circuit id as used by the results feed, e.g. "red_bull_ring"
+    pub id: String,
+    pub name: String,
+    pub country: String,
+    pub city: String,
+    /// Grand prix names and other names the circuit is known by, e.g. "austrian"
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub length_km: f64,
+    pub laps: u32,
+    pub corners: u32,
+    pub drs_zones: u32,
+    pub overtaking_difficulty: f64,
+    pub pit_lane_loss_secs: f64,
+    pub weather_risk: f64,
+    /// How hard the circuit is on tyres relative to an average track
+    #[serde(default = "unknown_tyre_wear")]
+    pub tyre_wear: f64,
+    /// Chances that a crash brings out the safety car, the virtual safety car
+    /// or a red flag
+    #[serde(default = "unknown_safety_car_chance")]
+    pub safety_car_chance: f64,
+    #[serde(default = "unknown_virtual_safety_car_chance")]
+    pub virtual_safety_car_chance: f64,
+    #[serde(default = "unknown_red_flag_chance")]
+    pub red_flag_chance: f64,
+}
+
+// Characteristics of a catalogue entry that does not give them
+fn unknown_tyre_wear() -> f64 {
+    UNKNOWN_TYRE_WEAR
+}
+
+fn unknown_safety_car_chance() -> f64 {
+    UNKNOWN_SAFETY_CAR_CHANCE
+}
+
+fn unknown_virtual_safety_car_chance() -> f64 {
+    UNKNOWN_VIRTUAL_SAFETY_CAR_CHANCE
+}
+
+fn unknown_red_flag_chance() -> f64 {
+    UNKNOWN_RED_FLAG_CHANCE
+}
+
+impl CircuitEntry {
+    // Whether a canonical name refers to this circuit
+    fn matches(&self, key: &str) -> bool {
+        self.id == key || self.aliases.iter().any(|alias| canonical_key(alias) == key)
+    }
+    
+    /// The circuit with its characteristics
+    pub fn to_circuit(&self) -> Circuit {
+        let mut circuit = Circuit {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            country: self.country.clone(),
+            city: self.city.clone(),
+            ..Circuit::default()
+        };
+        self.characterize(&mut circuit);
+        circuit
+    }
+    
+    /// Set the length, race distance and characteristics of a circuit
+    pub fn characterize(&self, circuit: &mut Circuit) {
+        circuit.length_km = self.length_km;
+        circuit.laps = self.laps;
+        circuit.corners = self.corners;
+        circuit.drs_zones = self.drs_zones;
+        circuit.overtaking_difficulty = self.overtaking_difficulty;
+        circuit.pit_lane_loss_secs = self.pit_lane_loss_secs;
+        circuit.weather_risk = self.weather_risk;
+        circuit.tyre_wear = self.tyre_wear;
+        circuit.safety_car_chance = self.safety_car_chance;
+        circuit.virtual_safety_car_chance = self.virtual_safety_car_chance;
+        circuit.red_flag_chance = self.red_flag_chance;
+    }
+}
+
+/// Length, race distance and characteristics of every known circuit
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitCatalogue {
+    circuits: Vec<CircuitEntry>,
+}
+
+impl CircuitCatalogue {
+    /// The catalogue shipped with the simulator
+    pub fn bundled() -> Self {
+        Self::from_json(BUNDLED_CIRCUITS).expect("bundled circuit catalogue is valid")
+    }
+    
+    /// Parse a catalogue: a JSON list of circuits
+    pub fn from_json(json: &str) -> Result<Self> {
+        let circuits: Vec<CircuitEntry> = serde_json::from_str(json)?;
+        Ok(CircuitCatalogue { circuits })
+    }
+    
+    /// Read a catalogue file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read circuit catalogue {}", path.display()))?;
+        Self::from_json(&data)
+            .with_context(|| format!("Failed to parse circuit catalogue {}", path.display()))
+    }
+    
+    /// Add the circuits of another catalogue, replacing those with the same id
+    pub fn merge(&mut self, other: CircuitCatalogue) {
+        for entry in other.circuits {
+            match self.circuits.iter_mut().find(|c| c.id == entry.id) {
+                Some(existing) => *existing = entry,
+                None => self.circuits.push(entry),
+            }
+        }
+    }
+    
+    /// Every circuit in the catalogue
+    pub fn entries(&self) -> &[CircuitEntry] {
+        &self.circuits
+    }
+    
+    /// Find a circuit by id, alias or grand prix name, e.g. "monza", "Italian GP" or "italian"
+    pub fn find(&self, name: &str) -> Option<&CircuitEntry> {
+        let key = race_name_alias(name);
+        let normalized = canonical_key(&normalize_gp_name(name));
+        
+        self.circuits.iter().find(|c| c.matches(&key))
+            .or_else(|| self.circuits.iter().find(|c| c.matches(&normalized)))
+    }
+    
+    /// The circuit a grand prix is held at
+    pub fn circuit(&self, gp: &str) -> Result<Circuit> {
+        self.find(gp)
+            .map(CircuitEntry::to_circuit)
+            .ok_or_else(|| anyhow::anyhow!("Unknown GP: {}", gp))
+    }
+    
+    /// Fill in the length, race distance and characteristics of a circuit
+    /// from its catalogue entry, or with those of an average track if it has none
+    pub fn characterize(&self, circuit: &mut Circuit) {
+        if let Some(entry) = self.find(&circuit.id) {
+            entry.characterize(circuit);
+            return;
+        }
+        
+        circuit.length_km = UNKNOWN_LENGTH_KM;
+        circuit.laps = (RACE_DISTANCE_KM / UNKNOWN_LENGTH_KM).ceil() as u32;
+        circuit.corners = UNKNOWN_CORNERS;
+        circuit.drs_zones = UNKNOWN_DRS_ZONES;
+        circuit.overtaking_difficulty = UNKNOWN_OVERTAKING_DIFFICULTY;
+        circuit.pit_lane_loss_secs = UNKNOWN_PIT_LANE_LOSS_SECS;
+        circuit.weather_risk = UNKNOWN_WEATHER_RISK;
+        circuit.tyre_wear = UNKNOWN_TYRE_WEAR;
+        circuit.safety_car_chance = UNKNOWN_SAFETY_CAR_CHANCE;
+        circuit.virtual_safety_car_chance = UNKNOWN_VIRTUAL_SAFETY_CAR_CHANCE;
+        circuit.red_flag_chance = UNKNOWN_RED_FLAG_CHANCE;
+    }
+}
+
+impl Default for CircuitCatalogue {
+    fn default() -> Self {
+        Self::bundled()
+    }
+}
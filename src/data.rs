@@ -1,76 +1,195 @@
 use std::fs;
-use std::path::Path;
-use anyhow::{Result, Context};
-use reqwest::blocking::Client;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use chrono::{Datelike, NaiveDate, Utc};
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use serde::Deserialize;
 use serde_json::Value;
-use crate::models::{Driver, Circuit, Race, RaceResult, QualifyingResult, PracticeResult};
+use crate::circuits::CircuitCatalogue;
+use crate::models::{Driver, Circuit, Race, RaceResult, FastestLap, Lap, LapTiming, PitStop, QualifyingResult, PracticeResult, SprintResult, RaceFormat, WeekendResults};
+use crate::points::PointsSystem;
+use crate::ratings::{Ratings, RATING_SEASONS};
+use crate::standings::Standings;
 use crate::utils::normalize_gp_name;
 
-const API_BASE_URL: &str = "https://ergast.com/api/f1";
-const DATA_DIR: &str = "./data";
-const CURRENT_SEASON: u32 = 2025;
+pub mod config;
+pub mod error;
+pub mod fetch;
+pub mod index;
+pub mod manifest;
+
+pub use config::DataConfig;
+pub use error::DataError;
+use fetch::{FetchClient, fetch_all_pages};
+use manifest::Manifest;
+use index::{SeasonIndex, RoundEntry, load_index, save_index, round_file_name, parse_round_file_name, migrate_circuit_keyed_files};
+
+/// First season of the world championship
+pub const FIRST_SEASON: u32 = 1950;
 
 /// Data interface trait for dependency injection and testing
 pub trait DataInterface {
     fn load_race_data(&self, season: u32, gp: &str) -> Result<Race>;
     fn load_qualifying_data(&self, season: u32, gp: &str) -> Result<Vec<QualifyingResult>>;
     fn load_practice_data(&self, season: u32, gp: &str, practice_number: u32) -> Result<Vec<PracticeResult>>;
+    fn load_sprint_data(&self, season: u32, gp: &str) -> Result<Vec<SprintResult>>;
+    fn load_lap_data(&self, season: u32, gp: &str) -> Result<Vec<Lap>>;
+    fn load_pit_stops(&self, season: u32, gp: &str) -> Result<Vec<PitStop>>;
 }
 
 /// Default implementation that uses the file system and API
-pub struct DataManager;
+pub struct DataManager {
+    config: DataConfig,
+}
+
+impl DataManager {
+    pub fn new(config: DataConfig) -> Self {
+        DataManager { config }
+    }
+
+    pub fn config(&self) -> &DataConfig {
+        &self.config
+    }
+    
+    /// Serve only cached data and never fetch missing files
+    pub fn set_offline(&mut self, offline: bool) {
+        self.config.offline = offline;
+    }
+}
 
 impl DataInterface for DataManager {
     fn load_race_data(&self, season: u32, gp: &str) -> Result<Race> {
-        load_race_data(season, gp)
+        load_race_data(&self.config, season, gp)
     }
 
     fn load_qualifying_data(&self, season: u32, gp: &str) -> Result<Vec<QualifyingResult>> {
-        load_qualifying_data(season, gp)
+        load_qualifying_data(&self.config, season, gp)
     }
 
     fn load_practice_data(&self, season: u32, gp: &str, practice_number: u32) -> Result<Vec<PracticeResult>> {
-        load_practice_data(season, gp, practice_number)
+        load_practice_data(&self.config, season, gp, practice_number)
+    }
+
+    fn load_sprint_data(&self, season: u32, gp: &str) -> Result<Vec<SprintResult>> {
+        load_sprint_data(&self.config, season, gp)
+    }
+
+    fn load_lap_data(&self, season: u32, gp: &str) -> Result<Vec<Lap>> {
+        load_lap_data(&self.config, season, gp)
+    }
+
+    fn load_pit_stops(&self, season: u32, gp: &str) -> Result<Vec<PitStop>> {
+        load_pit_stops(&self.config, season, gp)
     }
 }
 
-/// Initialize data directory if it doesn't exist
-fn ensure_data_dir() -> Result<()> {
-    let path = Path::new(DATA_DIR);
+/// Data directories this process has already migrated
+static MIGRATED_DIRS: OnceLock<Mutex<HashSet<PathBuf>>> = OnceLock::new();
+
+/// Initialize data directory if it doesn't exist and migrate older cache layouts,
+/// scanning each directory once per process
+fn ensure_data_dir(config: &DataConfig) -> Result<()> {
+    let path = config.data_dir.as_path();
     if !path.exists() {
         fs::create_dir_all(path)?;
     }
+    
+    let mut migrated_dirs = MIGRATED_DIRS.get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if migrated_dirs.contains(path) {
+        return Ok(());
+    }
+    
+    let migrated = migrate_circuit_keyed_files(path)?;
+    if migrated > 0 {
+        println!("{}", format!("Migrated {} cached files to the round-based layout", migrated).yellow());
+    }
+    migrated_dirs.insert(path.to_path_buf());
     Ok(())
 }
 
-/// Get the file path for a season's data
-fn get_season_data_path(season: u32) -> String {
-    format!("{}/season_{}.json", DATA_DIR, season)
+/// Get the file path for a season's schedule
+fn get_season_data_path(config: &DataConfig, season: u32) -> PathBuf {
+    config.data_dir.join(format!("season_{}.json", season))
 }
 
 /// Get the file path for a specific race's data
-fn get_race_data_path(season: u32, gp_name: &str) -> String {
-    format!("{}/race_{}_{}.json", DATA_DIR, season, gp_name)
+fn get_race_data_path(config: &DataConfig, season: u32, round: u32) -> PathBuf {
+    config.data_dir.join(round_file_name("race", season, round))
 }
 
 /// Get the file path for qualifying data
-fn get_qualifying_data_path(season: u32, gp_name: &str) -> String {
-    format!("{}/qualifying_{}_{}.json", DATA_DIR, season, gp_name)
+fn get_qualifying_data_path(config: &DataConfig, season: u32, round: u32) -> PathBuf {
+    config.data_dir.join(round_file_name("qualifying", season, round))
+}
+
+/// Get the file path for sprint data
+fn get_sprint_data_path(config: &DataConfig, season: u32, round: u32) -> PathBuf {
+    config.data_dir.join(round_file_name("sprint", season, round))
 }
 
-/// Get the file path for practice data
-fn get_practice_data_path(season: u32, gp_name: &str, practice_number: u32) -> String {
-    format!("{}/practice{}_{}_{}.json", DATA_DIR, practice_number, season, gp_name)
+/// Get the file path for a season's entry list
+fn get_roster_data_path(config: &DataConfig, season: u32) -> PathBuf {
+    config.data_dir.join(format!("roster_{}.json", season))
+}
+
+/// The round index of a season, fetching the schedule if it is not cached yet
+pub fn load_schedule(config: &DataConfig, season: u32) -> Result<SeasonIndex> {
+    if let Some(index) = load_index(&config.data_dir, season)? {
+        return Ok(index);
+    }
+    if config.offline {
+        return Err(DataError::not_cached(format!("The {} schedule", season), season, "schedule").into());
+    }
+    
+    ensure_data_dir(config)?;
+    fetch_season_schedule(config, &FetchClient::new(&config.fetch)?, season)?
+        .ok_or_else(|| anyhow::anyhow!("No schedule found for season {}", season))
+}
+
+/// Resolve a GP name, circuit id, alias or round number to a round of the season,
+/// fetching the season schedule if it is not cached yet
+fn resolve_round(config: &DataConfig, season: u32, gp: &str) -> Result<u32> {
+    let index = match load_index(&config.data_dir, season)? {
+        Some(index) if !index.find_rounds(gp).is_empty() || config.offline => index,
+        None if config.offline => {
+            return Err(DataError::not_cached(format!("The {} schedule", season), season, "schedule").into());
+        },
+        _ => fetch_season_schedule(config, &FetchClient::new(&config.fetch)?, season)?
+            .ok_or_else(|| anyhow::anyhow!("No schedule found for season {}", season))?,
+    };
+    
+    index.resolve(gp)
+}
+
+/// Fetch a season's schedule and store it together with its round index.
+///
+/// Returns `None` when the API has no schedule for the season yet.
+fn fetch_season_schedule(config: &DataConfig, client: &FetchClient, season: u32) -> Result<Option<SeasonIndex>> {
+    let schedule_url = format!("{}/{}.json", config.api_base_url, season);
+    let races = fetch_all_pages(client, &schedule_url, "RaceTable", "Races")?;
+    
+    if races.is_empty() {
+        return Ok(None);
+    }
+    
+    fs::write(get_season_data_path(config, season), serde_json::to_string_pretty(&races)?)?;
+    
+    let index = SeasonIndex::from_races(season, &races);
+    save_index(&config.data_dir, &index)?;
+    
+    Ok(Some(index))
 }
 
 /// List available race data
-pub fn list_available_data(filter_season: Option<u32>) -> Result<()> {
-    ensure_data_dir()?;
+pub fn list_available_data(config: &DataConfig, filter_season: Option<u32>) -> Result<()> {
+    ensure_data_dir(config)?;
     
-    let data_dir = Path::new(DATA_DIR);
+    let data_dir = config.data_dir.as_path();
     
     // Check if data directory exists
     if !data_dir.exists() {
@@ -79,14 +198,14 @@ pub fn list_available_data(filter_season: Option<u32>) -> Result<()> {
     }
     
     let mut has_data = false;
-    let mut seasons: HashMap<u32, Vec<String>> = HashMap::new();
+    let mut seasons: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
     
     // Go through data directory and catalog files
     for entry in fs::read_dir(data_dir)? {
         let entry = entry?;
         let file_name = entry.file_name().into_string().unwrap_or_default();
         
-        // Season data files
+        // Season schedule files
         if file_name.starts_with("season_") && file_name.ends_with(".json") {
             let season: u32 = file_name
                 .replace("season_", "")
@@ -95,28 +214,16 @@ pub fn list_available_data(filter_season: Option<u32>) -> Result<()> {
                 .unwrap_or(0);
                 
             if season > 0 && (filter_season.is_none() || filter_season == Some(season)) {
-                seasons.entry(season).or_insert_with(Vec::new);
+                seasons.entry(season).or_default();
                 has_data = true;
             }
         }
         
         // Race data files
-        else if file_name.starts_with("race_") && file_name.ends_with(".json") {
-            let file_name_string = file_name
-                .replace("race_", "")
-                .replace(".json", "");
-            let parts: Vec<&str> = file_name_string
-                .split('_')
-                .collect();
-                
-            if parts.len() >= 2 {
-                if let Ok(season) = parts[0].parse::<u32>() {
-                    if filter_season.is_none() || filter_season == Some(season) {
-                        let gp = parts[1..].join("_");
-                        seasons.entry(season).or_insert_with(Vec::new).push(gp);
-                        has_data = true;
-                    }
-                }
+        else if let Some(("race", season, round)) = parse_round_file_name(&file_name) {
+            if filter_season.is_none() || filter_season == Some(season) {
+                seasons.entry(season).or_default().push(round);
+                has_data = true;
             }
         }
     }
@@ -131,15 +238,22 @@ pub fn list_available_data(filter_season: Option<u32>) -> Result<()> {
     }
     
     // Print found data
-    for (season, gp_list) in seasons.iter().filter(|(s, _)| filter_season.is_none() || filter_season == Some(**s)) {
+    for (season, rounds) in seasons.iter_mut() {
         println!("\n{} {}", "Season".green(), season.to_string().green().bold());
         println!("{}", "-".repeat(40));
         
-        if gp_list.is_empty() {
+        if rounds.is_empty() {
             println!("  {}", "Season data available, no specific races downloaded".italic());
-        } else {
-            for gp in gp_list {
-                println!("  • {}", gp.replace("_", " ").to_uppercase());
+            continue;
+        }
+        
+        rounds.sort();
+        let index = load_index(data_dir, *season)?;
+        
+        for round in rounds.iter() {
+            match index.as_ref().and_then(|i| i.get(*round)) {
+                Some(entry) => println!("  • Round {:>2} - {} ({})", round, entry.race_name, entry.circuit_id),
+                None => println!("  • Round {:>2}", round),
             }
         }
     }
@@ -148,7 +262,7 @@ pub fn list_available_data(filter_season: Option<u32>) -> Result<()> {
 }
 
 /// Determine which F1 seasons to fetch based on provided options
-fn determine_seasons_to_fetch(
+pub fn determine_seasons_to_fetch(
     previous: Option<u32>, 
     specific_seasons: Option<String>, 
     all: bool,
@@ -156,7 +270,7 @@ fn determine_seasons_to_fetch(
 ) -> Vec<u32> {
     if all {
         // Return all seasons from 1950 to current
-        return (1950..=current_season).collect();
+        return (FIRST_SEASON..=current_season).collect();
     }
     
     if let Some(specific) = specific_seasons {
@@ -169,28 +283,131 @@ fn determine_seasons_to_fetch(
     
     if let Some(prev_count) = previous {
         // Return current season and specified number of previous seasons
-        let start_season = current_season.saturating_sub(prev_count);
+        let start_season = current_season.saturating_sub(prev_count).max(FIRST_SEASON);
         return (start_season..=current_season).collect();
     }
     
     // Default behavior - current and last 2 seasons
-    vec![current_season - 2, current_season - 1, current_season]
+    let start_season = current_season.saturating_sub(2).max(FIRST_SEASON);
+    (start_season..=current_season).collect()
+}
+
+/// Find the next race taking place on or after `today`.
+///
+/// Without an explicit season the search starts at the current season and moves on to
+/// the following one once its last race has been run. Schedules are read from the cache
+/// and fetched only when missing; offline, a following season that is not cached has
+/// no upcoming race.
+pub fn next_race(config: &DataConfig, season: Option<u32>, today: NaiveDate) -> Result<(u32, RoundEntry)> {
+    ensure_data_dir(config)?;
+    
+    let seasons = match season {
+        Some(season) => vec![season],
+        None => {
+            let current = config.current_season_at(today);
+            vec![current, current + 1]
+        },
+    };
+    
+    let client = FetchClient::new(&config.fetch)?;
+    for (i, &season) in seasons.iter().enumerate() {
+        let index = match load_index(&config.data_dir, season)? {
+            Some(index) => index,
+            None if config.offline && i > 0 => continue,
+            None if config.offline => {
+                return Err(DataError::not_cached(format!("The {} schedule", season), season, "schedule").into());
+            },
+            None => match fetch_season_schedule(config, &client, season) {
+                Ok(Some(index)) => index,
+                Ok(None) | Err(_) => continue,
+            },
+        };
+        
+        if let Some(entry) = index.next_round(today) {
+            return Ok((season, entry.clone()));
+        }
+    }
+    
+    Err(anyhow::anyhow!(
+        "No upcoming race found in the {} calendar. Use --gp and --season to pick a race.",
+        seasons.iter().map(|s| s.to_string()).collect::<Vec<_>>().join("/")
+    ))
+}
+
+/// A fetch that still failed after every retry during an update
+#[derive(Debug, Clone)]
+pub struct FetchFailure {
+    pub season: u32,
+    /// What was being fetched, e.g. "season 2021 results"
+    pub what: String,
+    pub error: String,
+}
+
+/// The `update` command that retries every season with a failed fetch
+pub fn resume_command(failures: &[FetchFailure]) -> String {
+    let mut seasons: Vec<u32> = failures.iter().map(|f| f.season).collect();
+    seasons.sort_unstable();
+    seasons.dedup();
+    
+    format!(
+        "f1-cli-simulator update --seasons {}",
+        seasons.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(",")
+    )
+}
+
+/// Print the fetches that failed and how to resume them
+fn print_failure_summary(failures: &[FetchFailure]) {
+    println!("\n{}", format!("{} fetches failed:", failures.len()).red().bold());
+    for failure in failures {
+        println!("  • {}: {}", failure.what, failure.error);
+    }
+    println!("{} {}", "Resume with:".yellow(), resume_command(failures).bold());
+}
+
+/// Options of the `update` command
+#[derive(Debug, Clone, Default)]
+pub struct UpdateOptions {
+    /// Number of previous seasons to fetch in addition to the current one
+    pub previous: Option<u32>,
+    /// Specific comma-separated seasons to fetch
+    pub seasons: Option<String>,
+    /// Fetch every season from 1950 onwards
+    pub all: bool,
+    /// Refetch everything in range, even data the manifest marks as complete
+    pub force: bool,
+    /// Refetch rounds raced on or after this date
+    pub since: Option<NaiveDate>,
 }
 
 /// Update F1 race data from the Ergast API
-pub fn update_data(
-    previous: Option<u32>,
-    specific_seasons: Option<String>,
-    all: bool
-) -> Result<()> {
-    ensure_data_dir()?;
+pub fn update_data(config: &DataConfig, options: &UpdateOptions) -> Result<()> {
+    update_data_at(config, options, Utc::now().date_naive())
+}
+
+/// Update F1 race data as of `today`, fetching only what the manifest lacks
+pub fn update_data_at(config: &DataConfig, options: &UpdateOptions, today: NaiveDate) -> Result<()> {
+    if config.offline {
+        return Err(anyhow::anyhow!("Updating downloads data from the API and cannot run in offline mode"));
+    }
     
-    let client = Client::new();
+    ensure_data_dir(config)?;
+    
+    // One client for the whole run so the rate limit covers every request
+    let client = FetchClient::new(&config.fetch)?;
+    let mut manifest = Manifest::load(&config.data_dir)?;
+    let mut failures: Vec<FetchFailure> = Vec::new();
     
     println!("{}", "Updating F1 race data...".green());
     
     // Determine which seasons to fetch based on provided options
-    let seasons_to_fetch = determine_seasons_to_fetch(previous, specific_seasons, all, CURRENT_SEASON);
+    let current_season = config.current_season();
+    let seasons_to_fetch = match options.since {
+        // --since on its own covers every season from that date onwards
+        Some(since) if options.previous.is_none() && options.seasons.is_none() && !options.all => {
+            (since.year() as u32..=current_season).collect()
+        },
+        _ => determine_seasons_to_fetch(options.previous, options.seasons.clone(), options.all, current_season),
+    };
     
     println!("{} {}", 
         "Seasons to fetch:".blue(),
@@ -203,301 +420,445 @@ pub fn update_data(
     );
     
     for season in seasons_to_fetch {
-        println!("\n{} {}", "Fetching data for season".blue(), season.to_string().blue().bold());
-        
-        // Create a progress bar
-        let pb = ProgressBar::new_spinner();
-        pb.set_style(
-            ProgressStyle::default_spinner()
-                .template("{spinner:.green} {msg}")
-                .unwrap()
-        );
-        pb.set_message(format!("Fetching season {} schedule...", season));
-        pb.enable_steady_tick(std::time::Duration::from_millis(100));
-        
-        // Fetch season schedule
-        let season_url = format!("{}/{}/circuits.json", API_BASE_URL, season);
-        let season_response = client.get(&season_url).send()
-            .with_context(|| format!("Failed to fetch season {} data", season))?;
-            
-        if !season_response.status().is_success() {
-            pb.finish_with_message(format!("Season {} data not available (status: {})", season, season_response.status()));
+        let reopened = options.since.is_some_and(|since| since.year() as u32 <= season);
+        if manifest.is_final(season) && !options.force && !reopened && season_fully_fetched(config, &manifest, season) {
+            println!("\n{}", format!("Season {} is complete, skipping (use --force to refetch)", season).dimmed());
             continue;
         }
         
-        let season_data: Value = season_response.json()?;
-        
-        // Extract circuit data
-        let circuits = match season_data.get("MRData")
-            .and_then(|d| d.get("CircuitTable"))
-            .and_then(|t| t.get("Circuits"))
-        {
-            Some(circuits) => circuits,
-            None => {
-                pb.finish_with_message(format!("No circuit data found for season {}", season));
-                continue;
-            }
-        };
+        println!("\n{} {}", "Fetching data for season".blue(), season.to_string().blue().bold());
         
-        // Save season data
-        let season_path = get_season_data_path(season);
-        fs::write(&season_path, serde_json::to_string_pretty(&circuits)?)?;
-        pb.finish_with_message(format!("Saved season {} data", season));
+        update_season(config, &client, &mut manifest, season, options, today, &mut failures)?;
         
-        // Fetch data for each race
-        if let Some(circuits_array) = circuits.as_array() {
-            for circuit in circuits_array {
-                if let Some(circuit_id) = circuit.get("circuitId").and_then(|id| id.as_str()) {
-                    let pb = ProgressBar::new_spinner();
-                    pb.set_style(
-                        ProgressStyle::default_spinner()
-                            .template("{spinner:.green} {msg}")
-                            .unwrap()
-                    );
-                    pb.set_message(format!("Fetching data for {} GP...", circuit_id));
-                    pb.enable_steady_tick(std::time::Duration::from_millis(100));
-                    
-                    // Fetch race results
-                    let race_url = format!("{}/{}/circuits/{}/results.json", API_BASE_URL, season, circuit_id);
-                    let race_response = client.get(&race_url).send();
-                    
-                    match race_response {
-                        Ok(response) if response.status().is_success() => {
-                            let race_data: Value = response.json()?;
-                            if let Some(races) = race_data.get("MRData")
-                                .and_then(|d| d.get("RaceTable"))
-                                .and_then(|t| t.get("Races"))
-                            {
-                                let race_path = get_race_data_path(season, circuit_id);
-                                fs::write(&race_path, serde_json::to_string_pretty(&races)?)?;
-                                pb.finish_with_message(format!("Saved data for {} GP", circuit_id));
-                            } else {
-                                pb.finish_with_message(format!("No race data found for {} GP", circuit_id));
-                            }
-                        },
-                        _ => pb.finish_with_message(format!("Failed to fetch data for {} GP", circuit_id)),
-                    }
-                }
-            }
-        }
+        // Save after every season so an interrupted run can pick up where it stopped
+        manifest.save(&config.data_dir)?;
+    }
+    
+    if !failures.is_empty() {
+        print_failure_summary(&failures);
+        return Err(anyhow::anyhow!(
+            "{} fetches failed. Resume with `{}`",
+            failures.len(), resume_command(&failures)
+        ));
     }
     
     println!("\n{}", "F1 race data update completed".green().bold());
     Ok(())
 }
 
-/// Fetch data for a specific race from the Ergast API
-fn fetch_race_data(client: &Client, season: u32, gp: &str) -> Result<()> {
-    println!("{}", format!("Race data for {} GP {} not found locally, fetching from API...", gp, season).yellow());
-    
-    // Create a progress bar
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
-            .unwrap()
-    );
-    pb.set_message(format!("Fetching data for {} GP {}...", gp, season));
-    pb.enable_steady_tick(std::time::Duration::from_millis(100));
+/// A session of a round that the API serves and the cache keeps in a file of its own
+#[derive(Debug, Clone, Copy)]
+struct RoundSession<'a> {
+    /// Session name used in the manifest and as the cache file prefix
+    name: &'a str,
+    /// Ergast endpoint, e.g. `results` for `/{season}/{round}/results.json`
+    endpoint: &'a str,
+    /// Result list inside each race entry
+    list: &'a str,
+    /// What the session holds, for messages
+    label: &'a str,
+}
+
+const RACE: RoundSession<'static> = RoundSession { name: "race", endpoint: "results", list: "Results", label: "race data" };
+const QUALIFYING: RoundSession<'static> = RoundSession { name: "qualifying", endpoint: "qualifying", list: "QualifyingResults", label: "qualifying data" };
+const SPRINT: RoundSession<'static> = RoundSession { name: "sprint", endpoint: "sprint", list: "SprintResults", label: "sprint data" };
+// Lap timings fill only a few laps per page, so a round walks many pages
+const LAPS: RoundSession<'static> = RoundSession { name: "laps", endpoint: "laps", list: "Laps", label: "lap data" };
+const PIT_STOPS: RoundSession<'static> = RoundSession { name: "pitstops", endpoint: "pitstops", list: "PitStops", label: "pit stop data" };
+
+/// A session downloaded for every round by `update`
+struct BulkSession {
+    session: RoundSession<'static>,
+    /// First season the API has the session for
+    first_season: u32,
+    /// The API serves the session only round by round, never for a whole season
+    per_round: bool,
+}
+
+/// Sessions fetched by `update`, in order
+const BULK_SESSIONS: [BulkSession; 5] = [
+    BulkSession { session: RACE, first_season: FIRST_SEASON, per_round: false },
+    BulkSession { session: QUALIFYING, first_season: FIRST_SEASON, per_round: false },
+    BulkSession { session: SPRINT, first_season: FIRST_SEASON, per_round: false },
+    BulkSession { session: LAPS, first_season: 1996, per_round: true },
+    BulkSession { session: PIT_STOPS, first_season: 2011, per_round: true },
+];
+
+/// Whether `update` downloads a session ("schedule" for the season schedule)
+/// for a season
+fn fetched_by_update(season: u32, session: &str) -> bool {
+    session == "schedule" || BULK_SESSIONS.iter().any(|b| b.session.name == session && season >= b.first_season)
+}
+
+/// Fetch the schedule and any missing sessions of one season
+fn update_season(
+    config: &DataConfig,
+    client: &FetchClient,
+    manifest: &mut Manifest,
+    season: u32,
+    options: &UpdateOptions,
+    today: NaiveDate,
+    failures: &mut Vec<FetchFailure>,
+) -> Result<()> {
+    let pb = spinner(format!("Fetching season {} schedule...", season));
     
-    // First, we need to determine the correct circuit ID
-    let circuit_id = normalize_gp_name(gp);
-    
-    // Fetch race results
-    let race_url = format!("{}/{}/circuits/{}/results.json", API_BASE_URL, season, circuit_id);
-    let race_response = client.get(&race_url).send();
-    
-    match race_response {
-        Ok(response) if response.status().is_success() => {
-            let race_data: Value = response.json()?;
-            if let Some(races) = race_data.get("MRData")
-                .and_then(|d| d.get("RaceTable"))
-                .and_then(|t| t.get("Races"))
-            {
-                let race_path = get_race_data_path(season, &circuit_id);
-                fs::write(&race_path, serde_json::to_string_pretty(&races)?)?;
-                pb.finish_with_message(format!("Successfully fetched data for {} GP {}", gp, season));
-                Ok(())
-            } else {
-                pb.finish_with_message(format!("No race data found for {} GP {}", gp, season));
-                Err(anyhow::anyhow!("No race data found for {} GP {}", gp, season))
-            }
-        },
-        Ok(response) => {
-            pb.finish_with_message(format!("Failed to fetch data for {} GP {} (status: {})", gp, season, response.status()));
-            Err(anyhow::anyhow!("API returned error status: {}", response.status()))
+    // Fetch season schedule and round index
+    let index = match fetch_season_schedule(config, client, season) {
+        Ok(Some(index)) => index,
+        Ok(None) => {
+            pb.finish_with_message(format!("Season {} data not available", season));
+            return Ok(());
         },
         Err(e) => {
-            pb.finish_with_message(format!("Failed to connect to API for {} GP {}", gp, season));
-            Err(anyhow::anyhow!("Failed to connect to API: {}", e))
+            pb.finish_with_message(format!("Failed to fetch season {} schedule", season));
+            failures.push(FetchFailure {
+                season,
+                what: format!("season {} schedule", season),
+                error: e.to_string(),
+            });
+            return Ok(());
+        }
+    };
+    manifest.record_schedule(season, Utc::now());
+    pb.finish_with_message(format!("Saved season {} schedule ({} rounds)", season, index.rounds.len()));
+    
+    // Rounds that have been run
+    let raced: Vec<&RoundEntry> = index.rounds.iter()
+        .filter(|r| r.race_date().is_none_or(|date| date <= today))
+        .collect();
+    
+    for bulk in BULK_SESSIONS.iter().filter(|b| season >= b.first_season) {
+        // Only sprint weekends have a sprint
+        let expected: Vec<&RoundEntry> = raced.iter()
+            .filter(|r| bulk.session.name != "sprint" || r.sprint)
+            .copied()
+            .collect();
+        let failed = failures.len();
+        update_session(config, client, manifest, season, bulk, &expected, options, failures)?;
+        if failures.len() == failed {
+            manifest.record_season_session(season, bulk.session.name);
         }
     }
+    
+    // A season is final once its last race has been run and every race result is cached
+    let finished = index.rounds.iter().all(|r| r.race_date().is_some_and(|date| date < today));
+    let complete = index.rounds.iter().all(|r| is_cached(config, manifest, season, r.round, "race"));
+    manifest.set_final(season, finished && complete);
+    
+    Ok(())
 }
 
-/// Fetch qualifying data for a specific race from the Ergast API
-fn fetch_qualifying_data(client: &Client, season: u32, gp: &str) -> Result<()> {
-    println!("{}", format!("Qualifying data for {} GP {} not found locally, fetching from API...", gp, season).yellow());
+/// Fetch one session for the rounds of a season that are missing, stale or forced
+#[allow(clippy::too_many_arguments)]
+fn update_session(
+    config: &DataConfig,
+    client: &FetchClient,
+    manifest: &mut Manifest,
+    season: u32,
+    bulk: &BulkSession,
+    expected: &[&RoundEntry],
+    options: &UpdateOptions,
+    failures: &mut Vec<FetchFailure>,
+) -> Result<()> {
+    if expected.is_empty() {
+        return Ok(());
+    }
     
-    // Create a progress bar
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
-            .unwrap()
-    );
-    pb.set_message(format!("Fetching qualifying data for {} GP {}...", gp, season));
-    pb.enable_steady_tick(std::time::Duration::from_millis(100));
+    let session = &bulk.session;
+    let pending: Vec<u32> = expected.iter()
+        .filter(|r| {
+            options.force
+                || options.since.is_some_and(|since| r.race_date().is_none_or(|date| date >= since))
+                || !is_cached(config, manifest, season, r.round, session.name)
+        })
+        .map(|r| r.round)
+        .collect();
+    
+    if pending.is_empty() {
+        println!("  {}", format!("{} data for all {} rounds is cached", capitalize(session.name), expected.len()).dimmed());
+        return Ok(());
+    }
     
-    // First, we need to determine the correct circuit ID
-    let circuit_id = normalize_gp_name(gp);
-    
-    // Fetch qualifying results
-    let qualifying_url = format!("{}/{}/circuits/{}/qualifying.json", API_BASE_URL, season, circuit_id);
-    let qualifying_response = client.get(&qualifying_url).send();
-    
-    match qualifying_response {
-        Ok(response) if response.status().is_success() => {
-            let qualifying_data: Value = response.json()?;
-            if let Some(races) = qualifying_data.get("MRData")
-                .and_then(|d| d.get("RaceTable"))
-                .and_then(|t| t.get("Races"))
-            {
-                let qualifying_path = get_qualifying_data_path(season, &circuit_id);
-                fs::write(&qualifying_path, serde_json::to_string_pretty(&races)?)?;
-                pb.finish_with_message(format!("Successfully fetched qualifying data for {} GP {}", gp, season));
-                Ok(())
-            } else {
-                pb.finish_with_message(format!("No qualifying data found for {} GP {}", gp, season));
-                Err(anyhow::anyhow!("No qualifying data found for {} GP {}", gp, season))
+    let pb = spinner(format!("Fetching season {} {} data for {} rounds...", season, session.name, pending.len()));
+    
+    // A session without any cached round is read in one paginated walk,
+    // otherwise only the missing rounds are requested
+    let urls: Vec<String> = if pending.len() == expected.len() && !bulk.per_round {
+        vec![format!("{}/{}/{}.json", config.api_base_url, season, session.endpoint)]
+    } else {
+        pending.iter()
+            .map(|round| format!("{}/{}/{}/{}.json", config.api_base_url, season, round, session.endpoint))
+            .collect()
+    };
+    
+    let mut saved = 0;
+    for url in urls {
+        let races = match fetch_all_pages(client, &url, "RaceTable", "Races") {
+            Ok(races) => races,
+            Err(e) => {
+                failures.push(FetchFailure {
+                    season,
+                    what: format!("season {} {} ({})", season, session.name, url.trim_start_matches(config.api_base_url.as_str())),
+                    error: e.to_string(),
+                });
+                continue;
             }
-        },
-        Ok(response) => {
-            pb.finish_with_message(format!("Failed to fetch qualifying data for {} GP {} (status: {})", gp, season, response.status()));
-            Err(anyhow::anyhow!("API returned error status: {}", response.status()))
-        },
-        Err(e) => {
-            pb.finish_with_message(format!("Failed to connect to API for {} GP {} qualifying", gp, season));
-            Err(anyhow::anyhow!("Failed to connect to API: {}", e))
+        };
+        
+        for race in &races {
+            let Some(round) = parse_round(race).filter(|r| pending.contains(r)) else {
+                continue;
+            };
+            let rows = count_rows(race, session.list);
+            if rows == 0 {
+                continue;
+            }
+            let path = config.data_dir.join(round_file_name(session.name, season, round));
+            fs::write(path, serde_json::to_string_pretty(&vec![race])?)?;
+            manifest.record(season, round, session.name, rows, Utc::now());
+            saved += 1;
         }
     }
+    
+    pb.finish_with_message(format!("Saved {} data for {} of {} rounds", session.name, saved, pending.len()));
+    Ok(())
 }
 
-/// Fetch practice data for a specific race from the Ergast API
-fn fetch_practice_data(client: &Client, season: u32, gp: &str, practice_number: u32) -> Result<()> {
-    println!("{}", format!("Practice data for {} GP {} FP{} not found locally, fetching from API...", gp, season, practice_number).yellow());
-    
-    // Create a progress bar
+/// Upper-case the first letter of a word
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Whether a session is recorded in the manifest with rows and still present on disk
+fn is_cached(config: &DataConfig, manifest: &Manifest, season: u32, round: u32, session: &str) -> bool {
+    manifest.session(season, round, session).is_some_and(|record| record.rows > 0)
+        && config.data_dir.join(round_file_name(session, season, round)).exists()
+}
+
+/// Whether every session the manifest lists for a season is still on disk
+fn season_files_present(config: &DataConfig, manifest: &Manifest, season: u32) -> bool {
+    manifest.season(season).is_some_and(|s| {
+        s.rounds.iter().all(|(round, sessions)| {
+            sessions.keys().all(|session| config.data_dir.join(round_file_name(session, season, *round)).exists())
+        })
+    })
+}
+
+/// Whether `update` has fetched every session of a season the API has and
+/// every file the manifest lists for it is still on disk
+fn season_fully_fetched(config: &DataConfig, manifest: &Manifest, season: u32) -> bool {
+    BULK_SESSIONS.iter()
+        .filter(|b| season >= b.first_season)
+        .all(|b| manifest.has_season_session(season, b.session.name))
+        && season_files_present(config, manifest, season)
+}
+
+/// Number of rows in a race entry's result list, e.g. `Results` or `QualifyingResults`
+fn count_rows(race: &Value, list: &str) -> usize {
+    race.get(list)
+        .and_then(|l| l.as_array())
+        .map(|l| l.len())
+        .unwrap_or(0)
+}
+
+/// Note a lazily fetched session in the manifest
+fn record_fetch(config: &DataConfig, season: u32, round: u32, session: &str, rows: usize) -> Result<()> {
+    let mut manifest = Manifest::load(&config.data_dir)?;
+    manifest.record(season, round, session, rows, Utc::now());
+    manifest.save(&config.data_dir)
+}
+
+/// A spinner showing a message until it is finished
+fn spinner(message: String) -> ProgressBar {
     let pb = ProgressBar::new_spinner();
     pb.set_style(
         ProgressStyle::default_spinner()
             .template("{spinner:.green} {msg}")
             .unwrap()
     );
-    pb.set_message(format!("Fetching FP{} data for {} GP {}...", practice_number, gp, season));
+    pb.set_message(message);
     pb.enable_steady_tick(std::time::Duration::from_millis(100));
+    pb
+}
+
+/// Fetch something missing from the cache behind a spinner, e.g. "race data for
+/// monza GP 2023". Finding nothing is an error.
+fn fetch_missing(what: &str, fetch: impl FnOnce() -> Result<Vec<Value>>) -> Result<Vec<Value>> {
+    println!("{}", format!("{} not found locally, fetching from API...", capitalize(what)).yellow());
+    let pb = spinner(format!("Fetching {}...", what));
     
-    // First, we need to determine the correct circuit ID
-    let circuit_id = normalize_gp_name(gp);
-    
-    // Determine the practice session from the number
-    let session = match practice_number {
-        1 => "fp1",
-        2 => "fp2",
-        3 => "fp3",
-        _ => return Err(anyhow::anyhow!("Invalid practice session number: {}", practice_number)),
-    };
-    
-    // Fetch practice results
-    let practice_url = format!("{}/{}/circuits/{}/{}/results.json", API_BASE_URL, season, circuit_id, session);
-    let practice_response = client.get(&practice_url).send();
-    
-    match practice_response {
-        Ok(response) if response.status().is_success() => {
-            let practice_data: Value = response.json()?;
-            if let Some(races) = practice_data.get("MRData")
-                .and_then(|d| d.get("RaceTable"))
-                .and_then(|t| t.get("Races"))
-            {
-                let practice_path = get_practice_data_path(season, &circuit_id, practice_number);
-                fs::write(&practice_path, serde_json::to_string_pretty(&races)?)?;
-                pb.finish_with_message(format!("Successfully fetched FP{} data for {} GP {}", practice_number, gp, season));
-                Ok(())
-            } else {
-                pb.finish_with_message(format!("No FP{} data found for {} GP {}", practice_number, gp, season));
-                Err(anyhow::anyhow!("No FP{} data found for {} GP {}", practice_number, gp, season))
-            }
+    match fetch() {
+        Ok(entries) if !entries.is_empty() => {
+            pb.finish_with_message(format!("Successfully fetched {}", what));
+            Ok(entries)
         },
-        Ok(response) => {
-            pb.finish_with_message(format!("Failed to fetch FP{} data for {} GP {} (status: {})", practice_number, gp, season, response.status()));
-            Err(anyhow::anyhow!("API returned error status: {}", response.status()))
+        Ok(_) => {
+            pb.finish_with_message(format!("No {} found", what));
+            Err(anyhow::anyhow!("No {} found", what))
         },
         Err(e) => {
-            pb.finish_with_message(format!("Failed to connect to API for {} GP {} FP{}", gp, season, practice_number));
-            Err(anyhow::anyhow!("Failed to connect to API: {}", e))
+            pb.finish_with_message(format!("Failed to fetch {}", what));
+            Err(e)
         }
     }
 }
 
-/// Load race data for a specific GP
-pub fn load_race_data(season: u32, gp: &str) -> Result<Race> {
-    ensure_data_dir()?;
-    let normalized_gp = normalize_gp_name(gp);
-    let file_path = get_race_data_path(season, &normalized_gp);
+/// Fetch one session of a round from the Ergast API into the cache
+fn fetch_round_session(config: &DataConfig, client: &FetchClient, season: u32, round: u32, gp: &str, session: &RoundSession) -> Result<()> {
+    let url = format!("{}/{}/{}/{}.json", config.api_base_url, season, round, session.endpoint);
+    let races = fetch_missing(&format!("{} for {} GP {}", session.label, gp, season), || {
+        fetch_all_pages(client, &url, "RaceTable", "Races")
+    })?;
+    
+    fs::write(config.data_dir.join(round_file_name(session.name, season, round)), serde_json::to_string_pretty(&races)?)?;
+    record_fetch(config, season, round, session.name, count_rows(&races[0], session.list))
+}
+
+/// Fetch a season's entry list: every constructor with the drivers entered for it
+fn fetch_roster(config: &DataConfig, client: &FetchClient, season: u32) -> Result<()> {
+    let entries = fetch_missing(&format!("entry list for {}", season), || {
+        let constructors_url = format!("{}/{}/constructors.json", config.api_base_url, season);
+        let mut entries = Vec::new();
+        for mut constructor in fetch_all_pages(client, &constructors_url, "ConstructorTable", "Constructors")? {
+            let id = constructor.get("constructorId").and_then(|id| id.as_str()).unwrap_or_default().to_string();
+            let drivers_url = format!("{}/{}/constructors/{}/drivers.json", config.api_base_url, season, id);
+            let drivers = fetch_all_pages(client, &drivers_url, "DriverTable", "Drivers")?;
+            constructor["Drivers"] = Value::Array(drivers);
+            entries.push(constructor);
+        }
+        Ok(entries)
+    })?;
+    
+    fs::write(get_roster_data_path(config, season), serde_json::to_string_pretty(&entries)?)?;
+    Ok(())
+}
+
+/// Read the round number of an Ergast race entry
+fn parse_round(race: &Value) -> Option<u32> {
+    race.get("round")
+        .and_then(|r| r.as_str())
+        .and_then(|r| r.parse::<u32>().ok())
+}
+
+/// The race entry of one session of a round, read from the cache and fetched
+/// first if it is missing, along with the round. Offline, a missing session is
+/// a `DataError::NotCached`.
+fn load_round_session(config: &DataConfig, season: u32, gp: &str, session: &RoundSession) -> Result<(u32, Value)> {
+    ensure_data_dir(config)?;
+    let round = resolve_round(config, season, gp)?;
+    let file_path = config.data_dir.join(round_file_name(session.name, season, round));
     
     // If the file doesn't exist, attempt to fetch it
-    if !Path::new(&file_path).exists() {
-        let client = Client::new();
-        fetch_race_data(&client, season, gp)?;
+    if !file_path.exists() {
+        if config.offline {
+            return Err(DataError::not_cached(format!("{} for {} GP {}", capitalize(session.label), gp, season), season, session.name).into());
+        }
+        let client = FetchClient::new(&config.fetch)?;
+        fetch_round_session(config, &client, season, round, gp, session)?;
     }
     
-    // Now try to load the data (which should exist now if the fetch was successful)
-    if !Path::new(&file_path).exists() {
-        return Err(anyhow::anyhow!(
-            "Unable to retrieve race data for {} GP {}. Race may not exist or network issues occurred.",
-            gp, season
-        ));
+    let data: Value = serde_json::from_str(&fs::read_to_string(&file_path)?)?;
+    let race = data.as_array().and_then(|races| races.first()).cloned().unwrap_or(Value::Null);
+    Ok((round, race))
+}
+
+/// Load race data for a specific GP
+pub fn load_race_data(config: &DataConfig, season: u32, gp: &str) -> Result<Race> {
+    let (round, race) = load_round_session(config, season, gp, &RACE)?;
+    let circuits = load_circuits(config)?;
+    parse_race(&race, season, round, &normalize_gp_name(gp), &circuits)
+}
+
+/// Read a cached race results file into our model
+fn read_race_file(file_path: &Path, season: u32, round: u32, fallback_name: &str, circuits: &CircuitCatalogue) -> Result<Race> {
+    let race_data: Value = serde_json::from_str(&fs::read_to_string(file_path)?)?;
+    let race = race_data.as_array().and_then(|races| races.first()).unwrap_or(&Value::Null);
+    parse_race(race, season, round, fallback_name, circuits)
+}
+
+/// Parse a race entry with its results into our model
+fn parse_race(race: &Value, season: u32, round: u32, fallback_name: &str, circuits: &CircuitCatalogue) -> Result<Race> {
+    if race.is_null() {
+        return Err(anyhow::anyhow!("Failed to parse race data"));
     }
     
-    let data = fs::read_to_string(&file_path)?;
-    let race_data: Value = serde_json::from_str(&data)?;
+    let circuit = parse_circuit(race, circuits)?;
+    let results = parse_results(race)?;
     
-    // Process the race data into our model
-    if let Some(races) = race_data.as_array() {
-        if let Some(race) = races.first() {
-            let circuit = parse_circuit(race)?;
-            let results = parse_results(race)?;
-            
-            let race_name = race.get("raceName")
-                .and_then(|n| n.as_str())
-                .unwrap_or(&normalized_gp)
-                .to_string();
-                
-            let date = race.get("date")
-                .and_then(|d| d.as_str())
-                .unwrap_or("Unknown")
-                .to_string();
-                
-            let round = race.get("round")
-                .and_then(|r| r.as_str())
-                .and_then(|r| r.parse::<u32>().ok())
-                .unwrap_or(0);
-                
-            return Ok(Race {
-                season,
-                round,
-                name: race_name,
-                circuit,
-                date,
-                results,
-            });
+    let race_name = race.get("raceName")
+        .and_then(|n| n.as_str())
+        .unwrap_or(fallback_name)
+        .to_string();
+    
+    let date = race.get("date")
+        .and_then(|d| d.as_str())
+        .unwrap_or("Unknown")
+        .to_string();
+    
+    let round = parse_round(race).unwrap_or(round);
+    
+    Ok(Race {
+        season,
+        round,
+        name: race_name,
+        circuit,
+        date,
+        results,
+    })
+}
+
+/// Load the race, qualifying and sprint results of every round of a season that is in
+/// the cache, in round order. Nothing is fetched; rounds without cached race results are skipped.
+pub fn load_cached_results(config: &DataConfig, season: u32) -> Result<Vec<WeekendResults>> {
+    let Some(index) = load_index(&config.data_dir, season)? else {
+        return Ok(Vec::new());
+    };
+    
+    let circuits = load_circuits(config)?;
+    let mut weekends = Vec::new();
+    
+    for entry in &index.rounds {
+        let race_path = get_race_data_path(config, season, entry.round);
+        if !race_path.exists() {
+            continue;
         }
+        
+        let race = read_race_file(&race_path, season, entry.round, &entry.race_name, &circuits)?;
+        
+        let qualifying_path = get_qualifying_data_path(config, season, entry.round);
+        let qualifying = if qualifying_path.exists() {
+            let data: Value = serde_json::from_str(&fs::read_to_string(&qualifying_path)?)?;
+            match data.as_array().and_then(|races| races.first()) {
+                Some(qualifying_race) => parse_qualifying_results(qualifying_race)?,
+                None => Vec::new(),
+            }
+        } else {
+            Vec::new()
+        };
+        
+        let sprint_path = get_sprint_data_path(config, season, entry.round);
+        let sprint = if sprint_path.exists() {
+            let data: Value = serde_json::from_str(&fs::read_to_string(&sprint_path)?)?;
+            match data.as_array().and_then(|races| races.first()) {
+                Some(sprint_race) => parse_sprint_results(sprint_race, season)?,
+                None => Vec::new(),
+            }
+        } else {
+            Vec::new()
+        };
+        
+        weekends.push(WeekendResults { race, qualifying, sprint });
     }
     
-    Err(anyhow::anyhow!("Failed to parse race data"))
+    Ok(weekends)
 }
 
 /// Parse circuit information from race data
-fn parse_circuit(race: &Value) -> Result<Circuit> {
+fn parse_circuit(race: &Value, circuits: &CircuitCatalogue) -> Result<Circuit> {
     if let Some(circuit_data) = race.get("Circuit") {
         let id = circuit_data.get("circuitId")
             .and_then(|id| id.as_str())
@@ -521,18 +882,17 @@ fn parse_circuit(race: &Value) -> Result<Circuit> {
             .unwrap_or("Unknown")
             .to_string();
             
-        // These fields aren't in the API, so we'll use defaults
-        let length_km = 5.0; // Default circuit length
-        let laps = 50;      // Default number of laps
-        
-        return Ok(Circuit {
+        // Length and characteristics aren't in the API, so they come from the catalogue
+        let mut circuit = Circuit {
             id,
             name,
             country,
             city,
-            length_km,
-            laps,
-        });
+            ..Circuit::default()
+        };
+        circuits.characterize(&mut circuit);
+        
+        return Ok(circuit);
     }
     
     Err(anyhow::anyhow!("Failed to parse circuit data"))
@@ -551,15 +911,36 @@ fn parse_results(race: &Value) -> Result<Vec<RaceResult>> {
                 
             let driver = parse_driver(result)?;
             
+            let number = result.get("number")
+                .and_then(|n| n.as_str())
+                .and_then(|n| n.parse::<u32>().ok())
+                .unwrap_or(driver.number);
+                
+            let constructor_id = result.get("Constructor")
+                .and_then(|c| c.get("constructorId"))
+                .and_then(|id| id.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+                
+            let grid = result.get("grid")
+                .and_then(|g| g.as_str())
+                .and_then(|g| g.parse::<u32>().ok())
+                .unwrap_or(0);
+                
             let time = result.get("Time")
                 .and_then(|t| t.get("time"))
                 .and_then(|t| t.as_str())
                 .map(|t| t.to_string());
                 
+            let time_millis = result.get("Time")
+                .and_then(|t| t.get("millis"))
+                .and_then(|m| m.as_str())
+                .and_then(|m| m.parse::<u64>().ok());
+                
             let points = result.get("points")
                 .and_then(|p| p.as_str())
-                .and_then(|p| p.parse::<u32>().ok())
-                .unwrap_or(0);
+                .and_then(|p| p.parse::<f64>().ok())
+                .unwrap_or(0.0);
                 
             let laps = result.get("laps")
                 .and_then(|l| l.as_str())
@@ -571,13 +952,20 @@ fn parse_results(race: &Value) -> Result<Vec<RaceResult>> {
                 .unwrap_or("Unknown")
                 .to_string();
                 
+            let fastest_lap = result.get("FastestLap").and_then(parse_fastest_lap);
+                
             results.push(RaceResult {
                 position,
                 driver,
+                number,
+                constructor_id,
+                grid,
                 time,
+                time_millis,
                 points,
                 laps,
                 status,
+                fastest_lap,
             });
         }
     }
@@ -585,6 +973,35 @@ fn parse_results(race: &Value) -> Result<Vec<RaceResult>> {
     Ok(results)
 }
 
+/// Parse a driver's fastest lap from result data
+fn parse_fastest_lap(fastest_lap: &Value) -> Option<FastestLap> {
+    let rank = fastest_lap.get("rank")
+        .and_then(|r| r.as_str())
+        .and_then(|r| r.parse::<u32>().ok())?;
+        
+    let lap = fastest_lap.get("lap")
+        .and_then(|l| l.as_str())
+        .and_then(|l| l.parse::<u32>().ok())
+        .unwrap_or(0);
+        
+    let time = fastest_lap.get("Time")
+        .and_then(|t| t.get("time"))
+        .and_then(|t| t.as_str())?
+        .to_string();
+        
+    let average_speed_kph = fastest_lap.get("AverageSpeed")
+        .and_then(|s| s.get("speed"))
+        .and_then(|s| s.as_str())
+        .and_then(|s| s.parse::<f64>().ok());
+        
+    Some(FastestLap {
+        rank,
+        lap,
+        time,
+        average_speed_kph,
+    })
+}
+
 /// Parse driver information from result data
 fn parse_driver(result: &Value) -> Result<Driver> {
     if let Some(driver_data) = result.get("Driver") {
@@ -634,130 +1051,249 @@ fn parse_driver(result: &Value) -> Result<Driver> {
 }
 
 /// Load qualifying data for a specific GP
-pub fn load_qualifying_data(season: u32, gp: &str) -> Result<Vec<QualifyingResult>> {
-    ensure_data_dir()?;
-    let normalized_gp = normalize_gp_name(gp);
-    let file_path = get_qualifying_data_path(season, &normalized_gp);
+pub fn load_qualifying_data(config: &DataConfig, season: u32, gp: &str) -> Result<Vec<QualifyingResult>> {
+    let (_, race) = load_round_session(config, season, gp, &QUALIFYING)?;
+    let qualifying_results = parse_qualifying_results(&race)?;
     
-    // If the file doesn't exist, attempt to fetch it
-    if !Path::new(&file_path).exists() {
-        let client = Client::new();
-        fetch_qualifying_data(&client, season, gp)?;
+    if qualifying_results.is_empty() {
+        return Err(anyhow::anyhow!("No qualifying results found for {} GP {}", gp, season));
     }
     
-    // Now try to load the data (which should exist now if the fetch was successful)
-    if !Path::new(&file_path).exists() {
-        return Err(anyhow::anyhow!(
-            "Unable to retrieve qualifying data for {} GP {}. Data may not exist or network issues occurred.",
-            gp, season
-        ));
+    Ok(qualifying_results)
+}
+
+/// Parse qualifying results from race data
+fn parse_qualifying_results(race: &Value) -> Result<Vec<QualifyingResult>> {
+    let mut results = Vec::new();
+    
+    if let Some(results_data) = race.get("QualifyingResults").and_then(|r| r.as_array()) {
+        for (index, result) in results_data.iter().enumerate() {
+            let position = result.get("position")
+                .and_then(|p| p.as_str())
+                .and_then(|p| p.parse::<u32>().ok())
+                .unwrap_or((index + 1) as u32);
+                
+            let driver = parse_driver(result)?;
+            
+            let q1 = result.get("Q1")
+                .and_then(|t| t.as_str())
+                .map(|t| t.to_string());
+                
+            let q2 = result.get("Q2")
+                .and_then(|t| t.as_str())
+                .map(|t| t.to_string());
+                
+            let q3 = result.get("Q3")
+                .and_then(|t| t.as_str())
+                .map(|t| t.to_string());
+                
+            results.push(QualifyingResult {
+                position,
+                driver,
+                q1,
+                q2,
+                q3,
+            });
+        }
     }
     
-    let data = fs::read_to_string(&file_path)?;
-    let qualifying_data: Value = serde_json::from_str(&data)?;
-    
-    // Process the qualifying data into our model
-    let mut qualifying_results = Vec::new();
-    
-    if let Some(races) = qualifying_data.as_array() {
-        if let Some(race) = races.first() {
-            if let Some(qualifying_results_data) = race.get("QualifyingResults").and_then(|r| r.as_array()) {
-                for (index, result) in qualifying_results_data.iter().enumerate() {
-                    let position = result.get("position")
-                        .and_then(|p| p.as_str())
-                        .and_then(|p| p.parse::<u32>().ok())
-                        .unwrap_or((index + 1) as u32);
-                        
-                    let driver = parse_driver(result)?;
-                    
-                    let q1_time = result.get("Q1")
-                        .and_then(|t| t.as_str())
-                        .map(|t| t.to_string());
-                        
-                    let q2_time = result.get("Q2")
-                        .and_then(|t| t.as_str())
-                        .map(|t| t.to_string());
-                        
-                    let q3_time = result.get("Q3")
-                        .and_then(|t| t.as_str())
-                        .map(|t| t.to_string());
-                        
-                    qualifying_results.push(QualifyingResult {
-                        position,
-                        driver,
-                        q1: q1_time,
-                        q2: q2_time,
-                        q3: q3_time,
-                    });
-                }
-            }
+    Ok(results)
+}
+
+/// Load sprint data for a specific GP
+pub fn load_sprint_data(config: &DataConfig, season: u32, gp: &str) -> Result<Vec<SprintResult>> {
+    let (_, race) = load_round_session(config, season, gp, &SPRINT)?;
+    let sprint_results = parse_sprint_results(&race, season)?;
+    
+    if sprint_results.is_empty() {
+        return Err(anyhow::anyhow!("No sprint results found for {} GP {}", gp, season));
+    }
+    
+    Ok(sprint_results)
+}
+
+/// Parse sprint results from race data
+fn parse_sprint_results(race: &Value, season: u32) -> Result<Vec<SprintResult>> {
+    let mut results = Vec::new();
+    let points_system = PointsSystem::for_season(season);
+    
+    if let Some(results_data) = race.get("SprintResults").and_then(|r| r.as_array()) {
+        for (index, result) in results_data.iter().enumerate() {
+            let position = result.get("position")
+                .and_then(|p| p.as_str())
+                .and_then(|p| p.parse::<u32>().ok())
+                .unwrap_or((index + 1) as u32);
+                
+            let driver = parse_driver(result)?;
+            
+            let grid = result.get("grid")
+                .and_then(|g| g.as_str())
+                .and_then(|g| g.parse::<u32>().ok())
+                .unwrap_or(0);
+                
+            let time = result.get("Time")
+                .and_then(|t| t.get("time"))
+                .and_then(|t| t.as_str())
+                .map(|t| t.to_string());
+                
+            // Fall back to the sprint points table if the feed has no points
+            let points = result.get("points")
+                .and_then(|p| p.as_str())
+                .and_then(|p| p.parse::<f64>().ok())
+                .unwrap_or_else(|| points_system.points_for(RaceFormat::Sprint, position));
+                
+            let laps = result.get("laps")
+                .and_then(|l| l.as_str())
+                .and_then(|l| l.parse::<u32>().ok())
+                .unwrap_or(0);
+                
+            let status = result.get("status")
+                .and_then(|s| s.as_str())
+                .unwrap_or("Unknown")
+                .to_string();
+                
+            results.push(SprintResult {
+                position,
+                driver,
+                grid,
+                time,
+                points,
+                laps,
+                status,
+            });
         }
     }
     
-    if qualifying_results.is_empty() {
-        return Err(anyhow::anyhow!("No qualifying results found for {} GP {}", gp, season));
+    Ok(results)
+}
+
+/// Load lap-by-lap timings for a specific GP
+pub fn load_lap_data(config: &DataConfig, season: u32, gp: &str) -> Result<Vec<Lap>> {
+    let (_, race) = load_round_session(config, season, gp, &LAPS)?;
+    let laps = parse_laps(&race);
+    
+    if laps.is_empty() {
+        return Err(anyhow::anyhow!("No lap data found for {} GP {}", gp, season));
     }
     
-    Ok(qualifying_results)
+    Ok(laps)
 }
 
-/// Load practice data for a specific GP
-pub fn load_practice_data(season: u32, gp: &str, practice_number: u32) -> Result<Vec<PracticeResult>> {
-    ensure_data_dir()?;
-    let normalized_gp = normalize_gp_name(gp);
-    let file_path = get_practice_data_path(season, &normalized_gp, practice_number);
+/// Parse lap timings from race data, in lap order
+fn parse_laps(race: &Value) -> Vec<Lap> {
+    let mut laps = Vec::new();
     
-    // If the file doesn't exist, attempt to fetch it
-    if !Path::new(&file_path).exists() {
-        let client = Client::new();
-        fetch_practice_data(&client, season, gp, practice_number)?;
+    if let Some(laps_data) = race.get("Laps").and_then(|l| l.as_array()) {
+        for lap in laps_data {
+            let Some(number) = lap.get("number")
+                .and_then(|n| n.as_str())
+                .and_then(|n| n.parse::<u32>().ok()) else {
+                continue;
+            };
+            
+            let timings = lap.get("Timings")
+                .and_then(|t| t.as_array())
+                .map(|timings| timings.iter().filter_map(parse_lap_timing).collect())
+                .unwrap_or_default();
+                
+            laps.push(Lap { number, timings });
+        }
     }
     
-    // Now try to load the data (which should exist now if the fetch was successful)
-    if !Path::new(&file_path).exists() {
-        return Err(anyhow::anyhow!(
-            "Unable to retrieve practice data for {} GP {} FP{}. Data may not exist or network issues occurred.",
-            gp, season, practice_number
-        ));
+    laps.sort_by_key(|lap| lap.number);
+    laps
+}
+
+/// Parse one driver's timing on a lap
+fn parse_lap_timing(timing: &Value) -> Option<LapTiming> {
+    let driver_id = timing.get("driverId")
+        .and_then(|id| id.as_str())?
+        .to_string();
+        
+    let position = timing.get("position")
+        .and_then(|p| p.as_str())
+        .and_then(|p| p.parse::<u32>().ok())?;
+        
+    let time = timing.get("time")
+        .and_then(|t| t.as_str())?
+        .to_string();
+        
+    Some(LapTiming {
+        driver_id,
+        position,
+        time,
+    })
+}
+
+/// Load pit stops for a specific GP, in the order they happened
+pub fn load_pit_stops(config: &DataConfig, season: u32, gp: &str) -> Result<Vec<PitStop>> {
+    let (_, race) = load_round_session(config, season, gp, &PIT_STOPS)?;
+    let pit_stops = parse_pit_stops(&race);
+    
+    if pit_stops.is_empty() {
+        return Err(anyhow::anyhow!("No pit stop data found for {} GP {}", gp, season));
     }
     
-    let data = fs::read_to_string(&file_path)?;
-    let practice_data: Value = serde_json::from_str(&data)?;
-    
-    // Process the practice data into our model
-    let mut practice_results = Vec::new();
-    
-    if let Some(races) = practice_data.as_array() {
-        if let Some(race) = races.first() {
-            if let Some(practice_results_data) = race.get("PracticeResults").and_then(|r| r.as_array()) {
-                for (index, result) in practice_results_data.iter().enumerate() {
-                    let position = result.get("position")
-                        .and_then(|p| p.as_str())
-                        .and_then(|p| p.parse::<u32>().ok())
-                        .unwrap_or((index + 1) as u32);
-                        
-                    let driver = parse_driver(result)?;
-                    
-                    let time = result.get("time")
-                        .and_then(|t| t.as_str())
-                        .map(|t| t.to_string());
-
-                    let laps = result.get("laps")
-                        .and_then(|l| l.as_str())
-                        .and_then(|l| l.parse::<u32>().ok())
-                        .unwrap_or(0);
-                        
-                    practice_results.push(PracticeResult {
-                        position,
-                        driver,
-                        time,
-                        laps,
-                    });
-                }
-            }
+    Ok(pit_stops)
+}
+
+/// Parse pit stops from race data
+fn parse_pit_stops(race: &Value) -> Vec<PitStop> {
+    let mut pit_stops = Vec::new();
+    
+    if let Some(pit_stops_data) = race.get("PitStops").and_then(|p| p.as_array()) {
+        for pit_stop in pit_stops_data {
+            let Some(driver_id) = pit_stop.get("driverId").and_then(|id| id.as_str()) else {
+                continue;
+            };
+            
+            let stop = pit_stop.get("stop")
+                .and_then(|s| s.as_str())
+                .and_then(|s| s.parse::<u32>().ok())
+                .unwrap_or(0);
+                
+            let lap = pit_stop.get("lap")
+                .and_then(|l| l.as_str())
+                .and_then(|l| l.parse::<u32>().ok())
+                .unwrap_or(0);
+                
+            let time_of_day = pit_stop.get("time")
+                .and_then(|t| t.as_str())
+                .unwrap_or("")
+                .to_string();
+                
+            let duration = pit_stop.get("duration")
+                .and_then(|d| d.as_str())
+                .map(|d| d.to_string());
+                
+            pit_stops.push(PitStop {
+                driver_id: driver_id.to_string(),
+                stop,
+                lap,
+                time_of_day,
+                duration,
+            });
         }
     }
     
+    pit_stops.sort_by(|a, b| a.lap.cmp(&b.lap).then_with(|| a.time_of_day.cmp(&b.time_of_day)));
+    pit_stops
+}
+
+/// Load practice data for a specific GP
+pub fn load_practice_data(config: &DataConfig, season: u32, gp: &str, practice_number: u32) -> Result<Vec<PracticeResult>> {
+    if !(1..=3).contains(&practice_number) {
+        return Err(anyhow::anyhow!("Invalid practice session number: {}", practice_number));
+    }
+    
+    let name = format!("practice{}", practice_number);
+    let endpoint = format!("fp{}/results", practice_number);
+    let label = format!("FP{} data", practice_number);
+    let session = RoundSession { name: &name, endpoint: &endpoint, list: "PracticeResults", label: &label };
+    
+    let (_, race) = load_round_session(config, season, gp, &session)?;
+    let practice_results = parse_practice_results(&race)?;
+    
     if practice_results.is_empty() {
         return Err(anyhow::anyhow!("No practice results found for {} GP {} FP{}", gp, season, practice_number));
     }
@@ -765,20 +1301,188 @@ pub fn load_practice_data(season: u32, gp: &str, practice_number: u32) -> Result
     Ok(practice_results)
 }
 
-/// Get current season's driver standings
-#[allow(dead_code)]
-pub fn get_driver_standings(season: u32) -> Result<HashMap<String, u32>> {
-    let mut standings = HashMap::new();
+/// Parse practice results from race data
+fn parse_practice_results(race: &Value) -> Result<Vec<PracticeResult>> {
+    let mut results = Vec::new();
     
-    // This would be implemented to aggregate points from all races
-    // For now, just return dummy data
-    if season == CURRENT_SEASON {
-        standings.insert("Max Verstappen".to_string(), 230);
-        standings.insert("Lando Norris".to_string(), 190);
-        standings.insert("Charles Leclerc".to_string(), 186);
-        standings.insert("Carlos Sainz".to_string(), 168);
-        standings.insert("Lewis Hamilton".to_string(), 152);
+    if let Some(results_data) = race.get("PracticeResults").and_then(|r| r.as_array()) {
+        for (index, result) in results_data.iter().enumerate() {
+            let position = result.get("position")
+                .and_then(|p| p.as_str())
+                .and_then(|p| p.parse::<u32>().ok())
+                .unwrap_or((index + 1) as u32);
+            
+            let driver = parse_driver(result)?;
+            
+            let time = result.get("time")
+                .and_then(|t| t.as_str())
+                .map(|t| t.to_string());
+            
+            let laps = result.get("laps")
+                .and_then(|l| l.as_str())
+                .and_then(|l| l.parse::<u32>().ok())
+                .unwrap_or(0);
+            
+            results.push(PracticeResult {
+                position,
+                driver,
+                time,
+                laps,
+            });
+        }
+    }
+    
+    Ok(results)
+}
+
+/// Championship standings of a season after a round (default: the latest cached round),
+/// optionally rescored with another points system
+pub fn load_standings(config: &DataConfig, season: u32, round: Option<u32>, points_system: Option<&PointsSystem>) -> Result<Standings> {
+    let weekends = load_cached_results(config, season)?;
+    
+    if weekends.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No race results for season {} are cached. Run `f1-cli-simulator update --seasons {}` to download them.",
+            season, season
+        ));
+    }
+    
+    Ok(Standings::after_round(&weekends, round, points_system))
+}
+
+/// The season `standings` shows by default: the current one, or the one just
+/// finished while the current season has no cached race results yet
+pub fn default_standings_season(config: &DataConfig) -> Result<u32> {
+    let current = config.current_season();
+    let has_results = load_index(&config.data_dir, current)?.is_some_and(|index| {
+        index.rounds.iter().any(|r| get_race_data_path(config, current, r.round).exists())
+    });
+    
+    Ok(if has_results || current <= FIRST_SEASON { current } else { current - 1 })
+}
+
+/// Driver and constructor ratings fitted from the cached results of the seasons
+/// leading up to and including `season`. Nothing is fetched.
+pub fn load_ratings(config: &DataConfig, season: u32) -> Result<Ratings> {
+    let mut weekends = Vec::new();
+    for year in season.saturating_sub(RATING_SEASONS - 1)..=season {
+        weekends.extend(load_cached_results(config, year)?);
+    }
+    
+    if weekends.is_empty() {
+        println!("{}", format!(
+            "No cached results up to {} to rate drivers from; every driver and car is rated equal. Run `update` to download them.",
+            season
+        ).yellow());
+    }
+    
+    Ok(Ratings::fit(&weekends))
+}
+
+/// The drivers and teams entered for a season, as of the round before `round`
+/// (default: the latest round).
+///
+/// A roster file set in the configuration takes precedence. Otherwise the entry
+/// list is the field of the latest cached race of the season, then the season's
+/// entry list from the API, then the field of the previous season's last race.
+pub fn load_roster(config: &DataConfig, season: u32, round: Option<u32>) -> Result<Vec<Driver>> {
+    if let Some(path) = &config.roster {
+        return read_roster_file(path);
+    }
+    
+    let before = |weekend: &WeekendResults| round.is_none_or(|r| weekend.race.round < r);
+    if let Some(drivers) = latest_field(load_cached_results(config, season)?.iter().filter(|w| before(w))) {
+        return Ok(drivers);
+    }
+    
+    let roster_path = get_roster_data_path(config, season);
+    if !roster_path.exists() && !config.offline {
+        ensure_data_dir(config)?;
+        let client = FetchClient::new(&config.fetch)?;
+        if let Err(e) = fetch_roster(config, &client, season) {
+            println!("{}", format!("Could not fetch the {} entry list: {}", season, e).yellow());
+        }
+    }
+    if roster_path.exists() {
+        let entries: Value = serde_json::from_str(&fs::read_to_string(&roster_path)?)?;
+        let drivers = parse_roster(&entries)?;
+        if !drivers.is_empty() {
+            return Ok(drivers);
+        }
+    }
+    
+    let previous = season.saturating_sub(1);
+    if let Some(drivers) = latest_field(load_cached_results(config, previous)?.iter()) {
+        println!("{}", format!("No {} entry list available; using the field of the last {} race", season, previous).yellow());
+        return Ok(drivers);
     }
     
-    Ok(standings)
-}
\ No newline at end of file
+    Err(anyhow::anyhow!(
+        "No entry list for {} is available. Run `f1-cli-simulator update --seasons {}` or pass a roster file with --roster.",
+        season, season
+    ))
+}
+
+// The drivers of the last of these weekends, with the team they raced for
+fn latest_field<'a>(weekends: impl Iterator<Item = &'a WeekendResults>) -> Option<Vec<Driver>> {
+    weekends
+        .filter(|w| !w.race.results.is_empty())
+        .max_by_key(|w| w.race.round)
+        .map(|w| w.race.results.iter().map(|r| r.driver.clone()).collect())
+}
+
+// Parse a fetched entry list: constructors, each with the drivers entered for it
+fn parse_roster(entries: &Value) -> Result<Vec<Driver>> {
+    let mut drivers = Vec::new();
+    
+    for constructor in entries.as_array().into_iter().flatten() {
+        for driver in constructor.get("Drivers").and_then(|d| d.as_array()).into_iter().flatten() {
+            let entry = serde_json::json!({ "Driver": driver, "Constructor": constructor });
+            drivers.push(parse_driver(&entry)?);
+        }
+    }
+    
+    Ok(drivers)
+}
+
+/// A user-supplied roster entry; the id defaults to the lowercased code
+#[derive(Debug, Deserialize)]
+struct RosterEntry {
+    #[serde(default)]
+    id: Option<String>,
+    code: String,
+    name: String,
+    team: String,
+    #[serde(default)]
+    number: u32,
+}
+
+/// Read a roster file: a JSON list of drivers with their code, name, team and number
+pub fn read_roster_file(path: &Path) -> Result<Vec<Driver>> {
+    let data = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read roster file {}", path.display()))?;
+    let entries: Vec<RosterEntry> = serde_json::from_str(&data)
+        .with_context(|| format!("Failed to parse roster file {}", path.display()))?;
+    
+    if entries.is_empty() {
+        return Err(anyhow::anyhow!("Roster file {} lists no drivers", path.display()));
+    }
+    
+    Ok(entries.into_iter().map(|entry| Driver {
+        id: entry.id.unwrap_or_else(|| entry.code.to_lowercase()),
+        code: entry.code,
+        name: entry.name,
+        team: entry.team,
+        number: entry.number,
+    }).collect())
+}
+
+/// The circuit catalogue: the bundled one, with the circuits of the catalogue
+/// file set in the configuration added or replaced
+pub fn load_circuits(config: &DataConfig) -> Result<CircuitCatalogue> {
+    let mut catalogue = CircuitCatalogue::bundled();
+    if let Some(path) = &config.circuits {
+        catalogue.merge(CircuitCatalogue::from_file(path)?);
+    }
+    Ok(catalogue)
+}
@@ -0,0 +1,167 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::Deserialize;
+
+use super::fetch::FetchSettings;
+use super::index::load_index;
+
+/// Default API endpoint (the Jolpica mirror of the retired Ergast API)
+pub const DEFAULT_API_BASE_URL: &str = "https://api.jolpi.ca/ergast/f1";
+
+/// Environment variable overriding the data directory
+pub const DATA_DIR_ENV: &str = "F1_DATA_DIR";
+/// Environment variable overriding the API base URL
+pub const API_URL_ENV: &str = "F1_API_URL";
+/// Environment variable overriding the current season
+pub const CURRENT_SEASON_ENV: &str = "F1_CURRENT_SEASON";
+/// Environment variable enabling offline mode
+pub const OFFLINE_ENV: &str = "F1_OFFLINE";
+/// Environment variable pointing at a config file
+pub const CONFIG_ENV: &str = "F1_CONFIG";
+/// Environment variable pointing at a roster file
+pub const ROSTER_ENV: &str = "F1_ROSTER";
+/// Environment variable pointing at a circuit catalogue file
+pub const CIRCUITS_ENV: &str = "F1_CIRCUITS";
+
+const APP_DIR_NAME: &str = "f1-cli-simulator";
+
+/// Where cached data lives and which API it is fetched from
+#[derive(Debug, Clone)]
+pub struct DataConfig {
+    pub data_dir: PathBuf,
+    pub api_base_url: String,
+    /// Season to treat as current instead of the one on the clock
+    pub current_season: Option<u32>,
+    /// Never touch the network; only cached data is used
+    pub offline: bool,
+    /// Rate limit, timeout and retry settings for API requests
+    pub fetch: FetchSettings,
+    /// Roster file replacing the entry list derived from race data
+    pub roster: Option<PathBuf>,
+    /// Circuit catalogue file adding to or replacing circuits of the bundled one
+    pub circuits: Option<PathBuf>,
+}
+
+/// Contents of the optional JSON config file; every field may be omitted
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ConfigFile {
+    data_dir: Option<PathBuf>,
+    api_base_url: Option<String>,
+    current_season: Option<u32>,
+    offline: Option<bool>,
+    roster: Option<PathBuf>,
+    circuits: Option<PathBuf>,
+}
+
+impl DataConfig {
+    pub fn new(data_dir: impl Into<PathBuf>, api_base_url: &str) -> Self {
+        DataConfig {
+            data_dir: data_dir.into(),
+            api_base_url: api_base_url.trim_end_matches('/').to_string(),
+            current_season: None,
+            offline: false,
+            fetch: FetchSettings::default(),
+            roster: None,
+            circuits: None,
+        }
+    }
+    
+    /// Load the configuration from the config file and environment.
+    ///
+    /// Precedence, lowest to highest: built-in defaults, config file, environment.
+    /// CLI flags are applied on top by the caller.
+    pub fn load(config_path: Option<&Path>) -> Result<Self> {
+        Self::from_env(&|key| std::env::var(key).ok(), config_path)
+    }
+    
+    /// Load the configuration using `env` to look up environment variables
+    pub fn from_env(env: &dyn Fn(&str) -> Option<String>, config_path: Option<&Path>) -> Result<Self> {
+        // An explicitly requested config file must exist; the default one is optional
+        let file = match config_path.map(PathBuf::from).or_else(|| env(CONFIG_ENV).map(PathBuf::from)) {
+            Some(path) => read_config_file(&path)?,
+            None => match default_config_path(env) {
+                Some(path) if path.exists() => read_config_file(&path)?,
+                _ => ConfigFile::default(),
+            },
+        };
+        
+        let data_dir = env(DATA_DIR_ENV).map(PathBuf::from)
+            .or(file.data_dir)
+            .or_else(|| default_data_dir(env))
+            .unwrap_or_else(|| PathBuf::from("./data"));
+        
+        let api_base_url = env(API_URL_ENV)
+            .or(file.api_base_url)
+            .unwrap_or_else(|| DEFAULT_API_BASE_URL.to_string());
+        
+        let current_season = match env(CURRENT_SEASON_ENV) {
+            Some(season) => Some(season.trim().parse::<u32>()
+                .with_context(|| format!("Invalid {} value '{}'", CURRENT_SEASON_ENV, season))?),
+            None => file.current_season,
+        };
+        
+        let offline = match env(OFFLINE_ENV) {
+            Some(value) => matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"),
+            None => file.offline.unwrap_or(false),
+        };
+        
+        let roster = env(ROSTER_ENV).map(PathBuf::from).or(file.roster);
+        let circuits = env(CIRCUITS_ENV).map(PathBuf::from).or(file.circuits);
+        
+        let mut config = DataConfig::new(data_dir, &api_base_url);
+        config.current_season = current_season;
+        config.offline = offline;
+        config.roster = roster;
+        config.circuits = circuits;
+        Ok(config)
+    }
+    
+    /// The season treated as current: the override if one is set, otherwise the year on the clock
+    pub fn current_season(&self) -> u32 {
+        self.current_season_at(Utc::now().date_naive())
+    }
+    
+    /// The season treated as current on `today`: the override if one is set,
+    /// otherwise the year, or the next one once every race of the year's cached
+    /// calendar has been run
+    pub fn current_season_at(&self, today: NaiveDate) -> u32 {
+        if let Some(season) = self.current_season {
+            return season;
+        }
+        
+        let year = today.year() as u32;
+        let calendar_over = load_index(&self.data_dir, year).ok().flatten()
+            .is_some_and(|index| !index.rounds.is_empty() && index.next_round(today).is_none());
+        if calendar_over { year + 1 } else { year }
+    }
+}
+
+/// Read and parse a JSON config file
+fn read_config_file(path: &Path) -> Result<ConfigFile> {
+    let data = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file {}", path.display()))?;
+    serde_json::from_str(&data)
+        .with_context(|| format!("Failed to parse config file {}", path.display()))
+}
+
+/// `$XDG_DATA_HOME/f1-cli-simulator`, falling back to the platform default
+fn default_data_dir(env: &dyn Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    if let Some(dir) = env("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join(APP_DIR_NAME));
+    }
+    if let Some(dir) = env("APPDATA") {
+        return Some(PathBuf::from(dir).join(APP_DIR_NAME));
+    }
+    env("HOME").map(|home| PathBuf::from(home).join(".local/share").join(APP_DIR_NAME))
+}
+
+/// `$XDG_CONFIG_HOME/f1-cli-simulator/config.json`, falling back to the platform default
+fn default_config_path(env: &dyn Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let dir = env("XDG_CONFIG_HOME").filter(|d| !d.is_empty()).map(PathBuf::from)
+        .or_else(|| env("APPDATA").map(PathBuf::from))
+        .or_else(|| env("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(dir.join(APP_DIR_NAME).join("config.json"))
+}
@@ -0,0 +1,55 @@
+use std::fmt;
+
+/// Errors from the data layer that callers may want to handle specifically
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataError {
+    /// Data is missing from the cache and offline mode forbids fetching it
+    NotCached {
+        /// What was requested, e.g. "Race data for monza GP 2023"
+        what: String,
+        season: u32,
+        /// Session the data belongs to, as named in the manifest ("race", "practice2", ...),
+        /// or "schedule" for the season schedule
+        session: String,
+    },
+}
+
+impl DataError {
+    pub fn not_cached(what: impl Into<String>, season: u32, session: impl Into<String>) -> Self {
+        DataError::NotCached {
+            what: what.into(),
+            season,
+            session: session.into(),
+        }
+    }
+    
+    /// The `update` command that downloads the missing data, if `update`
+    /// downloads it at all; other sessions are fetched only on demand
+    pub fn update_command(&self) -> Option<String> {
+        match self {
+            DataError::NotCached { season, session, .. } => super::fetched_by_update(*season, session)
+                .then(|| format!("f1-cli-simulator update --seasons {}", season)),
+        }
+    }
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataError::NotCached { what, .. } => match self.update_command() {
+                Some(command) => write!(
+                    f,
+                    "{} is not cached and offline mode is enabled. Run `{}` to download it.",
+                    what, command
+                ),
+                None => write!(
+                    f,
+                    "{} is not cached and offline mode is enabled. Run the command again without --offline or {} to download it.",
+                    what, super::config::OFFLINE_ENV
+                ),
+            },
+        }
+    }
+}
+
+impl std::error::Error for DataError {}
@@ -0,0 +1,295 @@
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::blocking::Client;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use serde_json::Value;
+
+/// Number of rows requested per page (the Jolpica mirror caps this at 100)
+pub const PAGE_LIMIT: u32 = 100;
+
+/// Sustained request rate allowed by the Jolpica mirror
+pub const DEFAULT_REQUESTS_PER_SECOND: f64 = 4.0;
+
+/// Keys that identify a race, whose results a page boundary may split
+const RACE_KEYS: [&str; 2] = ["season", "round"];
+
+/// Keys that identify a lap inside a race's `Laps`, whose timings a page
+/// boundary may split
+const LAP_KEYS: [&str; 1] = ["number"];
+
+/// How often and how patiently failed requests are retried
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for each further retry
+    pub base_delay: Duration,
+    /// Upper bound for any single delay, including `Retry-After`
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff before retry number `attempt` (starting at 0)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+/// Settings of the shared fetch client
+#[derive(Debug, Clone)]
+pub struct FetchSettings {
+    pub requests_per_second: f64,
+    /// Requests that may be sent back to back before the rate limit applies
+    pub burst: u32,
+    /// Timeout of a single request
+    pub timeout: Duration,
+    pub retry: RetryPolicy,
+}
+
+impl Default for FetchSettings {
+    fn default() -> Self {
+        FetchSettings {
+            requests_per_second: DEFAULT_REQUESTS_PER_SECOND,
+            burst: 4,
+            timeout: Duration::from_secs(30),
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+/// Token bucket limiting how fast requests are sent
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_second: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket holding `capacity` tokens, refilled at `refill_per_second`
+    pub fn new(refill_per_second: f64, capacity: u32, now: Instant) -> Self {
+        TokenBucket {
+            capacity: capacity.max(1) as f64,
+            tokens: capacity.max(1) as f64,
+            refill_per_second,
+            last_refill: now,
+        }
+    }
+    
+    /// Take a token at `now` and return how long to wait before using it.
+    ///
+    /// The bucket may go into debt, so callers that each wait their turn
+    /// are spaced out evenly at the refill rate.
+    pub fn reserve(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.last_refill = now;
+        
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 || self.refill_per_second <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.refill_per_second)
+        }
+    }
+}
+
+/// HTTP client shared by every API fetch: rate limited, with timeouts and retries
+pub struct FetchClient {
+    client: Client,
+    limiter: Mutex<TokenBucket>,
+    retry: RetryPolicy,
+}
+
+impl FetchClient {
+    pub fn new(settings: &FetchSettings) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(settings.timeout)
+            .build()
+            .context("Failed to create HTTP client")?;
+        
+        Ok(FetchClient {
+            client,
+            limiter: Mutex::new(TokenBucket::new(settings.requests_per_second, settings.burst, Instant::now())),
+            retry: settings.retry.clone(),
+        })
+    }
+    
+    /// GET a JSON document, retrying rate-limited, failing and timed out requests
+    pub fn get_json(&self, url: &str, query: &[(&str, u32)]) -> Result<Value> {
+        let mut attempt = 0;
+        
+        loop {
+            self.wait_for_turn();
+            
+            let (error, delay) = match self.client.get(url).query(query).send() {
+                Ok(response) if response.status().is_success() => {
+                    return response.json()
+                        .with_context(|| format!("Failed to parse response from {}", url));
+                },
+                Ok(response) if is_retryable(response.status()) => {
+                    let delay = retry_after(response.headers(), Utc::now())
+                        .unwrap_or_else(|| self.retry.backoff(attempt));
+                    (anyhow::anyhow!("API returned error status: {}", response.status()), delay)
+                },
+                Ok(response) => {
+                    return Err(anyhow::anyhow!("API returned error status: {}", response.status()));
+                },
+                Err(e) if e.is_timeout() => {
+                    (anyhow::anyhow!("Request to {} timed out", url), self.retry.backoff(attempt))
+                },
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to connect to API at {}", url));
+                },
+            };
+            
+            if attempt >= self.retry.max_retries {
+                return Err(anyhow::anyhow!("{} (gave up after {} attempts)", error, attempt + 1));
+            }
+            
+            thread::sleep(delay.min(self.retry.max_delay));
+            attempt += 1;
+        }
+    }
+    
+    /// Block until the rate limiter allows another request
+    fn wait_for_turn(&self) {
+        let wait = self.limiter.lock()
+            .map(|mut bucket| bucket.reserve(Instant::now()))
+            .unwrap_or(Duration::ZERO);
+        
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+}
+
+/// Rate limiting and server errors are worth another try; other client errors are not
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Read a `Retry-After` header given either in seconds or as an HTTP date
+pub fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    
+    let date = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    Some((date - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Fetch every page of an Ergast table and merge the rows into a single list.
+///
+/// `table` and `list` name the container inside `MRData`, for example
+/// `RaceTable`/`Races` or `CircuitTable`/`Circuits`. The walk follows
+/// `MRData.total` and advances `offset` by the page size the server reports,
+/// so a server that caps `limit` below `PAGE_LIMIT` is still read in full.
+pub fn fetch_all_pages(client: &FetchClient, url: &str, table: &str, list: &str) -> Result<Vec<Value>> {
+    let mut rows: Vec<Value> = Vec::new();
+    let mut offset = 0;
+    
+    loop {
+        let page = fetch_page(client, url, PAGE_LIMIT, offset)?;
+        let mr_data = page.get("MRData")
+            .with_context(|| format!("Missing MRData in response from {}", url))?;
+        
+        let total = read_count(mr_data, "total").unwrap_or(0);
+        let limit = read_count(mr_data, "limit").unwrap_or(PAGE_LIMIT);
+        
+        let items = mr_data.get(table)
+            .and_then(|t| t.get(list))
+            .and_then(|l| l.as_array())
+            .cloned()
+            .unwrap_or_default();
+        
+        append_rows(&mut rows, items);
+        
+        offset += limit;
+        if limit == 0 || offset >= total {
+            break;
+        }
+    }
+    
+    Ok(rows)
+}
+
+/// Fetch a single page of an Ergast endpoint
+fn fetch_page(client: &FetchClient, url: &str, limit: u32, offset: u32) -> Result<Value> {
+    client.get_json(url, &[("limit", limit), ("offset", offset)])
+}
+
+/// Read one of the numeric `MRData` fields, which Ergast encodes as strings
+fn read_count(mr_data: &Value, key: &str) -> Option<u32> {
+    match mr_data.get(key)? {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.as_u64().map(|n| n as u32),
+        _ => None,
+    }
+}
+
+/// Append a page of rows, merging the first row into the last one when a
+/// page boundary split it (e.g. half of a race's results on each page)
+pub fn append_rows(rows: &mut Vec<Value>, page: Vec<Value>) {
+    append_split_rows(rows, page, &RACE_KEYS);
+}
+
+/// Append a page of rows, merging the first row into the last one when both
+/// carry the same `keys`
+fn append_split_rows(rows: &mut Vec<Value>, page: Vec<Value>, keys: &[&str]) {
+    let mut items = page.into_iter();
+    
+    if let Some(first) = items.next() {
+        match rows.last_mut() {
+            Some(last) if same_entry(last, &first, keys) => merge_entry(last, first),
+            _ => rows.push(first),
+        }
+    }
+    
+    rows.extend(items);
+}
+
+/// Check whether two rows carry the same `keys`
+fn same_entry(a: &Value, b: &Value, keys: &[&str]) -> bool {
+    keys.iter().all(|key| matches!((a.get(key), b.get(key)), (Some(x), Some(y)) if x == y))
+}
+
+/// Merge the nested lists of `other` into `target`. Only laps continue across
+/// pages; every other row, such as a result, is an entry of its own even when
+/// it looks like the last one (two drivers sharing a car have the same number).
+fn merge_entry(target: &mut Value, other: Value) {
+    let (Some(target), Value::Object(other)) = (target.as_object_mut(), other) else {
+        return;
+    };
+    
+    for (key, value) in other {
+        match (target.get_mut(&key), value) {
+            (Some(Value::Array(existing)), Value::Array(incoming)) if key == "Laps" => {
+                append_split_rows(existing, incoming, &LAP_KEYS)
+            },
+            (Some(Value::Array(existing)), Value::Array(incoming)) => existing.extend(incoming),
+            (None, value) => {
+                target.insert(key, value);
+            },
+            _ => {},
+        }
+    }
+}
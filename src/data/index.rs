@@ -0,0 +1,281 @@
+use std::fs;
+use std::path::Path;
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::utils::normalize_gp_name;
+
+/// File prefixes of the per-round session caches
+pub const SESSION_PREFIXES: [&str; 6] = ["race", "qualifying", "sprint", "practice1", "practice2", "practice3"];
+
+/// Round lookup for one season: which circuit and names belong to each round
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeasonIndex {
+    pub season: u32,
+    pub rounds: Vec<RoundEntry>,
+}
+
+/// A single round of a season
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundEntry {
+    pub round: u32,
+    pub race_name: String,
+    pub circuit_id: String,
+    pub date: String,
+    /// Whether the weekend includes a sprint race
+    #[serde(default)]
+    pub sprint: bool,
+    /// Alternative names the round can be selected by (race name, locality, country)
+    pub aliases: Vec<String>,
+}
+
+impl SeasonIndex {
+    pub fn new(season: u32) -> Self {
+        SeasonIndex {
+            season,
+            rounds: Vec::new(),
+        }
+    }
+    
+    /// Build an index from the `Races` list of a season schedule
+    pub fn from_races(season: u32, races: &[Value]) -> Self {
+        let mut index = SeasonIndex::new(season);
+        for race in races {
+            index.add_race(race);
+        }
+        index
+    }
+    
+    /// Insert or replace the round described by an Ergast race entry
+    pub fn add_race(&mut self, race: &Value) -> Option<u32> {
+        let round = race.get("round")
+            .and_then(|r| r.as_str())
+            .and_then(|r| r.parse::<u32>().ok())?;
+        
+        let race_name = race.get("raceName")
+            .and_then(|n| n.as_str())
+            .unwrap_or("Unknown Grand Prix")
+            .to_string();
+        
+        let circuit = race.get("Circuit");
+        let circuit_id = circuit.and_then(|c| c.get("circuitId"))
+            .and_then(|id| id.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        
+        let location = circuit.and_then(|c| c.get("Location"));
+        let mut aliases = vec![race_name_alias(&race_name)];
+        for key in ["locality", "country"] {
+            if let Some(name) = location.and_then(|l| l.get(key)).and_then(|n| n.as_str()) {
+                aliases.push(canonical_key(name));
+            }
+        }
+        aliases.retain(|a| !a.is_empty() && *a != circuit_id);
+        aliases.dedup();
+        
+        let date = race.get("date")
+            .and_then(|d| d.as_str())
+            .unwrap_or("Unknown")
+            .to_string();
+        
+        let entry = RoundEntry {
+            round,
+            race_name,
+            circuit_id,
+            date,
+            sprint: race.get("Sprint").is_some(),
+            aliases,
+        };
+        
+        match self.rounds.iter_mut().find(|r| r.round == round) {
+            Some(existing) => *existing = entry,
+            None => {
+                self.rounds.push(entry);
+                self.rounds.sort_by_key(|r| r.round);
+            }
+        }
+        
+        Some(round)
+    }
+    
+    /// Look up a round by number
+    pub fn get(&self, round: u32) -> Option<&RoundEntry> {
+        self.rounds.iter().find(|r| r.round == round)
+    }
+    
+    /// The first round taking place on or after `today`
+    pub fn next_round(&self, today: NaiveDate) -> Option<&RoundEntry> {
+        self.rounds.iter()
+            .find(|r| r.race_date().is_some_and(|date| date >= today))
+    }
+    
+    /// Find every round matching a GP name, circuit id, alias or round number
+    pub fn find_rounds(&self, gp: &str) -> Vec<u32> {
+        if let Ok(round) = gp.trim().parse::<u32>() {
+            return self.get(round).map(|r| vec![r.round]).unwrap_or_default();
+        }
+        
+        let key = race_name_alias(gp);
+        let normalized = canonical_key(&normalize_gp_name(gp));
+        
+        self.rounds.iter()
+            .filter(|r| {
+                r.circuit_id == key
+                    || r.circuit_id == normalized
+                    || r.aliases.iter().any(|a| *a == key || *a == normalized)
+            })
+            .map(|r| r.round)
+            .collect()
+    }
+    
+    /// Resolve a GP selector to exactly one round
+    pub fn resolve(&self, gp: &str) -> Result<u32> {
+        let rounds = self.find_rounds(gp);
+        
+        match rounds.as_slice() {
+            [round] => Ok(*round),
+            [] => Err(anyhow::anyhow!("No race matching '{}' found in the {} season", gp, self.season)),
+            _ => {
+                let options = rounds.iter()
+                    .filter_map(|r| self.get(*r))
+                    .map(|r| format!("round {} ({})", r.round, r.race_name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Err(anyhow::anyhow!(
+                    "'{}' matches more than one race in {}: {}. Use --round to pick one.",
+                    gp, self.season, options
+                ))
+            }
+        }
+    }
+}
+
+impl RoundEntry {
+    /// Race day, if the schedule gave a valid date
+    pub fn race_date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.date, "%Y-%m-%d").ok()
+    }
+}
+
+/// Lowercase a name and join its words with underscores, matching circuit id style
+pub fn canonical_key(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Alias for a race name, e.g. "Styrian Grand Prix" -> "styrian"
+pub(crate) fn race_name_alias(race_name: &str) -> String {
+    canonical_key(&race_name.to_lowercase().replace("grand prix", ""))
+}
+
+/// File name of a round-keyed session cache, e.g. `race_2020_r2.json`
+pub fn round_file_name(prefix: &str, season: u32, round: u32) -> String {
+    format!("{}_{}_r{}.json", prefix, season, round)
+}
+
+/// File name of a season index
+pub fn index_file_name(season: u32) -> String {
+    format!("index_{}.json", season)
+}
+
+/// Split a cache file name into prefix, season and the key after the season
+fn split_cache_file_name(file_name: &str) -> Option<(&'static str, u32, &str)> {
+    let stem = file_name.strip_suffix(".json")?;
+    
+    SESSION_PREFIXES.iter().find_map(|prefix| {
+        let rest = stem.strip_prefix(prefix)?.strip_prefix('_')?;
+        let (season, key) = rest.split_once('_')?;
+        Some((*prefix, season.parse().ok()?, key))
+    })
+}
+
+/// Parse a round-keyed cache file name into its prefix, season and round
+pub fn parse_round_file_name(file_name: &str) -> Option<(&'static str, u32, u32)> {
+    let (prefix, season, key) = split_cache_file_name(file_name)?;
+    let round = key.strip_prefix('r')?.parse().ok()?;
+    Some((prefix, season, round))
+}
+
+/// Load the index of a season from the data directory, if it has been cached
+pub fn load_index(dir: &Path, season: u32) -> Result<Option<SeasonIndex>> {
+    let path = dir.join(index_file_name(season));
+    if !path.exists() {
+        return Ok(None);
+    }
+    
+    let data = fs::read_to_string(&path)?;
+    Ok(Some(serde_json::from_str(&data)?))
+}
+
+/// Write the index of a season to the data directory
+pub fn save_index(dir: &Path, index: &SeasonIndex) -> Result<()> {
+    let path = dir.join(index_file_name(index.season));
+    fs::write(path, serde_json::to_string_pretty(index)?)?;
+    Ok(())
+}
+
+/// Move circuit-keyed cache files (`race_2020_red_bull_ring.json`) to the
+/// round-keyed layout, splitting files that hold more than one race.
+///
+/// Returns the number of legacy files migrated.
+pub fn migrate_circuit_keyed_files(dir: &Path) -> Result<usize> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    
+    let mut migrated = 0;
+    
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let file_name = path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string();
+        
+        let Some((prefix, season, _)) = split_cache_file_name(&file_name) else {
+            continue;
+        };
+        if parse_round_file_name(&file_name).is_some() {
+            continue;
+        }
+        
+        // Leave files we cannot make sense of untouched
+        let Ok(races) = fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| Ok(serde_json::from_str::<Vec<Value>>(&data)?))
+        else {
+            continue;
+        };
+        
+        let mut index = load_index(dir, season)?.unwrap_or_else(|| SeasonIndex::new(season));
+        let mut complete = true;
+        
+        for race in &races {
+            match index.add_race(race) {
+                Some(round) => {
+                    let target = dir.join(round_file_name(prefix, season, round));
+                    if !target.exists() {
+                        fs::write(&target, serde_json::to_string_pretty(&vec![race])?)?;
+                    }
+                },
+                None => complete = false,
+            }
+        }
+        
+        save_index(dir, &index)?;
+        
+        // Only drop the legacy file once every race in it has a new home
+        if complete {
+            fs::remove_file(&path)?;
+            migrated += 1;
+        }
+    }
+    
+    Ok(migrated)
+}
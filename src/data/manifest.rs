@@ -0,0 +1,99 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// File name of the manifest inside the data directory
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Record of what has been downloaded, so `update` can skip complete data
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub seasons: BTreeMap<u32, SeasonManifest>,
+}
+
+/// Download state of one season
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeasonManifest {
+    /// Every round has been run and fetched; the season will not change anymore
+    #[serde(rename = "final")]
+    pub is_final: bool,
+    pub schedule_fetched_at: Option<DateTime<Utc>>,
+    /// Fetched sessions per round, keyed by session name ("race", "qualifying", ...)
+    pub rounds: BTreeMap<u32, BTreeMap<String, SessionRecord>>,
+    /// Sessions `update` has fetched for every round of the season that has them
+    #[serde(default)]
+    pub sessions: BTreeSet<String>,
+}
+
+/// One fetched session of a round
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub fetched_at: DateTime<Utc>,
+    /// Number of result rows in the cached file
+    pub rows: usize,
+}
+
+impl Manifest {
+    /// Load the manifest from the data directory; a missing manifest is empty
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(MANIFEST_FILE_NAME);
+        if !path.exists() {
+            return Ok(Manifest::default());
+        }
+        
+        let data = fs::read_to_string(&path)?;
+        serde_json::from_str(&data)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+    
+    /// Write the manifest to the data directory
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        fs::write(dir.join(MANIFEST_FILE_NAME), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+    
+    pub fn season(&self, season: u32) -> Option<&SeasonManifest> {
+        self.seasons.get(&season)
+    }
+    
+    /// Whether a season has been marked as final
+    pub fn is_final(&self, season: u32) -> bool {
+        self.season(season).is_some_and(|s| s.is_final)
+    }
+    
+    /// Look up a fetched session
+    pub fn session(&self, season: u32, round: u32, session: &str) -> Option<&SessionRecord> {
+        self.season(season)?.rounds.get(&round)?.get(session)
+    }
+    
+    /// Note that a session was fetched
+    pub fn record(&mut self, season: u32, round: u32, session: &str, rows: usize, fetched_at: DateTime<Utc>) {
+        self.seasons.entry(season)
+            .or_default()
+            .rounds.entry(round)
+            .or_default()
+            .insert(session.to_string(), SessionRecord { fetched_at, rows });
+    }
+    
+    /// Note that a season's schedule was fetched
+    pub fn record_schedule(&mut self, season: u32, fetched_at: DateTime<Utc>) {
+        self.seasons.entry(season).or_default().schedule_fetched_at = Some(fetched_at);
+    }
+    
+    /// Note that `update` fetched a session for the whole season
+    pub fn record_season_session(&mut self, season: u32, session: &str) {
+        self.seasons.entry(season).or_default().sessions.insert(session.to_string());
+    }
+    
+    /// Whether `update` has fetched a session for the whole season
+    pub fn has_season_session(&self, season: u32, session: &str) -> bool {
+        self.season(season).is_some_and(|s| s.sessions.contains(session))
+    }
+    
+    pub fn set_final(&mut self, season: u32, is_final: bool) {
+        self.seasons.entry(season).or_default().is_final = is_final;
+    }
+}
@@ -1,8 +1,13 @@
 // Export modules for use in tests and as a library
+pub mod circuits;
 pub mod data;
 pub mod models;
+pub mod points;
+pub mod ratings;
 pub mod simulator;
+pub mod standings;
 pub mod utils;
+pub mod weather;
 
 // Re-export main simulator modules for convenience
 pub use simulator::historical;
@@ -1,16 +1,46 @@
 use anyhow::Result;
+use chrono::{NaiveDate, Utc};
 use clap::{Parser, Subcommand};
 use colored::Colorize;
+use std::path::PathBuf;
 
-mod data;
-mod simulator;
-mod utils;
-mod models;
+use f1_cli_simulator::{data, models, simulator, utils};
+use f1_cli_simulator::points::PointsSystem;
+use f1_cli_simulator::simulator::simulation::scenario::Scenario;
+use f1_cli_simulator::weather::Weather;
 
 #[derive(Parser)]
 #[command(name = "f1-cli-simulator")]
 #[command(about = "Formula 1 Race Simulator CLI Tool", long_about = None)]
 struct Cli {
+    /// Directory for cached race data (default: $XDG_DATA_HOME/f1-cli-simulator)
+    #[arg(long, global = true)]
+    data_dir: Option<PathBuf>,
+    
+    /// Base URL of the Ergast-compatible API (default: the Jolpica mirror)
+    #[arg(long, global = true)]
+    api_url: Option<String>,
+    
+    /// Path to a JSON config file (default: $XDG_CONFIG_HOME/f1-cli-simulator/config.json)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    
+    /// Season to treat as the current one (default: the current year, or the next once its cached calendar is over)
+    #[arg(long, global = true)]
+    current_season: Option<u32>,
+    
+    /// Use cached data only and never access the network
+    #[arg(long, global = true)]
+    offline: bool,
+    
+    /// JSON file with the drivers to simulate (default: the season's entry list)
+    #[arg(long, global = true)]
+    roster: Option<PathBuf>,
+    
+    /// JSON file with circuits adding to or replacing the bundled circuit catalogue
+    #[arg(long, global = true)]
+    circuits: Option<PathBuf>,
+    
     #[command(subcommand)]
     command: Commands,
 }
@@ -24,31 +54,58 @@ enum Commands {
         season: u32,
         
         /// GP name (e.g., "monaco", "spa", "monza")
-        #[arg(short, long)]
-        gp: String,
+        #[arg(short, long, required_unless_present = "round")]
+        gp: Option<String>,
         
-        /// Session type: "practice", "qualifying", or "race"
+        /// Round number within the season (takes precedence over --gp)
+        #[arg(short = 'R', long)]
+        round: Option<u32>,
+        
+        /// Session type: "race", "qualifying", "sprint", "sprint-qualifying", "pitstops" or "practice"
         #[arg(short = 't', long, default_value = "race")]
         session: String,
         
         /// Run in interactive mode (lap-by-lap playback)
         #[arg(short, long)]
         interactive: bool,
+        
+        /// Seed for the random draws, so that a run can be repeated exactly
+        #[arg(long)]
+        seed: Option<u64>,
     },
     
     /// Simulate an upcoming F1 race using predictive modeling
     Predict {
-        /// Season year (e.g., 2025)
+        /// Season year (default: the current season)
         #[arg(short, long)]
-        season: u32,
+        season: Option<u32>,
         
-        /// GP name (e.g., "monaco", "spa", "monza")
+        /// GP name (default: the next upcoming race)
         #[arg(short, long)]
-        gp: String,
+        gp: Option<String>,
         
         /// Number of simulation runs to aggregate results from
         #[arg(short, long, default_value_t = 100)]
         runs: u32,
+        
+        /// Seed for the random draws, so that a run can be repeated exactly
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    
+    /// Simulate the rest of a season and estimate the championship odds
+    SeasonSim {
+        /// Season year (default: the current season)
+        #[arg(short, long)]
+        season: Option<u32>,
+        
+        /// Number of times to simulate the remaining rounds
+        #[arg(short, long, default_value_t = 100)]
+        runs: u32,
+        
+        /// Seed for the random draws, so that a run can be repeated exactly
+        #[arg(long)]
+        seed: Option<u64>,
     },
     
     /// Simulate a custom F1 race with adjustable parameters
@@ -65,9 +122,10 @@ enum Commands {
         #[arg(short = 'r', long, default_value_t = 0.95)]
         reliability: f64,
         
-        /// Weather factor (0.7-1.2, where lower means wetter conditions)
-        #[arg(short = 'w', long, default_value_t = 1.0)]
-        weather: f64,
+        /// Weather: "dry", "wet", "random" with an optional seed ("random:42"), or rain
+        /// intensity (0-1) from given laps on, e.g. "20:0.6,35:0" (default: dry, or the scenario's)
+        #[arg(short = 'w', long)]
+        weather: Option<String>,
         
         /// Disable random racing incidents
         #[arg(short = 'n', long)]
@@ -76,6 +134,54 @@ enum Commands {
         /// Run in interactive mode (lap-by-lap updates)
         #[arg(short, long)]
         interactive: bool,
+        
+        /// Simulate a sprint (about 100 km, sprint points) instead of a full race
+        #[arg(long)]
+        sprint: bool,
+        
+        /// Tyre sets per car, e.g. "soft=4,hard=1" (default: 8 soft, 3 medium, 2 hard, 4 intermediate, 3 wet)
+        #[arg(long)]
+        tyres: Option<String>,
+        
+        /// Tyre wear multiplier for every compound, e.g. "1.5", or per compound, e.g. "soft=1.5,hard=0.8"
+        #[arg(long)]
+        degradation: Option<String>,
+        
+        /// A driver's pit strategy, e.g. "VER=two-stop:undercut" or "NOR=soft/hard@18" (repeatable)
+        #[arg(long = "strategy", value_name = "DRIVER=STRATEGY")]
+        strategies: Vec<String>,
+        
+        /// JSON file with the strategies and weather of the scenario to simulate
+        #[arg(long)]
+        scenario: Option<PathBuf>,
+        
+        /// Seed for the random draws, so that a run can be repeated exactly
+        #[arg(long)]
+        seed: Option<u64>,
+        
+        /// Log every race event (laps, passes, stops, retirements, fastest laps,
+        /// safety cars and the finish) to a file, one JSON object per line
+        #[arg(long, value_name = "PATH")]
+        events: Option<PathBuf>,
+    },
+    
+    /// Show championship standings computed from cached race results
+    Standings {
+        /// Season year (default: the current season, or the last one until it has results)
+        #[arg(short, long)]
+        season: Option<u32>,
+        
+        /// Show the standings after this round (default: the latest cached round)
+        #[arg(short = 'R', long)]
+        round: Option<u32>,
+        
+        /// Show the constructors' championship instead of the drivers'
+        #[arg(short, long)]
+        constructors: bool,
+        
+        /// Rescore every result with the points system of this season
+        #[arg(long, value_name = "SEASON")]
+        points_system: Option<u32>,
     },
     
     /// List available historical race data
@@ -98,6 +204,14 @@ enum Commands {
         /// Fetch all historical seasons (from 1950 to current)
         #[arg(short, long)]
         all: bool,
+        
+        /// Refetch data even if the manifest marks it as complete
+        #[arg(short, long)]
+        force: bool,
+        
+        /// Refetch rounds raced on or after this date (YYYY-MM-DD or YYYY)
+        #[arg(long, value_parser = parse_since)]
+        since: Option<NaiveDate>,
     },
 }
 
@@ -107,39 +221,132 @@ fn main() -> Result<()> {
     
     let cli = Cli::parse();
     
+    // Settings from the command line take precedence over the config file and environment
+    let mut config = data::DataConfig::load(cli.config.as_deref())?;
+    if let Some(data_dir) = cli.data_dir {
+        config.data_dir = data_dir;
+    }
+    if let Some(api_url) = cli.api_url {
+        config.api_base_url = api_url.trim_end_matches('/').to_string();
+    }
+    if let Some(season) = cli.current_season {
+        config.current_season = Some(season);
+    }
+    if cli.offline {
+        config.offline = true;
+    }
+    if let Some(roster) = cli.roster {
+        config.roster = Some(roster);
+    }
+    if let Some(circuits) = cli.circuits {
+        config.circuits = Some(circuits);
+    }
+    
     match cli.command {
-        Commands::Historical { season, gp, session, interactive } => {
-            println!("Simulating historical {} session for {} GP {}{}", 
-                     session, gp, season, 
+        Commands::Historical { season, gp, round, session, interactive, seed } => {
+            // A round number is accepted anywhere a GP name is
+            let (gp, label) = match round {
+                Some(round) => (round.to_string(), format!("round {} of", round)),
+                None => {
+                    let gp = gp.unwrap_or_default();
+                    let label = format!("{} GP", gp);
+                    (gp, label)
+                },
+            };
+            println!("Simulating historical {} session for {} {}{}", 
+                     session, label, season, 
                      if interactive { " in interactive mode" } else { "" });
-            simulator::historical::simulate(season, &gp, &session, interactive)
+            simulator::historical::simulate(season, &gp, &session, interactive, seed, &config)
         },
-        Commands::Predict { season, gp, runs } => {
+        Commands::Predict { season, gp, runs, seed } => {
+            let (season, gp, round) = match gp {
+                Some(gp) => (season.unwrap_or_else(|| config.current_season()), gp, None),
+                None => {
+                    // Without a GP, predict the next race on the calendar
+                    let today = Utc::now().date_naive();
+                    let (season, next) = data::next_race(&config, season, today)?;
+                    println!("Next race: {} (round {}, {})", next.race_name, next.round, next.date);
+                    (season, next.circuit_id, Some(next.round))
+                },
+            };
             println!("Predicting {} GP {} with {} simulation runs", gp, season, runs);
-            simulator::prediction::simulate(season, &gp, runs)
+            simulator::prediction::simulate(season, &gp, round, runs, seed, &config)
+        },
+        Commands::SeasonSim { season, runs, seed } => {
+            let season = season.unwrap_or_else(|| config.current_season());
+            simulator::season::simulate(season, runs, seed, &config)
         },
-        Commands::Simulate { season, gp, reliability, weather, no_incidents, interactive } => {
-            println!("Simulating custom race for {} GP {} with reliability {}, weather {}, no incidents: {}, interactive: {}", 
-                     gp, season, reliability, weather, no_incidents, interactive);
+        Commands::Simulate { season, gp, reliability, weather, no_incidents, interactive, sprint, tyres, degradation, strategies, scenario, seed, events } => {
+            println!("Simulating custom race for {} GP {} with reliability {}, no incidents: {}, interactive: {}", 
+                     gp, season, reliability, no_incidents, interactive);
             
-            let params = models::SimulationParameters {
+            // Strategies given on the command line override those of the scenario file, whatever key names the driver
+            let scenario = match scenario {
+                Some(path) => Scenario::from_file(&path)?,
+                None => Scenario::default(),
+            }.with_strategies(&strategies)?;
+            
+            // The weather given on the command line, or else the scenario's
+            let weather: Weather = match weather {
+                Some(weather) => weather.parse()?,
+                None => scenario.weather.clone().unwrap_or_default(),
+            };
+            
+            let mut params = models::SimulationParameters {
                 reliability_factor: reliability,
-                weather_factor: weather,
+                weather,
                 random_incidents: !no_incidents,
+                ..Default::default()
+            };
+            if let Some(tyres) = tyres {
+                params.tyre_allocation = params.tyre_allocation.with_overrides(&tyres)?;
+            }
+            if let Some(degradation) = degradation {
+                params.degradation = params.degradation.with_overrides(&degradation)?;
+            }
+            
+            let format = if sprint { models::RaceFormat::Sprint } else { models::RaceFormat::GrandPrix };
+            simulator::simulation::simulate(season, &gp, params, format, interactive, &scenario, seed, events.as_deref(), &config)
+        },
+        Commands::Standings { season, round, constructors, points_system: points_system_season } => {
+            let season = match season {
+                Some(season) => season,
+                None => data::default_standings_season(&config)?,
             };
+            let points_system = points_system_season.map(PointsSystem::for_season);
+            let standings = data::load_standings(&config, season, round, points_system.as_ref())?;
             
-            simulator::simulation::simulate(season, &gp, params, interactive)
+            let (title, entries) = if constructors {
+                ("Constructors' Championship", &standings.constructors)
+            } else {
+                ("Drivers' Championship", &standings.drivers)
+            };
+            println!("\n{}", format!("{} {} after round {}", season, title, standings.round).green().bold());
+            if let Some(scoring) = points_system_season {
+                println!("{}", format!("Rescored with the {} points system", scoring).dimmed());
+            }
+            println!("{}", utils::format_standings(entries, constructors));
+            Ok(())
         },
         Commands::List { season } => {
             match season {
                 Some(year) => println!("Listing available race data for season {}", year),
                 None => println!("Listing all available race data"),
             }
-            data::list_available_data(season)
+            data::list_available_data(&config, season)
         },
-        Commands::Update { previous, seasons, all } => {
+        Commands::Update { previous, seasons, all, force, since } => {
             println!("Updating F1 race data...");
-            data::update_data(previous, seasons, all)
+            let options = data::UpdateOptions { previous, seasons, all, force, since };
+            data::update_data(&config, &options)
         },
     }
 }
+
+/// Parse a `--since` date given as YYYY-MM-DD or just a year
+fn parse_since(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .or_else(|| value.parse::<i32>().ok().and_then(|year| NaiveDate::from_ymd_opt(year, 1, 1)))
+        .ok_or_else(|| format!("'{}' is not a date (YYYY-MM-DD) or a year", value))
+}
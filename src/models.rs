@@ -1,4 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::weather::Weather;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Driver {
@@ -9,22 +14,102 @@ pub struct Driver {
     pub number: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Circuit {
     pub id: String,
     pub name: String,
     pub country: String,
     pub city: String,
     pub length_km: f64,
+    /// Laps of a full grand prix distance
     pub laps: u32,
+    #[serde(default)]
+    pub corners: u32,
+    #[serde(default)]
+    pub drs_zones: u32,
+    /// How hard it is to pass, from 0 (easy) to 1 (next to impossible)
+    #[serde(default)]
+    pub overtaking_difficulty: f64,
+    /// Time lost driving through the pit lane compared to staying out, in seconds
+    #[serde(default)]
+    pub pit_lane_loss_secs: f64,
+    /// Chance of a wet race
+    #[serde(default)]
+    pub weather_risk: f64,
+    /// How hard the circuit is on tyres relative to an average track
+    #[serde(default)]
+    pub tyre_wear: f64,
+    /// Chance that a crash brings out the safety car
+    #[serde(default)]
+    pub safety_car_chance: f64,
+    /// Chance that a crash, or a car stopped on track, brings out the virtual safety car
+    #[serde(default)]
+    pub virtual_safety_car_chance: f64,
+    /// Chance that a crash stops the race with a red flag
+    #[serde(default)]
+    pub red_flag_chance: f64,
+}
+
+/// Seconds per kilometre and per corner of a representative race lap
+const LAP_SECS_PER_KM: f64 = 10.2;
+const LAP_SECS_PER_CORNER: f64 = 2.0;
+
+impl Circuit {
+    /// Race lap time of an average car, from the length and number of corners
+    pub fn base_lap_time(&self) -> Duration {
+        Duration::from_secs_f64(self.length_km * LAP_SECS_PER_KM + self.corners as f64 * LAP_SECS_PER_CORNER)
+    }
+    
+    /// Multiplier of the chance to complete a pass: lower where overtaking is
+    /// difficult, higher with more DRS zones
+    pub fn overtaking_factor(&self) -> f64 {
+        (1.0 - self.overtaking_difficulty).max(0.05) * (1.0 + 0.15 * self.drs_zones as f64)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RaceResult {
     pub position: u32,
     pub driver: Driver,
+    /// Car number raced at this event, which can differ from the permanent number
+    #[serde(default)]
+    pub number: u32,
+    /// Ergast constructor id; empty for simulated results
+    #[serde(default)]
+    pub constructor_id: String,
+    /// Starting position (0 for a pit lane start or when unknown)
+    #[serde(default)]
+    pub grid: u32,
+    pub time: Option<String>,
+    /// Race time in milliseconds, only present for classified finishers on the lead lap
+    #[serde(default)]
+    pub time_millis: Option<u64>,
+    /// Fractional for shared drives and half-points races
+    pub points: f64,
+    pub laps: u32,
+    pub status: String,
+    /// Only recorded from 2004 onwards
+    #[serde(default)]
+    pub fastest_lap: Option<FastestLap>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FastestLap {
+    /// Rank of this lap among every driver's fastest lap
+    pub rank: u32,
+    pub lap: u32,
+    pub time: String,
+    pub average_speed_kph: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SprintResult {
+    pub position: u32,
+    pub driver: Driver,
+    /// Starting position, set by sprint qualifying (0 for a pit lane start)
+    pub grid: u32,
     pub time: Option<String>,
-    pub points: u32,
+    pub points: f64,
     pub laps: u32,
     pub status: String,
 }
@@ -46,6 +131,32 @@ pub struct PracticeResult {
     pub laps: u32,
 }
 
+/// Every driver's position and lap time at the end of one lap of a race
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lap {
+    pub number: u32,
+    pub timings: Vec<LapTiming>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LapTiming {
+    pub driver_id: String,
+    pub position: u32,
+    pub time: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PitStop {
+    pub driver_id: String,
+    /// Stop number for this driver, starting at 1
+    pub stop: u32,
+    pub lap: u32,
+    /// Local time of day the car entered the pit lane
+    pub time_of_day: String,
+    /// Time from pit entry to pit exit; the feed does not publish stationary time
+    pub duration: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Race {
     pub season: u32,
@@ -56,19 +167,183 @@ pub struct Race {
     pub results: Vec<RaceResult>,
 }
 
+/// The results of one round: the grand prix, qualifying and, on sprint weekends, the sprint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekendResults {
+    pub race: Race,
+    #[serde(default)]
+    pub qualifying: Vec<QualifyingResult>,
+    pub sprint: Vec<SprintResult>,
+}
+
+/// Whether a finish is classified: running at the flag, or covering at least 90% of the winner's laps
+pub fn is_classified(status: &str, laps: u32, winner_laps: u32) -> bool {
+    status != "Disqualified" && (status == "Finished" || status == "Lapped" || status.starts_with('+') || laps * 10 >= winner_laps * 9)
+}
+
+/// Distance of a sprint race in kilometres
+pub const SPRINT_DISTANCE_KM: f64 = 100.0;
+
+/// Kind of race being simulated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RaceFormat {
+    #[default]
+    GrandPrix,
+    Sprint,
+}
+
+impl RaceFormat {
+    /// Race distance in laps: the full distance, or about 100 km for a sprint
+    pub fn laps(self, circuit: &Circuit) -> u32 {
+        match self {
+            RaceFormat::GrandPrix => circuit.laps,
+            RaceFormat::Sprint if circuit.length_km > 0.0 => (SPRINT_DISTANCE_KM / circuit.length_km).ceil() as u32,
+            RaceFormat::Sprint => circuit.laps / 3,
+        }
+    }
+}
+
+/// Tyre compound
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compound {
+    Soft,
+    Medium,
+    Hard,
+    #[serde(alias = "inter")]
+    Intermediate,
+    Wet,
+}
+
+impl Compound {
+    /// Every compound, softest slick first
+    pub const ALL: [Compound; 5] = [Compound::Soft, Compound::Medium, Compound::Hard, Compound::Intermediate, Compound::Wet];
+    
+    /// Whether the compound is a slick for a dry track
+    pub fn is_dry(self) -> bool {
+        matches!(self, Compound::Soft | Compound::Medium | Compound::Hard)
+    }
+    
+    /// Single letter shown on timing screens
+    pub fn letter(self) -> char {
+        match self {
+            Compound::Soft => 'S',
+            Compound::Medium => 'M',
+            Compound::Hard => 'H',
+            Compound::Intermediate => 'I',
+            Compound::Wet => 'W',
+        }
+    }
+}
+
+impl fmt::Display for Compound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Compound::Soft => "Soft",
+            Compound::Medium => "Medium",
+            Compound::Hard => "Hard",
+            Compound::Intermediate => "Intermediate",
+            Compound::Wet => "Wet",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Compound {
+    type Err = anyhow::Error;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "soft" | "s" => Ok(Compound::Soft),
+            "medium" | "m" => Ok(Compound::Medium),
+            "hard" | "h" => Ok(Compound::Hard),
+            "intermediate" | "inter" | "i" => Ok(Compound::Intermediate),
+            "wet" | "w" => Ok(Compound::Wet),
+            _ => Err(anyhow::anyhow!("Unknown tyre compound: {}", s)),
+        }
+    }
+}
+
+/// A value for each tyre compound
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PerCompound<T> {
+    pub soft: T,
+    pub medium: T,
+    pub hard: T,
+    pub intermediate: T,
+    pub wet: T,
+}
+
+impl<T: Copy> PerCompound<T> {
+    /// The same value for every compound
+    pub const fn uniform(value: T) -> Self {
+        PerCompound { soft: value, medium: value, hard: value, intermediate: value, wet: value }
+    }
+    
+    pub fn get(&self, compound: Compound) -> T {
+        match compound {
+            Compound::Soft => self.soft,
+            Compound::Medium => self.medium,
+            Compound::Hard => self.hard,
+            Compound::Intermediate => self.intermediate,
+            Compound::Wet => self.wet,
+        }
+    }
+    
+    pub fn set(&mut self, compound: Compound, value: T) {
+        match compound {
+            Compound::Soft => self.soft = value,
+            Compound::Medium => self.medium = value,
+            Compound::Hard => self.hard = value,
+            Compound::Intermediate => self.intermediate = value,
+            Compound::Wet => self.wet = value,
+        }
+    }
+}
+
+impl<T: Copy + FromStr> PerCompound<T> {
+    /// Override values from a list like "soft=2,hard=3", or every value with a
+    /// single one like "1.5"
+    pub fn with_overrides(mut self, spec: &str) -> anyhow::Result<Self> {
+        let parse_value = |value: &str| value.trim().parse::<T>()
+            .map_err(|_| anyhow::anyhow!("Invalid value for a tyre compound: {}", value));
+        
+        if !spec.contains('=') {
+            return Ok(Self::uniform(parse_value(spec)?));
+        }
+        
+        for entry in spec.split(',').filter(|e| !e.trim().is_empty()) {
+            let (compound, value) = entry.split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Expected compound=value, got: {}", entry))?;
+            self.set(compound.parse()?, parse_value(value)?);
+        }
+        Ok(self)
+    }
+}
+
+/// Sets of each compound every car has for a grand prix
+pub const TYRE_ALLOCATION: PerCompound<u32> = PerCompound { soft: 8, medium: 3, hard: 2, intermediate: 4, wet: 3 };
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimulationParameters {
     pub reliability_factor: f64,
-    pub weather_factor: f64,
+    /// The weather to race in, changing lap by lap
+    pub weather: Weather,
     pub random_incidents: bool,
+    /// Sets of each compound every car may use
+    pub tyre_allocation: PerCompound<u32>,
+    /// Multipliers on how fast each compound wears, 1.0 being the norm
+    pub degradation: PerCompound<f64>,
 }
 
 impl Default for SimulationParameters {
     fn default() -> Self {
         Self {
             reliability_factor: 0.95,
-            weather_factor: 1.0,
+            weather: Weather::Dry,
             random_incidents: true,
+            tyre_allocation: TYRE_ALLOCATION,
+            degradation: PerCompound::uniform(1.0),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,148 @@
+use crate::models::RaceFormat;
+
+/// Extra points for the fastest lap of a grand prix
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FastestLapBonus {
+    pub points: f64,
+    /// Only awarded to a driver finishing in this position or better
+    pub top: Option<u32>,
+}
+
+/// What a grand prix scores when it is stopped before three quarters of its distance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortRaceRule {
+    /// Half points (until 2021)
+    HalfPoints,
+    /// Reduced tables depending on the distance completed (from 2022)
+    Scaled,
+}
+
+/// The points tables and rules a season was scored with
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointsSystem {
+    /// Points for each grand prix finishing position, winner first
+    pub race: Vec<f64>,
+    /// Points for each sprint finishing position; empty in seasons without sprints
+    pub sprint: Vec<f64>,
+    pub fastest_lap: Option<FastestLapBonus>,
+    /// Drivers who shared a car split its points equally
+    pub shared_drives: bool,
+    pub short_race: ShortRaceRule,
+}
+
+/// Share of the distance below which a grand prix is a short race
+const FULL_POINTS_DISTANCE: f64 = 0.75;
+
+/// Reduced grand prix tables from 2022, by the share of distance completed
+const SCALED_UP_TO_25: [f64; 5] = [6.0, 4.0, 3.0, 2.0, 1.0];
+const SCALED_UP_TO_50: [f64; 9] = [13.0, 10.0, 8.0, 6.0, 5.0, 4.0, 3.0, 2.0, 1.0];
+const SCALED_UP_TO_75: [f64; 10] = [19.0, 14.0, 12.0, 9.0, 8.0, 6.0, 5.0, 3.0, 2.0, 1.0];
+
+impl PointsSystem {
+    /// The points system in force in a season
+    pub fn for_season(season: u32) -> Self {
+        let race: &[f64] = match season {
+            ..=1959 => &[8.0, 6.0, 4.0, 3.0, 2.0],
+            1960 => &[8.0, 6.0, 4.0, 3.0, 2.0, 1.0],
+            1961..=1990 => &[9.0, 6.0, 4.0, 3.0, 2.0, 1.0],
+            1991..=2002 => &[10.0, 6.0, 4.0, 3.0, 2.0, 1.0],
+            2003..=2009 => &[10.0, 8.0, 6.0, 5.0, 4.0, 3.0, 2.0, 1.0],
+            _ => &[25.0, 18.0, 15.0, 12.0, 10.0, 8.0, 6.0, 4.0, 2.0, 1.0],
+        };
+        
+        let sprint: &[f64] = match season {
+            ..=2020 => &[],
+            2021 => &[3.0, 2.0, 1.0],
+            _ => &[8.0, 7.0, 6.0, 5.0, 4.0, 3.0, 2.0, 1.0],
+        };
+        
+        let fastest_lap = match season {
+            ..=1959 => Some(FastestLapBonus { points: 1.0, top: None }),
+            2019..=2024 => Some(FastestLapBonus { points: 1.0, top: Some(10) }),
+            _ => None,
+        };
+        
+        PointsSystem {
+            race: race.to_vec(),
+            sprint: sprint.to_vec(),
+            fastest_lap,
+            shared_drives: season <= 1957,
+            short_race: if season >= 2022 { ShortRaceRule::Scaled } else { ShortRaceRule::HalfPoints },
+        }
+    }
+    
+    /// Points for a finishing position over the full distance
+    pub fn points_for(&self, format: RaceFormat, position: u32) -> f64 {
+        table_points(self.table(format), position)
+    }
+    
+    /// Whether setting the fastest lap from this finishing position earns a bonus
+    pub fn awards_fastest_lap(&self, format: RaceFormat, position: u32) -> bool {
+        format == RaceFormat::GrandPrix
+            && self.fastest_lap.is_some_and(|bonus| bonus.top.is_none_or(|top| position <= top))
+    }
+    
+    /// Points for a finish, given whether the driver set the fastest lap and the
+    /// share of the scheduled distance that was completed (1.0 for a full race).
+    ///
+    /// The short race rule only applies to grand prix, and a shortened race
+    /// never awards the fastest lap bonus.
+    pub fn score(&self, format: RaceFormat, position: u32, fastest_lap: bool, distance: f64) -> f64 {
+        if format == RaceFormat::Sprint || distance >= FULL_POINTS_DISTANCE {
+            let bonus = match self.fastest_lap {
+                Some(bonus) if fastest_lap && self.awards_fastest_lap(format, position) => bonus.points,
+                _ => 0.0,
+            };
+            return self.points_for(format, position) + bonus;
+        }
+        
+        if distance <= 0.0 {
+            return 0.0;
+        }
+        
+        match self.short_race {
+            ShortRaceRule::HalfPoints => self.points_for(format, position) / 2.0,
+            ShortRaceRule::Scaled if distance <= 0.25 => table_points(&SCALED_UP_TO_25, position),
+            ShortRaceRule::Scaled if distance <= 0.5 => table_points(&SCALED_UP_TO_50, position),
+            ShortRaceRule::Scaled => table_points(&SCALED_UP_TO_75, position),
+        }
+    }
+    
+    /// Each driver's share of the points scored by a car they shared
+    pub fn shared_drive(&self, points: f64, drivers: usize) -> f64 {
+        if self.shared_drives && drivers > 1 {
+            points / drivers as f64
+        } else {
+            points
+        }
+    }
+    
+    /// Number of positions that score points in a format
+    pub fn scoring_positions(&self, format: RaceFormat) -> u32 {
+        self.table(format).len() as u32
+    }
+    
+    fn table(&self, format: RaceFormat) -> &[f64] {
+        match format {
+            RaceFormat::GrandPrix => &self.race,
+            RaceFormat::Sprint => &self.sprint,
+        }
+    }
+}
+
+/// Share of the scheduled distance a winner who ran `laps` of `scheduled_laps`
+/// completed, for `PointsSystem::score`; a full race if the schedule is unknown
+pub fn distance_completed(laps: u32, scheduled_laps: u32) -> f64 {
+    if scheduled_laps == 0 {
+        return 1.0;
+    }
+    (laps as f64 / scheduled_laps as f64).min(1.0)
+}
+
+// Points for a position in a table, zero outside it
+fn table_points(table: &[f64], position: u32) -> f64 {
+    position.checked_sub(1)
+        .and_then(|i| table.get(i as usize))
+        .copied()
+        .unwrap_or(0.0)
+}
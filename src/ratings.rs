@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use crate::data::index::canonical_key;
+use crate::models::{is_classified, Driver, WeekendResults};
+
+/// Seasons of results the ratings are fitted from, ending with the season simulated
+pub const RATING_SEASONS: u32 = 3;
+
+/// Largest change of a driver's rating in one race
+const DRIVER_K: f64 = 24.0;
+
+/// Largest change of a constructor's rating in one race
+const CONSTRUCTOR_K: f64 = 16.0;
+
+/// A qualifying session counts for this much of a race
+const QUALIFYING_WEIGHT: f64 = 0.5;
+
+/// Share of a rating carried over into the next season
+const SEASON_CARRY_OVER: f64 = 0.7;
+
+/// Rating of a driver without any results: a little below the average of 0
+const ROOKIE_RATING: f64 = -50.0;
+
+/// Base performance of an average driver and car, and the change per rating point
+const AVERAGE_SKILL: f64 = 0.93;
+const SKILL_PER_POINT: f64 = 0.00025;
+const AVERAGE_TEAM_PERFORMANCE: f64 = 0.91;
+const TEAM_PERFORMANCE_PER_POINT: f64 = 0.0004;
+
+/// Elo-style ratings of drivers and constructors, fitted from results.
+///
+/// Every classified finish is treated as a win over each driver further down
+/// the order, weighed against the combined rating of driver and car. A
+/// teammate comparison only moves the driver ratings, so car and driver
+/// separate over time. Ratings regress towards the average between seasons.
+#[derive(Debug, Clone, Default)]
+pub struct Ratings {
+    /// Driver ratings by driver id
+    drivers: HashMap<String, f64>,
+    /// Constructor ratings by canonical team name
+    constructors: HashMap<String, f64>,
+    /// Driver ids by code and name, for drivers known under another id
+    aliases: HashMap<String, String>,
+    /// Season of the last session rated
+    season: Option<u32>,
+}
+
+impl Ratings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Fit ratings from the race and qualifying results of the weekends, in date order
+    pub fn fit(weekends: &[WeekendResults]) -> Self {
+        let mut ratings = Ratings::new();
+        
+        let mut weekends: Vec<&WeekendResults> = weekends.iter().collect();
+        weekends.sort_by_key(|w| (w.race.season, w.race.round));
+        
+        for weekend in weekends {
+            let season = weekend.race.season;
+            
+            let mut qualifying: Vec<_> = weekend.qualifying.iter().collect();
+            qualifying.sort_by_key(|q| q.position);
+            let order: Vec<&Driver> = qualifying.iter().map(|q| &q.driver).collect();
+            ratings.rate_session(season, &order, QUALIFYING_WEIGHT);
+            
+            let winner_laps = weekend.race.results.iter().map(|r| r.laps).max().unwrap_or(0);
+            let mut finishers: Vec<_> = weekend.race.results.iter()
+                .filter(|r| is_classified(&r.status, r.laps, winner_laps))
+                .collect();
+            finishers.sort_by_key(|r| r.position);
+            let order: Vec<&Driver> = finishers.iter().map(|r| &r.driver).collect();
+            ratings.rate_session(season, &order, 1.0);
+        }
+        
+        ratings
+    }
+    
+    /// Update the ratings with a session the drivers finished in the order given.
+    /// `weight` scales the changes, 1.0 for a race.
+    pub fn rate_session(&mut self, season: u32, order: &[&Driver], weight: f64) {
+        if order.len() < 2 {
+            return;
+        }
+        
+        if let Some(last) = self.season.filter(|&last| last < season) {
+            let carry_over = SEASON_CARRY_OVER.powi((season - last) as i32);
+            self.drivers.values_mut().for_each(|r| *r *= carry_over);
+            self.constructors.values_mut().for_each(|r| *r *= carry_over);
+        }
+        self.season = Some(self.season.map_or(season, |last| last.max(season)));
+        
+        for driver in order {
+            // Drivers from before codes were introduced all share the placeholder code
+            if driver.code != "???" {
+                self.aliases.insert(driver.code.clone(), driver.id.clone());
+            }
+            self.aliases.insert(driver.name.clone(), driver.id.clone());
+        }
+        
+        // Every pair is scored from the ratings before the session
+        let strengths: Vec<f64> = order.iter()
+            .map(|d| self.driver_rating(d) + self.constructor_rating(&d.team))
+            .collect();
+        let scale = weight / (order.len() - 1) as f64;
+        
+        let mut driver_changes = vec![0.0; order.len()];
+        let mut constructor_changes: HashMap<String, f64> = HashMap::new();
+        
+        for ahead in 0..order.len() {
+            for behind in (ahead + 1)..order.len() {
+                let expected = 1.0 / (1.0 + 10f64.powf((strengths[behind] - strengths[ahead]) / 400.0));
+                let surprise = (1.0 - expected) * scale;
+                
+                driver_changes[ahead] += DRIVER_K * surprise;
+                driver_changes[behind] -= DRIVER_K * surprise;
+                
+                let (team_ahead, team_behind) = (canonical_key(&order[ahead].team), canonical_key(&order[behind].team));
+                if team_ahead != team_behind {
+                    *constructor_changes.entry(team_ahead).or_insert(0.0) += CONSTRUCTOR_K * surprise;
+                    *constructor_changes.entry(team_behind).or_insert(0.0) -= CONSTRUCTOR_K * surprise;
+                }
+            }
+        }
+        
+        for (driver, change) in order.iter().zip(driver_changes) {
+            let rating = self.driver_rating(driver) + change;
+            self.drivers.insert(driver.id.clone(), rating);
+        }
+        for (team, change) in constructor_changes {
+            let rating = self.constructors.get(&team).copied().unwrap_or(0.0) + change;
+            self.constructors.insert(team, rating);
+        }
+    }
+    
+    /// Whether no results have been rated
+    pub fn is_empty(&self) -> bool {
+        self.drivers.is_empty()
+    }
+    
+    /// A driver's rating, found by id, code or name
+    pub fn driver_rating(&self, driver: &Driver) -> f64 {
+        [&driver.id, &driver.code, &driver.name].into_iter()
+            .find_map(|key| {
+                let id = self.aliases.get(key.as_str()).unwrap_or(key);
+                self.drivers.get(id)
+            })
+            .copied()
+            .unwrap_or(ROOKIE_RATING)
+    }
+    
+    /// A constructor's rating. "Red Bull Racing" also matches a team rated as "Red Bull".
+    pub fn constructor_rating(&self, team: &str) -> f64 {
+        let key = canonical_key(team);
+        if let Some(&rating) = self.constructors.get(&key) {
+            return rating;
+        }
+        
+        self.constructors.iter()
+            .filter(|(rated, _)| key.starts_with(&format!("{}_", rated)) || rated.starts_with(&format!("{}_", key)))
+            .max_by_key(|(rated, _)| rated.len())
+            .map(|(_, &rating)| rating)
+            .unwrap_or(0.0)
+    }
+    
+    /// A driver's skill on the 0-1 scale the simulators use, higher is better
+    pub fn driver_skill(&self, driver: &Driver) -> f64 {
+        (AVERAGE_SKILL + self.driver_rating(driver) * SKILL_PER_POINT).clamp(0.8, 0.99)
+    }
+    
+    /// A team's car performance on the 0-1 scale the simulators use, higher is better
+    pub fn team_performance(&self, team: &str) -> f64 {
+        (AVERAGE_TEAM_PERFORMANCE + self.constructor_rating(team) * TEAM_PERFORMANCE_PER_POINT).clamp(0.8, 0.99)
+    }
+}
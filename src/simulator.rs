@@ -1,3 +1,4 @@
 pub mod historical;
 pub mod prediction;
-pub mod simulation;
\ No newline at end of file
+pub mod season;
+pub mod simulation;
@@ -1,39 +1,46 @@
 use anyhow::Result;
 use colored::Colorize;
+use rand::Rng;
 use std::io::{self, Write};
 use std::thread;
 use std::time::Duration;
 use std::collections::HashMap;
 
-use crate::data::{DataInterface, DataManager};
-use crate::models::{RaceResult, Circuit};
+use crate::data::{DataConfig, DataInterface, DataManager};
+use crate::models::{RaceResult, Lap, PitStop};
 use crate::utils;
 
-pub fn simulate(season: u32, gp: &str, session: &str, interactive: bool) -> Result<()> {
-    let data_manager = DataManager;
-    simulate_with_data_module(season, gp, session, interactive, &data_manager)
+pub fn simulate(season: u32, gp: &str, session: &str, interactive: bool, seed: Option<u64>, config: &DataConfig) -> Result<()> {
+    let data_manager = DataManager::new(config.clone());
+    simulate_with_data_module(season, gp, session, interactive, &mut utils::seeded_rng(seed), &data_manager)
 }
 
-pub fn simulate_with_data_module(
+/// Show a session from the data module; a race reconstructed from its results
+/// draws the passes, retirements and gaps from `rng`
+pub fn simulate_with_data_module<R: Rng + ?Sized>(
     season: u32, 
     gp: &str, 
     session: &str,
     interactive: bool,
+    rng: &mut R,
     data_module: &impl DataInterface
 ) -> Result<()> {
     println!("Loading historical data for {} GP {} - {} session", gp, season, session);
     
     match session.to_lowercase().as_str() {
-        "race" => simulate_race(season, gp, interactive, data_module),
+        "race" => simulate_race(season, gp, interactive, rng, data_module),
         "qualifying" => simulate_qualifying(season, gp, data_module),
+        "sprint" => simulate_sprint(season, gp, data_module),
+        "pitstops" | "pit-stops" | "pit_stops" => simulate_pit_stops(season, gp, data_module),
+        "sprint-qualifying" | "sprint_qualifying" | "sprint-shootout" => simulate_sprint_qualifying(season, gp, data_module),
         "practice" | "fp1" | "practice1" => simulate_practice(season, gp, 1, data_module),
         "fp2" | "practice2" => simulate_practice(season, gp, 2, data_module),
         "fp3" | "practice3" => simulate_practice(season, gp, 3, data_module),
-        _ => Err(anyhow::anyhow!("Unknown session type: {}. Valid options are race, qualifying, practice, fp1, fp2, fp3", session)),
+        _ => Err(anyhow::anyhow!("Unknown session type: {}. Valid options are race, qualifying, sprint, sprint-qualifying, pitstops, practice, fp1, fp2, fp3", session)),
     }
 }
 
-fn simulate_race(season: u32, gp: &str, interactive: bool, data_module: &impl DataInterface) -> Result<()> {
+fn simulate_race<R: Rng + ?Sized>(season: u32, gp: &str, interactive: bool, rng: &mut R, data_module: &impl DataInterface) -> Result<()> {
     println!("{}", "Simulating historical race...".blue());
     
     let race = data_module.load_race_data(season, gp)?;
@@ -47,25 +54,42 @@ fn simulate_race(season: u32, gp: &str, interactive: bool, data_module: &impl Da
     );
     
     if interactive {
-        simulate_interactive_historical_race(&race, &race.results)
+        // Replay the real race when lap data exists, otherwise reconstruct it from the results
+        match data_module.load_lap_data(season, gp) {
+            Ok(laps) => {
+                // Stops are only announced when the feed has them (from 2011 onwards)
+                let pit_stops = data_module.load_pit_stops(season, gp).unwrap_or_else(|e| {
+                    println!("{}", format!("Pit stop data unavailable ({}), the replay will not announce stops", e).yellow());
+                    Vec::new()
+                });
+                replay_historical_race(&race, &laps, &pit_stops)
+            },
+            Err(e) => {
+                println!("{}", format!("Lap data unavailable ({}), reconstructing the race from the results", e).yellow());
+                simulate_interactive_historical_race(&race, &race.results, rng)
+            }
+        }
     } else {
         // Display formatted results directly
         println!("\n{}", "Final Results:".green().bold());
         let formatted_results = utils::format_race_results(&race.results);
         println!("{}", formatted_results);
+        display_fastest_lap(&race.results);
         Ok(())
     }
 }
 
-fn simulate_interactive_historical_race(race: &crate::models::Race, final_results: &[RaceResult]) -> Result<()> {
+fn simulate_interactive_historical_race<R: Rng + ?Sized>(race: &crate::models::Race, final_results: &[RaceResult], rng: &mut R) -> Result<()> {
     println!("\n{}", "Interactive Historical Race Simulation".green().bold());
     println!("{}","-".repeat(50));
     
     // For historical races, we'll need to reconstruct a plausible race progression
     // based on the final results, as we don't have actual lap-by-lap data
     
-    // Estimate total laps based on circuit
-    let total_laps = estimate_laps_for_circuit(&race.circuit);
+    // The distance the race was run over, or the circuit's usual distance without lap counts
+    let total_laps = final_results.iter().map(|r| r.laps).max()
+        .filter(|&laps| laps > 0)
+        .unwrap_or(race.circuit.laps);
     
     // Create starting grid (often similar to final order but with some variations)
     let mut positions = create_starting_grid(final_results);
@@ -81,41 +105,207 @@ fn simulate_interactive_historical_race(race: &crate::models::Race, final_result
     let mut current_dnfs = Vec::new();
     
     // Track fastest lap
-    let fastest_lap_driver = identify_fastest_lap(final_results);
+    let fastest_lap = identify_fastest_lap(final_results);
     
     // Lap by lap simulation
     for lap in 1..=total_laps {
         println!("\n{}", format!("Lap {}/{}", lap, total_laps).bold());
         
         // Gradually move drivers toward their final positions
-        update_positions_for_lap(&mut positions, final_results, lap, total_laps);
+        update_positions_for_lap(&mut positions, final_results, lap, total_laps, rng);
         
         // Check for DNFs that might happen on this lap
         if !dnfs.is_empty() {
-            let lap_dnfs = check_for_lap_dnfs(&dnfs, lap, total_laps);
+            let lap_dnfs = check_for_lap_dnfs(&dnfs, lap, total_laps, rng);
             for dnf in lap_dnfs {
                 current_dnfs.push(dnf);
                 println!("{}", format!("LAP {} - INCIDENT: {} - {}", 
                     lap, 
                     get_driver_name(final_results, dnf),
-                    random_incident_for_driver(dnf)
+                    random_incident_for_driver(dnf, rng)
                 ).red());
             }
         }
         
         // Display current positions and status
-        display_lap_status(&positions, final_results, lap, &current_dnfs, fastest_lap_driver);
+        display_lap_status(&positions, final_results, lap, &current_dnfs, fastest_lap, rng);
+        
+        pause_after_lap(lap, total_laps);
+    }
+    
+    // Display final results
+    println!("\n{}", "RACE COMPLETE".green().bold());
+    println!("{}", "Final Results:".green().bold());
+    let formatted_results = utils::format_race_results(final_results);
+    println!("{}", formatted_results);
+    display_fastest_lap(final_results);
+    
+    Ok(())
+}
+
+// Display who set the fastest lap, when and how fast
+fn display_fastest_lap(results: &[RaceResult]) {
+    let fastest = results.iter()
+        .filter_map(|r| r.fastest_lap.as_ref().map(|fl| (r, fl)))
+        .find(|(_, fl)| fl.rank == 1);
         
-        if lap < total_laps {
-            // Interactive mode - wait for user to continue or auto-continue
-            if lap % 10 == 0 || lap == total_laps - 1 {
-                println!("\nPress Enter to continue...");
-                wait_for_user_input();
+    if let Some((result, fl)) = fastest {
+        let speed = fl.average_speed_kph
+            .map(|kph| format!(", {:.3} km/h", kph))
+            .unwrap_or_default();
+        println!("{} {} - {} (lap {}{})", "Fastest lap:".purple().bold(), result.driver.name, fl.time, fl.lap, speed);
+    }
+}
+
+/// Gap from a driver to the race leader at the end of a lap
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gap {
+    Leader,
+    Behind(Duration),
+    /// Laps down on the leader
+    Lapped(u32),
+}
+
+/// A driver's place in the running order at the end of a lap
+#[derive(Debug, Clone, PartialEq)]
+pub struct LapStanding {
+    pub driver_id: String,
+    pub position: u32,
+    pub lap_time: Duration,
+    pub gap: Gap,
+}
+
+/// Work out the running order after every lap from the recorded lap times.
+///
+/// Each driver's race time is the sum of their lap times, so gaps are the
+/// difference to the leader's race time when they completed the same lap. A
+/// driver is lapped once the leader has started a later lap before they
+/// complete this one.
+pub fn replay_standings(laps: &[Lap]) -> Vec<Vec<LapStanding>> {
+    let mut race_times: HashMap<&str, Duration> = HashMap::new();
+    
+    // Race time of every driver at the end of each lap
+    let lap_times: Vec<Vec<(&str, u32, Duration, Duration)>> = laps.iter().map(|lap| {
+        lap.timings.iter().map(|timing| {
+            let lap_time = utils::parse_lap_time(&timing.time).unwrap_or(Duration::ZERO);
+            let race_time = race_times.entry(timing.driver_id.as_str()).or_insert(Duration::ZERO);
+            *race_time += lap_time;
+            (timing.driver_id.as_str(), timing.position, lap_time, *race_time)
+        }).collect()
+    }).collect();
+    
+    // Race time of the leader at the end of each lap
+    let leader_times: Vec<Option<Duration>> = lap_times.iter()
+        .map(|timings| timings.iter().map(|&(_, _, _, race_time)| race_time).min())
+        .collect();
+    
+    lap_times.iter().enumerate().map(|(lap_idx, timings)| {
+        let leader_time = leader_times[lap_idx].unwrap_or(Duration::ZERO);
+        
+        let mut standings: Vec<LapStanding> = timings.iter().map(|&(driver_id, position, lap_time, race_time)| {
+            let laps_down = leader_times[lap_idx + 1..].iter()
+                .flatten()
+                .filter(|&&later| later < race_time)
+                .count() as u32;
+                
+            let gap = if laps_down > 0 {
+                Gap::Lapped(laps_down)
+            } else if race_time <= leader_time {
+                Gap::Leader
             } else {
-                // Short delay between laps for race feel
-                thread::sleep(Duration::from_millis(800));
+                Gap::Behind(race_time - leader_time)
+            };
+            
+            LapStanding {
+                driver_id: driver_id.to_string(),
+                position,
+                lap_time,
+                gap,
             }
+        }).collect();
+        
+        standings.sort_by_key(|s| s.position);
+        standings
+    }).collect()
+}
+
+/// Positions a driver gained (positive) or lost (negative) through a pit stop,
+/// comparing the lap before the stop with the end of the out lap
+pub fn positions_gained(laps: &[Lap], pit_stop: &PitStop) -> Option<i32> {
+    let position_after_lap = |number: u32| {
+        laps.iter()
+            .find(|lap| lap.number == number)?
+            .timings.iter()
+            .find(|t| t.driver_id == pit_stop.driver_id)
+            .map(|t| t.position as i32)
+    };
+    
+    let before = position_after_lap(pit_stop.lap.checked_sub(1)?)?;
+    let after = position_after_lap(pit_stop.lap + 1)?;
+    Some(before - after)
+}
+
+// Replay a race lap by lap from its recorded lap timings
+fn replay_historical_race(race: &crate::models::Race, laps: &[Lap], pit_stops: &[PitStop]) -> Result<()> {
+    println!("\n{}", "Historical Race Replay".green().bold());
+    println!("{}","-".repeat(50));
+    
+    let final_results = &race.results;
+    let total_laps = laps.len() as u32;
+    let results_by_driver: HashMap<&str, &RaceResult> = final_results.iter()
+        .map(|r| (r.driver.id.as_str(), r))
+        .collect();
+    
+    println!("\n{}", "Starting Grid:".yellow());
+    display_grid(&create_starting_grid(final_results), final_results);
+    
+    println!("\n{}", "Press Enter to start the race...".green());
+    wait_for_user_input();
+    
+    // Retirements are announced on the lap the driver failed to complete
+    let retirements: Vec<(u32, &RaceResult)> = identify_dnfs(final_results).into_iter()
+        .map(|idx| (final_results[idx].laps + 1, &final_results[idx]))
+        .collect();
+    
+    let mut fastest_lap: Option<(String, Duration)> = None;
+    
+    for (lap, standings) in laps.iter().zip(replay_standings(laps)) {
+        println!("\n{}", format!("Lap {}/{}", lap.number, total_laps).bold());
+        
+        for (_, result) in retirements.iter().filter(|(retired_on, _)| *retired_on == lap.number) {
+            println!("{}", format!("LAP {} - RETIRED: {} - {}", lap.number, result.driver.name, result.status).red());
         }
+        
+        for pit_stop in pit_stops.iter().filter(|p| p.lap == lap.number) {
+            println!("{}", format!("LAP {} - PIT STOP: {} (stop {}, {}s in the pit lane)",
+                lap.number,
+                driver_name(&results_by_driver, &pit_stop.driver_id),
+                pit_stop.stop,
+                pit_stop.duration.as_deref().unwrap_or("?")
+            ).cyan());
+        }
+        
+        // Lap times include the standing start on lap 1, so it cannot set the fastest lap
+        if lap.number > 1 {
+            let quickest = standings.iter()
+                .filter(|s| !s.lap_time.is_zero())
+                .min_by_key(|s| s.lap_time);
+                
+            if let Some(quickest) = quickest {
+                if fastest_lap.as_ref().is_none_or(|(_, best)| quickest.lap_time < *best) {
+                    fastest_lap = Some((quickest.driver_id.clone(), quickest.lap_time));
+                    println!("{}", format!("LAP {} - FASTEST LAP: {} - {}",
+                        lap.number,
+                        driver_name(&results_by_driver, &quickest.driver_id),
+                        utils::format_duration_as_lap_time(quickest.lap_time)
+                    ).purple());
+                }
+            }
+        }
+        
+        display_replay_status(&standings, &results_by_driver, fastest_lap.as_ref().map(|(driver_id, _)| driver_id.as_str()));
+        
+        pause_after_lap(lap.number, total_laps);
     }
     
     // Display final results
@@ -123,68 +313,89 @@ fn simulate_interactive_historical_race(race: &crate::models::Race, final_result
     println!("{}", "Final Results:".green().bold());
     let formatted_results = utils::format_race_results(final_results);
     println!("{}", formatted_results);
+    display_fastest_lap(final_results);
     
     Ok(())
 }
 
-// Estimate laps for a given circuit based on available data or defaults
-fn estimate_laps_for_circuit(circuit: &Circuit) -> u32 {
-    let circuit_laps: HashMap<&str, u32> = [
-        ("monza", 53),
-        ("monaco", 78),
-        ("spa", 44),
-        ("silverstone", 52),
-        ("bahrain", 57),
-        ("jeddah", 50),
-        ("albert_park", 58),
-        ("baku", 51),
-        ("miami", 57),
-        ("imola", 63),
-        ("monaco", 78),
-        ("catalunya", 66),
-        ("villeneuve", 70),
-        ("red_bull_ring", 71),
-        ("silverstone", 52),
-        ("hungaroring", 70),
-        ("spa", 44),
-        ("zandvoort", 72),
-        ("monza", 53),
-        ("marina_bay", 62),
-        ("suzuka", 53),
-        ("losail", 57),
-        ("americas", 56),
-        ("rodriguez", 71),
-        ("interlagos", 71),
-        ("vegas", 50),
-        ("yas_marina", 58),
-    ].iter().cloned().collect();
-    
-    *circuit_laps.get(circuit.id.as_str()).unwrap_or(&(circuit.laps.max(50)))
+// Name of a driver in the results, falling back to their id
+fn driver_name(results_by_driver: &HashMap<&str, &RaceResult>, driver_id: &str) -> String {
+    results_by_driver.get(driver_id)
+        .map(|r| r.driver.name.clone())
+        .unwrap_or_else(|| driver_id.to_string())
+}
+
+// Display the real running order, lap times and gaps after a lap
+fn display_replay_status(
+    standings: &[LapStanding],
+    results_by_driver: &HashMap<&str, &RaceResult>,
+    fastest_lap_driver: Option<&str>
+) {
+    for standing in standings.iter().take(10) {
+        let pos_str = format!("P{}", standing.position);
+        let pos_colored = match standing.position {
+            1 => pos_str.bright_yellow(),
+            2 => pos_str.bright_white(),
+            3 => pos_str.yellow(),
+            _ => pos_str.normal(),
+        };
+        
+        let team = results_by_driver.get(standing.driver_id.as_str())
+            .map(|r| r.driver.team.clone())
+            .unwrap_or_default();
+        
+        let gap_str = match standing.gap {
+            Gap::Leader => "Leader".to_string(),
+            Gap::Behind(gap) => format!("+{:.3}s", gap.as_secs_f64()),
+            Gap::Lapped(1) => "+1 Lap".to_string(),
+            Gap::Lapped(laps) => format!("+{} Laps", laps),
+        };
+        
+        let fl_indicator = if fastest_lap_driver == Some(standing.driver_id.as_str()) {
+            " 🟣".purple()
+        } else {
+            "".normal()
+        };
+        
+        println!("{:<4} {:<20} {:<15} {:<10} {:<10} {}", 
+            pos_colored,
+            driver_name(results_by_driver, &standing.driver_id),
+            team.bright_cyan(),
+            utils::format_duration_as_lap_time(standing.lap_time),
+            gap_str,
+            fl_indicator
+        );
+    }
 }
 
-// Create a plausible starting grid based on final results
+// Wait for the user every ten laps and before the final lap, otherwise pause briefly
+fn pause_after_lap(lap: u32, total_laps: u32) {
+    if lap < total_laps {
+        if lap.is_multiple_of(10) || lap == total_laps - 1 {
+            println!("\nPress Enter to continue...");
+            wait_for_user_input();
+        } else {
+            // Short delay between laps for race feel
+            thread::sleep(Duration::from_millis(800));
+        }
+    }
+}
+
+// Create the starting grid from the grid positions in the results
 fn create_starting_grid(final_results: &[RaceResult]) -> Vec<usize> {
     let mut grid: Vec<usize> = (0..final_results.len()).collect();
     
-    // Adjust the grid to be somewhat similar to the final results
-    // but with some realistic changes - especially for the mid-field
-    for i in 0..grid.len() {
-        // Front-runners often start near the front
-        if i < 3 && grid[i] < 6 {
-            // Keep top drivers near the front
-            continue;
-        }
-        
-        // Mid-field can have more variance
-        if i >= 3 && i < grid.len() - 3 {
-            // Allow for some position swaps in the midfield
-            if rand::random::<f32>() < 0.4 {
-                let swap_pos = (i as i32 + if rand::random() { 1 } else { -1 }).max(3).min((grid.len() - 4) as i32) as usize;
-                grid.swap(i, swap_pos);
-            }
-        }
+    // Without any grid data, start in finishing order
+    if final_results.iter().all(|r| r.grid == 0) {
+        return grid;
     }
     
+    // Pit lane starters (grid 0) line up behind everyone else
+    grid.sort_by_key(|&idx| match final_results[idx].grid {
+        0 => u32::MAX,
+        grid_pos => grid_pos,
+    });
+    
     grid
 }
 
@@ -211,20 +422,17 @@ fn identify_dnfs(results: &[RaceResult]) -> Vec<usize> {
     dnfs
 }
 
-// Identify the driver with the fastest lap
-fn identify_fastest_lap(results: &[RaceResult]) -> Option<usize> {
-    // In real data, this would be marked specifically
-    // For now, let's just assume one of the top 5 had the fastest lap
-    if !results.is_empty() {
-        let top_pos = results.len().min(5);
-        Some(rand::random::<usize>() % top_pos)
-    } else {
-        None
-    }
+// Identify the driver with the fastest lap and the lap it was set on, if the results record one
+fn identify_fastest_lap(results: &[RaceResult]) -> Option<(usize, u32)> {
+    results.iter().enumerate().find_map(|(idx, r)| {
+        r.fastest_lap.as_ref()
+            .filter(|fl| fl.rank == 1)
+            .map(|fl| (idx, fl.lap))
+    })
 }
 
 // Update positions gradually over the race to match final results
-fn update_positions_for_lap(positions: &mut Vec<usize>, final_results: &[RaceResult], current_lap: u32, total_laps: u32) {
+fn update_positions_for_lap<R: Rng + ?Sized>(positions: &mut [usize], final_results: &[RaceResult], current_lap: u32, total_laps: u32, rng: &mut R) {
     // Calculate how close we are to the end of the race
     let race_progress = current_lap as f32 / total_laps as f32;
     
@@ -251,14 +459,14 @@ fn update_positions_for_lap(positions: &mut Vec<usize>, final_results: &[RaceRes
         
         // If the next driver should be ahead of current driver in final results,
         // consider an overtake with some probability
-        if next_target_pos < current_target_pos && rand::random::<f32>() < overtake_probability {
+        if next_target_pos < current_target_pos && rng.gen::<f32>() < overtake_probability {
             positions.swap(i, i + 1);
         }
     }
 }
 
 // Check which DNFs should happen on the current lap
-fn check_for_lap_dnfs(all_dnfs: &[usize], current_lap: u32, total_laps: u32) -> Vec<usize> {
+fn check_for_lap_dnfs<R: Rng + ?Sized>(all_dnfs: &[usize], current_lap: u32, total_laps: u32, rng: &mut R) -> Vec<usize> {
     let mut lap_dnfs = Vec::new();
     
     for &dnf_idx in all_dnfs {
@@ -272,7 +480,7 @@ fn check_for_lap_dnfs(all_dnfs: &[usize], current_lap: u32, total_laps: u32) ->
             _ => 0.01,                      // Final laps - few DNFs
         };
         
-        if rand::random::<f32>() < dnf_probability {
+        if rng.gen::<f32>() < dnf_probability {
             lap_dnfs.push(dnf_idx);
         }
     }
@@ -290,7 +498,7 @@ fn get_driver_name(results: &[RaceResult], idx: usize) -> String {
 }
 
 // Generate a random plausible incident for a driver DNF
-fn random_incident_for_driver(driver_idx: usize) -> String {
+fn random_incident_for_driver<R: Rng + ?Sized>(driver_idx: usize, rng: &mut R) -> String {
     let incidents = [
         "Engine failure",
         "Hydraulics issue",
@@ -307,23 +515,23 @@ fn random_incident_for_driver(driver_idx: usize) -> String {
     ];
     
     // Use driver index to influence incident type slightly, but still with randomness
-    let incident_idx = (driver_idx + (rand::random::<usize>() % 5)) % incidents.len();
+    let incident_idx = (driver_idx + rng.gen_range(0..5)) % incidents.len();
     incidents[incident_idx].to_string()
 }
 
 // Display current race status for a lap
-fn display_lap_status(
+fn display_lap_status<R: Rng + ?Sized>(
     positions: &[usize], 
     results: &[RaceResult], 
     lap: u32,
     dnfs: &[usize],
-    fastest_lap: Option<usize>
+    fastest_lap: Option<(usize, u32)>,
+    rng: &mut R
 ) {
     // Show top positions (limited to what's visible on screen)
     let max_to_show = 10.min(positions.len());
     
-    for i in 0..max_to_show {
-        let driver_idx = positions[i];
+    for (i, &driver_idx) in positions.iter().enumerate().take(max_to_show) {
         if dnfs.contains(&driver_idx) {
             continue; // Skip DNF'd drivers
         }
@@ -343,12 +551,12 @@ fn display_lap_status(
             let gap_str = if i == 0 {
                 "Leader".to_string()
             } else {
-                format!("+{:.1}s", (i as f32) * 0.8 + (rand::random::<f32>() * 0.4))
+                format!("+{:.1}s", (i as f32) * 0.8 + (rng.gen::<f32>() * 0.4))
             };
             
             // Show fastest lap indicator
-            let fl_indicator = if let Some(fl_idx) = fastest_lap {
-                if fl_idx == driver_idx && lap > 5 {  // Show fastest lap after a few laps
+            let fl_indicator = if let Some((fl_idx, fl_lap)) = fastest_lap {
+                if fl_idx == driver_idx && lap >= fl_lap {  // Show fastest lap once it has been set
                     " 🟣".purple()
                 } else {
                     "".normal()
@@ -371,35 +579,95 @@ fn display_lap_status(
 fn simulate_qualifying(season: u32, gp: &str, data_module: &impl DataInterface) -> Result<()> {
     println!("{}", "Simulating historical qualifying session...".blue());
     
-    match data_module.load_qualifying_data(season, gp) {
-        Ok(results) => {
-            let formatted_results = utils::format_qualifying_results(&results);
-            println!("{}", formatted_results);
-            Ok(())
-        },
-        Err(e) => {
-            println!("{}", "Qualifying data is not yet implemented.".yellow());
-            println!("{}", "This feature will be available in a future update.".yellow());
-            Err(e)
+    let results = data_module.load_qualifying_data(season, gp)?;
+    
+    let formatted_results = utils::format_qualifying_results(&results);
+    println!("{}", formatted_results);
+    Ok(())
+}
+
+fn simulate_sprint(season: u32, gp: &str, data_module: &impl DataInterface) -> Result<()> {
+    println!("{}", "Simulating historical sprint...".blue());
+    
+    let results = data_module.load_sprint_data(season, gp)?;
+    
+    println!("\n{}", "Sprint Results:".green().bold());
+    println!("{}", utils::format_sprint_results(&results));
+    Ok(())
+}
+
+fn simulate_sprint_qualifying(season: u32, gp: &str, data_module: &impl DataInterface) -> Result<()> {
+    println!("{}", "Simulating historical sprint qualifying...".blue());
+    
+    // The feed has no sprint qualifying times, so the session is shown as the grid it set
+    let results = data_module.load_sprint_data(season, gp)?;
+    
+    println!("\n{}", "Sprint Grid:".green().bold());
+    println!("{}", utils::format_sprint_grid(&results));
+    Ok(())
+}
+
+fn simulate_pit_stops(season: u32, gp: &str, data_module: &impl DataInterface) -> Result<()> {
+    println!("{}", "Simulating historical pit stops...".blue());
+    
+    let race = data_module.load_race_data(season, gp)?;
+    let pit_stops = data_module.load_pit_stops(season, gp)?;
+    
+    // Positions gained or lost need lap data; without it the column is left empty
+    let laps = data_module.load_lap_data(season, gp).unwrap_or_default();
+    
+    println!("\n{} - {}", race.name.bold(), race.date.italic());
+    println!("\n{}", "Pit Stops:".green().bold());
+    println!("{:<20} {:<15} {:<5} {:<5} {:<10} {}", 
+        "Driver".bold(), 
+        "Team".bold(), 
+        "Stop".bold(), 
+        "Lap".bold(),
+        "Pit lane".bold(),
+        "Positions".bold()
+    );
+    println!("{}", "-".repeat(70));
+    
+    // Drivers in finishing order, each with their stops in order
+    for result in &race.results {
+        let driver_stops = pit_stops.iter().filter(|p| p.driver_id == result.driver.id);
+        
+        for (i, pit_stop) in driver_stops.enumerate() {
+            let (name, team) = if i == 0 {
+                (result.driver.name.as_str(), result.driver.team.as_str())
+            } else {
+                ("", "")
+            };
+            
+            let change = match positions_gained(&laps, pit_stop) {
+                Some(gained) if gained > 0 => format!("+{}", gained).green(),
+                Some(gained) if gained < 0 => gained.to_string().red(),
+                Some(_) => "0".normal(),
+                None => "-".normal(),
+            };
+            
+            println!("{:<20} {:<15} {:<5} {:<5} {:<10} {}",
+                name,
+                team.bright_cyan(),
+                pit_stop.stop,
+                pit_stop.lap,
+                pit_stop.duration.as_deref().map(|d| format!("{}s", d)).unwrap_or_else(|| "-".to_string()),
+                change
+            );
         }
     }
+    
+    Ok(())
 }
 
 fn simulate_practice(season: u32, gp: &str, practice_number: u32, data_module: &impl DataInterface) -> Result<()> {
     println!("{}", format!("Simulating historical FP{} session...", practice_number).blue());
     
-    match data_module.load_practice_data(season, gp, practice_number) {
-        Ok(_) => {
-            // Display practice results (would need a format function for this)
-            println!("Practice results loaded successfully");
-            Ok(())
-        },
-        Err(e) => {
-            println!("{}", "Practice session data is not yet implemented.".yellow());
-            println!("{}", "This feature will be available in a future update.".yellow());
-            Err(e)
-        }
-    }
+    data_module.load_practice_data(season, gp, practice_number)?;
+    
+    // Display practice results (would need a format function for this)
+    println!("Practice results loaded successfully");
+    Ok(())
 }
 
 // Wait for user to press Enter
@@ -0,0 +1,344 @@
+use anyhow::Result;
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
+use rand::Rng;
+use std::collections::HashMap;
+
+use crate::data::{self, DataConfig};
+use crate::data::index::{RoundEntry, SeasonIndex};
+use crate::models::{Circuit, Driver, Race, RaceFormat, RaceResult, SimulationParameters, SprintResult, WeekendResults};
+use crate::points::PointsSystem;
+use crate::ratings::Ratings;
+use crate::circuits::CircuitCatalogue;
+use crate::simulator::prediction::run_single_simulation;
+use crate::standings::{Standings, StandingsEntry};
+use crate::utils;
+use crate::weather::Weather;
+
+/// How a driver or constructor fared over every simulated season
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChampionshipOdds {
+    /// Driver name, or constructor name in the constructors' table
+    pub name: String,
+    /// Team of a driver; empty in the constructors' table
+    pub team: String,
+    /// Points already scored in the rounds that have been run
+    pub current_points: f64,
+    /// Average final points
+    pub expected_points: f64,
+    /// Number of runs finishing the season in each position: `positions[0]` is titles
+    pub positions: Vec<u32>,
+}
+
+impl ChampionshipOdds {
+    /// Share of runs in which the championship was won
+    pub fn title_probability(&self) -> f64 {
+        self.position_probability(1)
+    }
+    
+    /// Share of runs finishing the season in `position`
+    pub fn position_probability(&self, position: u32) -> f64 {
+        let runs: u32 = self.positions.iter().sum();
+        let count = position.checked_sub(1)
+            .and_then(|i| self.positions.get(i as usize))
+            .copied()
+            .unwrap_or(0);
+        if runs == 0 {
+            0.0
+        } else {
+            count as f64 / runs as f64
+        }
+    }
+    
+    /// Final positions by how often they occurred, most likely first
+    pub fn likeliest_positions(&self) -> Vec<(u32, f64)> {
+        let mut positions: Vec<(u32, f64)> = (1..=self.positions.len() as u32)
+            .map(|pos| (pos, self.position_probability(pos)))
+            .filter(|&(_, probability)| probability > 0.0)
+            .collect();
+        positions.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+        positions
+    }
+}
+
+/// The outcome of simulating the rest of a season many times
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeasonProjection {
+    pub runs: u32,
+    /// Last round with results, 0 before the season has started
+    pub completed_round: u32,
+    pub remaining_rounds: usize,
+    /// Sorted by title probability, then expected points
+    pub drivers: Vec<ChampionshipOdds>,
+    pub constructors: Vec<ChampionshipOdds>,
+}
+
+/// Simulate the remaining rounds of a season and report the championship odds;
+/// the same seed gives the same odds
+pub fn simulate(season: u32, runs: u32, seed: Option<u64>, config: &DataConfig) -> Result<()> {
+    let schedule = data::load_schedule(config, season)?;
+    let completed: Vec<WeekendResults> = data::load_cached_results(config, season)?
+        .into_iter()
+        .filter(|w| !w.race.results.is_empty())
+        .collect();
+    
+    println!("{}", format!(
+        "Simulating the {} season: {} of {} rounds completed, {} runs",
+        season, completed.len(), schedule.rounds.len(), runs
+    ).blue());
+    
+    let pb = ProgressBar::new(runs as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} seasons ({eta})")
+            .unwrap()
+            .progress_chars("#>-")
+    );
+    
+    let drivers = data::load_roster(config, season, None)?;
+    // Every session draws its own weather with the circuit's rain risk
+    let params = SimulationParameters {
+        weather: Weather::Random { seed: None },
+        ..Default::default()
+    };
+    let points_system = PointsSystem::for_season(season);
+    let ratings = data::load_ratings(config, season)?;
+    let circuits = data::load_circuits(config)?;
+    
+    let mut rng = utils::seeded_rng(seed);
+    let projection = project_season(&schedule, &completed, &drivers, runs, &params, &points_system, &ratings, &circuits, &mut rng, || pb.inc(1));
+    pb.finish_with_message("Simulation completed!");
+    
+    display_projection(season, &projection);
+    Ok(())
+}
+
+/// Run the rounds of the schedule without results `runs` times on top of the
+/// completed weekends and collect where everyone finishes the championship.
+/// `on_run` is called after every simulated season.
+#[allow(clippy::too_many_arguments)]
+pub fn project_season<R: Rng + ?Sized>(
+    schedule: &SeasonIndex,
+    completed: &[WeekendResults],
+    drivers: &[Driver],
+    runs: u32,
+    params: &SimulationParameters,
+    points_system: &PointsSystem,
+    ratings: &Ratings,
+    circuits: &CircuitCatalogue,
+    rng: &mut R,
+    mut on_run: impl FnMut(),
+) -> SeasonProjection {
+    let current = Standings::after_round(completed, None, None);
+    let identities = cached_identities(drivers, completed);
+    let remaining: Vec<&RoundEntry> = schedule.rounds.iter()
+        .filter(|entry| !completed.iter().any(|w| w.race.round == entry.round))
+        .collect();
+    
+    let mut driver_odds: HashMap<String, ChampionshipOdds> = HashMap::new();
+    let mut constructor_odds: HashMap<String, ChampionshipOdds> = HashMap::new();
+    
+    for _ in 0..runs {
+        let mut weekends = completed.to_vec();
+        for entry in &remaining {
+            let mut weekend = simulate_weekend(schedule.season, entry, drivers, params, points_system, ratings, circuits, rng);
+            for driver in weekend.race.results.iter_mut().map(|r| &mut r.driver)
+                .chain(weekend.sprint.iter_mut().map(|r| &mut r.driver))
+            {
+                if let Some(identity) = identities.get(&driver.id) {
+                    *driver = identity.clone();
+                }
+            }
+            weekends.push(weekend);
+        }
+        
+        let finals = Standings::after_round(&weekends, None, None);
+        record_season(&mut driver_odds, &finals.drivers, &current.drivers);
+        record_season(&mut constructor_odds, &finals.constructors, &current.constructors);
+        on_run();
+    }
+    
+    SeasonProjection {
+        runs,
+        completed_round: current.round,
+        remaining_rounds: remaining.len(),
+        drivers: summarize(driver_odds, runs),
+        constructors: summarize(constructor_odds, runs),
+    }
+}
+
+// Simulate the sprint, if there is one, and the grand prix of a round
+#[allow(clippy::too_many_arguments)]
+fn simulate_weekend<R: Rng + ?Sized>(
+    season: u32,
+    entry: &RoundEntry,
+    drivers: &[Driver],
+    params: &SimulationParameters,
+    points_system: &PointsSystem,
+    ratings: &Ratings,
+    circuits: &CircuitCatalogue,
+    rng: &mut R,
+) -> WeekendResults {
+    let circuit = circuit_for_round(entry, circuits);
+    
+    let sprint = if entry.sprint {
+        let mut sprint_circuit = circuit.clone();
+        sprint_circuit.laps = RaceFormat::Sprint.laps(&circuit);
+        run_single_simulation(drivers, &sprint_circuit, params, RaceFormat::Sprint, points_system, ratings, rng)
+            .into_iter()
+            .map(sprint_result)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    
+    let results = run_single_simulation(drivers, &circuit, params, RaceFormat::GrandPrix, points_system, ratings, rng);
+    
+    WeekendResults {
+        race: Race {
+            season,
+            round: entry.round,
+            name: entry.race_name.clone(),
+            circuit,
+            date: entry.date.clone(),
+            results,
+        },
+        qualifying: Vec::new(),
+        sprint,
+    }
+}
+
+// The drivers of the roster as they appear in the cached results (matched by
+// code, then name), so simulated points add up with the points already scored
+fn cached_identities(drivers: &[Driver], completed: &[WeekendResults]) -> HashMap<String, Driver> {
+    let mut identities = HashMap::new();
+    
+    let mut weekends: Vec<&WeekendResults> = completed.iter().collect();
+    weekends.sort_by_key(|w| w.race.round);
+    
+    for result in weekends.iter().flat_map(|w| &w.race.results) {
+        let cached = &result.driver;
+        if let Some(driver) = drivers.iter().find(|d| d.code == cached.code || d.name == cached.name) {
+            identities.insert(driver.id.clone(), cached.clone());
+        }
+    }
+    
+    identities
+}
+
+// The circuit a round is held at, as an average track if it is not in the catalogue
+fn circuit_for_round(entry: &RoundEntry, circuits: &CircuitCatalogue) -> Circuit {
+    circuits.circuit(&entry.circuit_id).unwrap_or_else(|_| {
+        let mut circuit = Circuit {
+            id: entry.circuit_id.clone(),
+            name: entry.race_name.clone(),
+            ..Circuit::default()
+        };
+        circuits.characterize(&mut circuit);
+        circuit
+    })
+}
+
+// A simulated race result as a sprint result
+fn sprint_result(result: RaceResult) -> SprintResult {
+    SprintResult {
+        position: result.position,
+        driver: result.driver,
+        grid: result.grid,
+        time: result.time,
+        points: result.points,
+        laps: result.laps,
+        status: result.status,
+    }
+}
+
+// Add the final table of one simulated season to the tallies
+fn record_season(odds: &mut HashMap<String, ChampionshipOdds>, finals: &[StandingsEntry], current: &[StandingsEntry]) {
+    for entry in finals {
+        let tally = odds.entry(entry.name.clone()).or_insert_with(|| ChampionshipOdds {
+            name: entry.name.clone(),
+            team: entry.team.clone(),
+            current_points: current.iter()
+                .find(|c| c.name == entry.name)
+                .map(|c| c.points)
+                .unwrap_or(0.0),
+            expected_points: 0.0,
+            positions: Vec::new(),
+        });
+        
+        tally.team = entry.team.clone();
+        tally.expected_points += entry.points;
+        
+        let idx = (entry.position - 1) as usize;
+        if tally.positions.len() <= idx {
+            tally.positions.resize(idx + 1, 0);
+        }
+        tally.positions[idx] += 1;
+    }
+}
+
+// Turn point totals into averages and sort by title chances
+fn summarize(odds: HashMap<String, ChampionshipOdds>, runs: u32) -> Vec<ChampionshipOdds> {
+    let mut odds: Vec<ChampionshipOdds> = odds.into_values()
+        .map(|mut o| {
+            o.expected_points /= runs.max(1) as f64;
+            o
+        })
+        .collect();
+    
+    odds.sort_by(|a, b| {
+        b.title_probability().partial_cmp(&a.title_probability()).unwrap()
+            .then(b.expected_points.partial_cmp(&a.expected_points).unwrap())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    odds
+}
+
+// Display the drivers' and constructors' championship odds
+fn display_projection(season: u32, projection: &SeasonProjection) {
+    let completed = match projection.completed_round {
+        0 => "before the first round".to_string(),
+        round => format!("after round {}", round),
+    };
+    
+    println!("\n{} {}",
+        format!("{} Championship Projection {}", season, completed).green().bold(),
+        format!("({} rounds simulated {} times)", projection.remaining_rounds, projection.runs).italic()
+    );
+    
+    for (title, odds, constructors) in [
+        ("Drivers' Championship", &projection.drivers, false),
+        ("Constructors' Championship", &projection.constructors, true),
+    ] {
+        println!("\n{}", title.bold());
+        println!("{}", "-".repeat(95));
+        println!("{:<20} {:<15} {:>8} {:>10} {:>10}   {}",
+            (if constructors { "Constructor" } else { "Driver" }).bold(),
+            (if constructors { "" } else { "Team" }).bold(),
+            "Points".bold(),
+            "Expected".bold(),
+            "Title".bold(),
+            "Likeliest final positions".bold()
+        );
+        println!("{}", "-".repeat(95));
+        
+        for entry in odds {
+            let distribution = entry.likeliest_positions().iter()
+                .take(3)
+                .map(|(pos, probability)| format!("P{} {:.1}%", pos, probability * 100.0))
+                .collect::<Vec<_>>()
+                .join(", ");
+            
+            println!("{:<20} {:<15} {:>8} {:>10.1} {:>9.1}%   {}",
+                entry.name,
+                entry.team,
+                utils::format_points(entry.current_points),
+                entry.expected_points,
+                entry.title_probability() * 100.0,
+                distribution
+            );
+        }
+    }
+    
+    println!("\n{}", "Note: These projections are simulations based on estimated data.".italic());
+}
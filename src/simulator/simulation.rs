@@ -1,108 +1,200 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
 use rand::Rng;
 use rand_distr::{Normal, Distribution};
-use std::collections::HashMap;
-use std::io::{self, Write};
+use std::cmp::Reverse;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
 use std::thread;
 use std::time::Duration;
 
-use crate::models::{Circuit, Driver, SimulationParameters};
-use crate::simulator::prediction::{create_circuit_for_gp, create_current_drivers};
+use crate::models::{is_classified, Circuit, Compound, Driver, FastestLap, RaceFormat, RaceResult, SimulationParameters};
+use crate::data::{self, DataConfig};
+use crate::points::{distance_completed, PointsSystem};
+use crate::ratings::Ratings;
 use crate::utils;
+use crate::weather::WeatherTimeline;
 
-/// Simulate a race with customizable parameters
-pub fn simulate(season: u32, gp: &str, params: SimulationParameters, interactive: bool) -> Result<()> {
-    println!("{}", format!("Simulating {} GP {}", gp, season).blue());
+pub mod events;
+pub mod phases;
+pub mod scenario;
+pub mod strategy;
+pub mod tyres;
+
+use events::{JsonLog, RaceEvent, RaceObserver};
+use phases::{Phase, PhasePeriod, QUEUE_GAP_SECS};
+use scenario::Scenario;
+use strategy::{PitWindow, Reaction, Strategy, ATTACK_GAP_SECS};
+
+/// Gap between consecutive grid slots at the start, in seconds
+const GRID_SLOT_SECS: f64 = 0.25;
+
+/// Time lost getting away from a standing start on the opening lap
+const STANDING_START_SECS: f64 = 2.5;
+
+/// Lap time gained with every lap of fuel burnt
+const FUEL_EFFECT_SECS_PER_LAP: f64 = 0.03;
+
+/// How much slower than a perfect car a car without any performance would
+/// lap, as a share of the lap time
+const PERFORMANCE_TIME_SPREAD: f64 = 0.1;
+
+/// Spread of performance in qualifying and of race pace from race to race
+const QUALIFYING_VARIATION: f64 = 0.015;
+const RACE_FORM_VARIATION: f64 = 0.02;
+
+/// Spread of lap times around a car's pace, as a share of the lap time
+const LAP_TIME_VARIATION: f64 = 0.003;
+
+/// Closest a car that cannot pass crosses the line behind the car ahead, in seconds
+const MIN_FOLLOWING_GAP_SECS: f64 = 0.3;
+
+/// Forecast chance of rain at which teams put off a planned stop for slicks,
+/// to change straight to intermediates when the rain comes
+const RAIN_STOP_PROBABILITY: f64 = 0.5;
+
+/// Spread of the time lost to the pit crew on top of the pit lane, in seconds
+const PIT_STOP_VARIATION_SECS: f64 = 0.6;
+
+/// Ways teams react to the cars around them, handed out in entry list order
+const TEAM_REACTIONS: [Reaction; 3] = [Reaction::Undercut, Reaction::Overcut, Reaction::None];
+
+/// Status of a car disqualified from the results
+const DISQUALIFIED: &str = "Disqualified";
+
+/// Retirements that leave a wrecked car on track rather than a stopped one
+const CRASHES: [&str; 3] = ["Accident", "Lost control in the corner", "Collision with another driver"];
+
+/// Lap time advantage, in seconds, at which a pass is as likely as not on a
+/// circuit where overtaking is easy
+const PASSING_ADVANTAGE_SECS: f64 = 0.5;
+
+/// Simulate a race or sprint with customizable parameters; the same seed runs
+/// the same race. Every event of the race is logged to `event_log` as a line
+/// of JSON if a path is given.
+#[allow(clippy::too_many_arguments)]
+pub fn simulate(season: u32, gp: &str, params: SimulationParameters, format: RaceFormat, interactive: bool, scenario: &Scenario, seed: Option<u64>, event_log: Option<&Path>, config: &DataConfig) -> Result<()> {
+    let session = match format {
+        RaceFormat::GrandPrix => "",
+        RaceFormat::Sprint => " sprint",
+    };
+    println!("{}", format!("Simulating {} GP {}{}", gp, season, session).blue());
     println!("Simulation parameters:");
     println!("  - Reliability factor: {:.2}", params.reliability_factor);
-    println!("  - Weather factor: {:.2}", params.weather_factor);
+    println!("  - Weather: {}", params.weather);
     println!("  - Random incidents: {}", params.random_incidents);
     
-    // Create a circuit for the specified GP
-    let circuit = create_circuit_for_gp(gp)?;
+    // The circuit of the specified GP from the catalogue, shortened to sprint distance if needed
+    let mut circuit = data::load_circuits(config)?.circuit(gp)?;
+    circuit.laps = format.laps(&circuit);
     
-    // Create current drivers
-    let drivers = create_current_drivers();
+    // The entry list of the season
+    let drivers = data::load_roster(config, season, None)?;
     
+    // Score the result with the season's points system
+    let points_system = PointsSystem::for_season(season);
+    
+    // Rate drivers and cars from the results leading up to the season
+    let ratings = data::load_ratings(config, season)?;
+    
+    let mut log = match event_log {
+        Some(path) => {
+            let file = File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+            Some(JsonLog::new(&drivers, BufWriter::new(file)))
+        },
+        None => None,
+    };
+    
+    let mut rng = utils::seeded_rng(seed);
     if interactive {
-        simulate_interactive_race(&drivers, &circuit, &params)
+        simulate_interactive_race(&drivers, &circuit, &params, format, &points_system, &ratings, scenario, &mut rng, &mut log)?;
     } else {
-        simulate_instant_race(&drivers, &circuit, &params)
+        simulate_instant_race(&drivers, &circuit, &params, format, &points_system, &ratings, scenario, &mut rng, &mut log)?;
+    }
+    
+    if let (Some(log), Some(path)) = (log, event_log) {
+        log.finish()?;
+        println!("\nRace events written to {}", path.display());
+    }
+    Ok(())
+}
+
+// Title of a race format for headings
+fn format_title(format: RaceFormat) -> &'static str {
+    match format {
+        RaceFormat::GrandPrix => "Race",
+        RaceFormat::Sprint => "Sprint",
     }
 }
 
-/// Run a single race simulation with turn-by-turn interactive display
-pub fn simulate_interactive_race(drivers: &[Driver], circuit: &Circuit, params: &SimulationParameters) -> Result<()> {
-    println!("\n{}", format!("Interactive Race Simulation at {}", circuit.name).green().bold());
-    println!("{} laps, {:.3} km", circuit.laps, circuit.length_km);
+// Display the race distance and characteristics of the circuit
+fn display_circuit(circuit: &Circuit) {
+    println!("{} laps, {:.3} km, {} corners, {} DRS zones", circuit.laps, circuit.length_km, circuit.corners, circuit.drs_zones);
+    println!("Overtaking difficulty: {:.0}%, rain risk: {:.0}%", circuit.overtaking_difficulty * 100.0, circuit.weather_risk * 100.0);
+}
+
+/// Run a single race simulation with turn-by-turn interactive display, reporting
+/// every event to the observer as well
+#[allow(clippy::too_many_arguments)]
+pub fn simulate_interactive_race<R: Rng + ?Sized, O: RaceObserver + ?Sized>(
+    drivers: &[Driver],
+    circuit: &Circuit,
+    params: &SimulationParameters,
+    format: RaceFormat,
+    points_system: &PointsSystem,
+    ratings: &Ratings,
+    scenario: &Scenario,
+    rng: &mut R,
+    observer: &mut O
+) -> Result<()> {
+    println!("\n{}", format!("Interactive {} Simulation at {}", format_title(format), circuit.name).green().bold());
+    display_circuit(circuit);
     println!("{}","-".repeat(50));
     
     println!("\n{}", "Starting Grid:".yellow());
-    // Show the starting grid (we'll randomize it a bit)
-    let mut driver_positions = initialize_driver_positions(drivers, params);
+    // Line up the grid from a simulated qualifying session
+    let mut race = RaceState::new(drivers, ratings, params, circuit, format, rng);
+    race.assign_strategies(drivers, scenario, params)?;
     
-    for (pos, (idx, _, _, _)) in driver_positions.iter().enumerate() {
-        let driver = &drivers[*idx];
-        println!("{:2}. {} - {}", pos + 1, driver.code, driver.team);
+    for car in &race.cars {
+        let driver = &drivers[car.driver];
+        println!("{:2}. {} - {} - {}", car.grid, driver.code, driver.team, car.strategy);
     }
     
     println!("\n{}", "Press Enter to start the race...".green());
     wait_for_user_input();
     
-    let total_laps = circuit.laps;
-    let mut dnf_drivers = Vec::new();
-    let mut fastest_lap: Option<(usize, Duration)> = None;
-    
-    // Initialize lap times with some baseline performance
-    let mut driver_performance = HashMap::new();
-    for (i, driver) in drivers.iter().enumerate() {
-        // Base performance on a combination of driver skill and car performance
-        let base_performance = calculate_driver_base_performance(driver, params);
-        driver_performance.insert(i, base_performance);
-    }
-    
     // Run the race lap by lap
-    for lap in 1..=total_laps {
-        println!("\n{}", format!("Lap {}/{}", lap, total_laps).bold());
-        
-        // Update positions and handle incidents
-        update_race_positions(&mut driver_positions, &driver_performance, params);
-        
-        // Check for incidents/DNFs
-        if params.random_incidents && lap > 5 {
-            check_for_incidents(drivers, &mut driver_positions, &mut dnf_drivers, lap, params);
-        }
-        
-        // Display current positions (top 5)
-        display_lap_summary(drivers, &driver_positions, lap, &dnf_drivers, fastest_lap);
-        
-        if lap < total_laps {
-            // Interactive mode - wait for user to continue
-            if lap % 10 == 0 || lap == total_laps - 1 {
-                println!("\nPress Enter to continue...");
-                wait_for_user_input();
-            } else {
-                // Short delay between laps for race feel
-                thread::sleep(Duration::from_millis(800));
-            }
-        }
-        
-        // Update fastest lap
-        update_fastest_lap(&driver_positions, lap, &mut fastest_lap);
+    let mut display = LapByLapDisplay { drivers, lap_shown: 0 };
+    while !race.is_finished() {
+        run_lap(drivers, &mut race, params, circuit, rng, &mut (&mut display, &mut *observer));
     }
     
     // Show final results
-    display_final_results(drivers, &driver_positions, &dnf_drivers, fastest_lap);
+    display_final_results(drivers, circuit, &race, format, points_system);
     
     Ok(())
 }
 
-/// Run a race simulation and display the final results immediately
-pub fn simulate_instant_race(drivers: &[Driver], circuit: &Circuit, params: &SimulationParameters) -> Result<()> {
-    println!("\n{}", format!("Race Simulation at {}", circuit.name).green().bold());
-    println!("{} laps, {:.3} km", circuit.laps, circuit.length_km);
+/// Run a race simulation and display the final results immediately, reporting
+/// every event to the observer
+#[allow(clippy::too_many_arguments)]
+pub fn simulate_instant_race<R: Rng + ?Sized, O: RaceObserver + ?Sized>(
+    drivers: &[Driver],
+    circuit: &Circuit,
+    params: &SimulationParameters,
+    format: RaceFormat,
+    points_system: &PointsSystem,
+    ratings: &Ratings,
+    scenario: &Scenario,
+    rng: &mut R,
+    observer: &mut O
+) -> Result<()> {
+    println!("\n{}", format!("{} Simulation at {}", format_title(format), circuit.name).green().bold());
+    display_circuit(circuit);
     println!("{}","-".repeat(50));
     
     // Set up progress bar for simulation
@@ -114,298 +206,1066 @@ pub fn simulate_instant_race(drivers: &[Driver], circuit: &Circuit, params: &Sim
             .progress_chars("#>-")
     );
     
-    // Initialize positions and performance
-    let mut driver_positions = initialize_driver_positions(drivers, params);
-    let mut dnf_drivers = Vec::new();
-    let mut fastest_lap: Option<(usize, Duration)> = None;
-    
-    // Initialize driver performance
-    let mut driver_performance = HashMap::new();
-    for (i, driver) in drivers.iter().enumerate() {
-        let base_performance = calculate_driver_base_performance(driver, params);
-        driver_performance.insert(i, base_performance);
-    }
+    let mut race = RaceState::new(drivers, ratings, params, circuit, format, rng);
+    race.assign_strategies(drivers, scenario, params)?;
     
     // Run the simulation
-    for lap in 1..=circuit.laps {
-        // Update positions
-        update_race_positions(&mut driver_positions, &driver_performance, params);
-        
-        // Check for incidents
-        if params.random_incidents && lap > 5 {
-            check_for_incidents(drivers, &mut driver_positions, &mut dnf_drivers, lap, params);
-        }
-        
-        // Update fastest lap
-        update_fastest_lap(&driver_positions, lap, &mut fastest_lap);
-        
-        pb.inc(1);
-        thread::sleep(Duration::from_millis(10)); // Small delay for visual effect
+    let mut display = ProgressDisplay { drivers, pb };
+    while !race.is_finished() {
+        run_lap(drivers, &mut race, params, circuit, rng, &mut (&mut display, &mut *observer));
     }
     
-    pb.finish_with_message("Race completed!");
+    display.pb.finish_with_message("Race completed!");
     
     // Display final results
-    display_final_results(drivers, &driver_positions, &dnf_drivers, fastest_lap);
+    display_final_results(drivers, circuit, &race, format, points_system);
     
     Ok(())
 }
 
-// Initialize driver positions with qualifying performance
-pub fn initialize_driver_positions(drivers: &[Driver], params: &SimulationParameters) -> Vec<(usize, f64, Duration, bool)> {
-    let mut rng = rand::thread_rng();
-    let mut positions = Vec::new();
-    
-    for (i, driver) in drivers.iter().enumerate() {
-        // Base performance calculation
-        let base_perf = calculate_driver_base_performance(driver, params);
-        
-        // Add qualifying variation
-        let quali_variation = Normal::new(0.0, 0.015).unwrap();
-        let perf_variation = 1.0 + quali_variation.sample(&mut rng);
-        let quali_performance = base_perf * perf_variation;
-        
-        // Convert performance to time
-        let base_lap_time = Duration::from_secs_f64(90.0);
-        let performance_factor = 1.0 + (1.0 - quali_performance) * 0.15;
-        let quali_time = base_lap_time.mul_f64(performance_factor);
-        
-        positions.push((i, quali_performance, quali_time, true));
-    }
-    
-    // Sort by qualifying time (lower is better)
-    positions.sort_by(|a, b| a.2.cmp(&b.2));
-    
-    positions
-}
-
-// Calculate base performance for a driver (0-1 scale, higher is better)
-pub fn calculate_driver_base_performance(driver: &Driver, params: &SimulationParameters) -> f64 {
-    // Driver skill factors (simplified model)
-    let driver_skill: HashMap<&str, f64> = [
-        ("Max Verstappen", 0.98),
-        ("Sergio Perez", 0.92),
-        ("Charles Leclerc", 0.95),
-        ("Carlos Sainz", 0.94),
-        ("Lewis Hamilton", 0.96),
-        ("George Russell", 0.94),
-        ("Lando Norris", 0.96),
-        ("Oscar Piastri", 0.93),
-        ("Fernando Alonso", 0.95),
-        ("Lance Stroll", 0.90),
-        // Add more drivers as needed
-    ].iter().cloned().collect();
-    
-    // Team performance factors (simplified model)
-    let team_performance: HashMap<&str, f64> = [
-        ("Red Bull Racing", 0.98),
-        ("Ferrari", 0.96),
-        ("Mercedes", 0.95),
-        ("McLaren", 0.97),
-        ("Aston Martin", 0.92),
-        ("Alpine", 0.89),
-        ("Williams", 0.87),
-        ("RB", 0.88),
-        ("Haas F1 Team", 0.86),
-        ("Sauber", 0.85),
-    ].iter().cloned().collect();
-    
-    // Get driver skill and team performance
-    let skill = *driver_skill.get(driver.name.as_str()).unwrap_or(&0.90);
-    let team_perf = *team_performance.get(driver.team.as_str()).unwrap_or(&0.85);
-    
-    // Apply weather factor for wet weather variance
-    let weather_adjustment = if params.weather_factor < 1.0 {
-        // Wet conditions can shuffle the order slightly
-        // Ensure weather adjustment is always between 0.7 and 1.0
-        // This ensures the final performance value stays within reasonable bounds
-        0.7 + (params.weather_factor * 0.3)
-    } else {
-        1.0
-    };
+/// Run a whole race without any output
+pub fn run_race<R: Rng + ?Sized>(
+    drivers: &[Driver],
+    circuit: &Circuit,
+    params: &SimulationParameters,
+    format: RaceFormat,
+    ratings: &Ratings,
+    rng: &mut R
+) -> RaceState {
+    run_race_with(drivers, circuit, params, format, ratings, rng, &mut ())
+}
+
+/// Run a whole race, reporting every event to the observer
+pub fn run_race_with<R: Rng + ?Sized, O: RaceObserver + ?Sized>(
+    drivers: &[Driver],
+    circuit: &Circuit,
+    params: &SimulationParameters,
+    format: RaceFormat,
+    ratings: &Ratings,
+    rng: &mut R,
+    observer: &mut O
+) -> RaceState {
+    let mut race = RaceState::new(drivers, ratings, params, circuit, format, rng);
+    
+    while !race.is_finished() {
+        run_lap(drivers, &mut race, params, circuit, rng, observer);
+    }
     
-    // Final base performance
-    skill * team_perf * weather_adjustment
+    race
 }
 
-// Update race positions for the current lap
-pub fn update_race_positions(
-    positions: &mut Vec<(usize, f64, Duration, bool)>, 
-    driver_performance: &HashMap<usize, f64>,
-    params: &SimulationParameters
+/// Run the coming lap of a race, rolling for incidents before it once five laps
+/// are run if the parameters allow them, and report what happens to the observer
+pub fn run_lap<R: Rng + ?Sized, O: RaceObserver + ?Sized>(
+    drivers: &[Driver],
+    race: &mut RaceState,
+    params: &SimulationParameters,
+    circuit: &Circuit,
+    rng: &mut R,
+    observer: &mut O
 ) {
-    let mut rng = rand::thread_rng();
-    
-    // For each driver still in the race
-    for i in 0..positions.len() {
-        if !positions[i].3 {
-            continue; // Skip DNF'd drivers
+    if params.random_incidents && race.lap() >= 5 {
+        check_for_incidents(drivers, race, params, circuit, rng);
+        race.report_events(observer);
+    }
+    race.simulate_lap(params, circuit, rng);
+    race.report_events(observer);
+}
+
+/// How far a car is behind another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gap {
+    /// The car is leading
+    Leader,
+    /// Time between the two cars crossing the line on the same lap
+    Time(Duration),
+    /// Laps the car is down
+    Laps(u32),
+}
+
+impl fmt::Display for Gap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Gap::Leader => write!(f, "Leader"),
+            Gap::Time(gap) => write!(f, "+{:.3}s", gap.as_secs_f64()),
+            Gap::Laps(1) => write!(f, "+1 Lap"),
+            Gap::Laps(laps) => write!(f, "+{} Laps", laps),
         }
+    }
+}
+
+/// A lap time and who set it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LapRecord {
+    /// Index of the driver in the entry list
+    pub driver: usize,
+    pub lap: u32,
+    pub time: Duration,
+}
+
+/// A car in a simulated race
+#[derive(Debug, Clone, PartialEq)]
+pub struct CarState {
+    /// Index of the driver in the entry list
+    pub driver: usize,
+    /// Race pace on the 0-1 performance scale, higher is better
+    pub pace: f64,
+    /// Race pace on a soaked track, where the driver counts for more and the car for less
+    pub wet_pace: f64,
+    /// Starting position
+    pub grid: u32,
+    /// Race time at every crossing of the line: `crossings[0]` is the start,
+    /// `crossings[n]` the end of lap n
+    pub crossings: Vec<Duration>,
+    /// Why the car retired, if it did
+    pub retirement: Option<String>,
+    /// Sets of tyres run, in order
+    pub stints: Vec<Stint>,
+    /// The strategy the car follows
+    pub strategy: Strategy,
+}
+
+/// A set of tyres run by a car
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stint {
+    pub compound: Compound,
+    /// Laps the car had completed when the set was fitted
+    pub from_lap: u32,
+    /// Time spent in the pit lane to fit the set; `None` for the set the car
+    /// started on and one fitted while the race was stopped
+    pub pit_lane_time: Option<Duration>,
+}
+
+impl Stint {
+    /// The set a car starts the race on
+    pub fn starting(compound: Compound) -> Self {
+        Stint { compound, from_lap: 0, pit_lane_time: None }
+    }
+}
+
+impl CarState {
+    /// Laps completed
+    pub fn laps(&self) -> u32 {
+        (self.crossings.len() - 1) as u32
+    }
+    
+    /// Race time at the end of the last lap completed
+    pub fn race_time(&self) -> Duration {
+        self.crossings[self.crossings.len() - 1]
+    }
+    
+    /// Time of a lap, counting from 1
+    pub fn lap_time(&self, lap: u32) -> Option<Duration> {
+        let lap = lap as usize;
+        (lap >= 1 && lap < self.crossings.len()).then(|| self.crossings[lap] - self.crossings[lap - 1])
+    }
+    
+    /// Time of the last lap completed
+    pub fn last_lap(&self) -> Option<Duration> {
+        self.lap_time(self.laps())
+    }
+    
+    /// The car's fastest lap as (lap, time)
+    pub fn best_lap(&self) -> Option<(u32, Duration)> {
+        (1..=self.laps())
+            .filter_map(|lap| self.lap_time(lap).map(|time| (lap, time)))
+            .min_by_key(|&(_, time)| time)
+    }
+    
+    pub fn is_running(&self) -> bool {
+        self.retirement.is_none()
+    }
+    
+    /// Race pace on a track of a wetness, between the dry and the wet pace
+    pub fn pace_in(&self, wetness: f64) -> f64 {
+        self.pace + (self.wet_pace - self.pace) * wetness
+    }
+    
+    /// Compound of the tyres on the car
+    pub fn tyres(&self) -> Compound {
+        self.stints.last().map_or(Compound::Medium, |stint| stint.compound)
+    }
+    
+    /// Laps done on the tyres on the car
+    pub fn tyre_age(&self) -> u32 {
+        self.laps() - self.stints.last().map_or(0, |stint| stint.from_lap)
+    }
+    
+    /// Compound of every set run, in order
+    pub fn compounds_used(&self) -> Vec<Compound> {
+        self.stints.iter().map(|stint| stint.compound).collect()
+    }
+    
+    // Sets of a compound the car has yet to run
+    fn sets_left(&self, compound: Compound, params: &SimulationParameters) -> u32 {
+        let sets_used = self.stints.iter().filter(|stint| stint.compound == compound).count() as u32;
+        params.tyre_allocation.get(compound).saturating_sub(sets_used)
+    }
+    
+    // Laps completed by race time `time`
+    fn laps_at(&self, time: Duration) -> u32 {
+        self.crossings.iter().skip(1).take_while(|&&t| t <= time).count() as u32
+    }
+}
+
+/// The state of a race: every car with the times it crossed the line.
+///
+/// Each lap every running car sets a lap time, and the order, gaps, lapped
+/// cars and fastest laps all follow from the accumulated race times.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RaceState {
+    /// Cars in grid order
+    pub cars: Vec<CarState>,
+    /// Race distance in laps
+    pub total_laps: u32,
+    /// Whether cars must run two dry compounds in a dry race, as in a grand prix
+    pub compound_rule: bool,
+    /// Whether the race is green, neutralised or stopped on the coming lap
+    pub phase: Phase,
+    /// Laps the phase has left to run
+    pub phase_laps_left: u32,
+    /// Laps run under either safety car or a red flag, in order
+    pub phases: Vec<PhasePeriod>,
+    /// The conditions on every lap
+    pub weather: WeatherTimeline,
+    // Events yet to be reported
+    events: Vec<RaceEvent>,
+}
+
+impl RaceState {
+    /// Draw the weather of the race and line up the drivers in the order of a
+    /// simulated qualifying session, run in the conditions of the start, each
+    /// with a race pace that varies a little from race to race
+    pub fn new<R: Rng + ?Sized>(drivers: &[Driver], ratings: &Ratings, params: &SimulationParameters, circuit: &Circuit, format: RaceFormat, rng: &mut R) -> Self {
+        let weather = params.weather.timeline(circuit.laps, circuit, rng);
+        let start_wetness = weather.conditions(1).track_wetness;
+        let qualifying_variation = Normal::new(0.0, QUALIFYING_VARIATION).unwrap();
+        let race_variation = Normal::new(0.0, RACE_FORM_VARIATION).unwrap();
         
-        let driver_idx = positions[i].0;
-        let base_perf = *driver_performance.get(&driver_idx).unwrap_or(&0.9);
+        let mut entries: Vec<(usize, f64, f64, f64)> = drivers.iter().enumerate().map(|(i, driver)| {
+            let quali_performance = calculate_driver_base_performance(driver, ratings, start_wetness) * (1.0 + qualifying_variation.sample(rng));
+            let form = 1.0 + race_variation.sample(rng);
+            let race_pace = calculate_driver_base_performance(driver, ratings, 0.0) * form;
+            let wet_pace = calculate_driver_base_performance(driver, ratings, 1.0) * form;
+            (i, quali_performance, race_pace, wet_pace)
+        }).collect();
         
-        // Add lap-to-lap variation
-        let lap_variation = Normal::new(0.0, 0.01 * params.weather_factor).unwrap();
-        let variation = 1.0 + lap_variation.sample(&mut rng);
+        // Fastest in qualifying first
+        entries.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
         
-        // Adjust performance for this lap
-        let lap_performance = base_perf * variation;
-        positions[i].1 = lap_performance;
+        let mut race = Self::from_grid(entries.iter().map(|&(driver, _, pace, _)| (driver, pace)).collect(), circuit.laps);
+        for (car, &(_, _, _, wet_pace)) in race.cars.iter_mut().zip(&entries) {
+            car.wet_pace = wet_pace;
+        }
+        race.compound_rule = format == RaceFormat::GrandPrix;
+        race.weather = weather;
         
-        // Attempt overtake logic
-        if i > 0 && positions[i].3 && positions[i-1].3 {
-            let overtake_chance = (positions[i].1 - positions[i-1].1) * 2.5;
-            if overtake_chance > 0.0 && rng.gen::<f64>() < overtake_chance {
-                // Successful overtake
-                positions.swap(i, i-1);
+        // Every team follows the strategy for the race, each reacting to the
+        // cars around it in its own way
+        let strategy = Strategy::for_race(format, circuit.laps, circuit, params);
+        let mut teams: Vec<&str> = Vec::new();
+        for driver in drivers {
+            if !teams.contains(&driver.team.as_str()) {
+                teams.push(&driver.team);
             }
         }
+        for car in &mut race.cars {
+            let team = teams.iter().position(|&team| team == drivers[car.driver].team).unwrap_or(0);
+            car.strategy = Strategy {
+                reaction: TEAM_REACTIONS[team % TEAM_REACTIONS.len()],
+                ..strategy.clone()
+            };
+            car.stints = vec![Stint::starting(car.strategy.starting_compound(start_wetness, params))];
+        }
+        
+        race
     }
-}
-
-// Check for mechanical failures and incidents
-pub fn check_for_incidents(
-    drivers: &[Driver], 
-    positions: &mut Vec<(usize, f64, Duration, bool)>,
-    dnf_drivers: &mut Vec<usize>,
-    current_lap: u32,
-    params: &SimulationParameters
-) {
-    let mut rng = rand::thread_rng();
     
-    // Using underscore prefix to suppress the unused variable warning
-    for (_race_pos, (driver_idx, _, _, active)) in positions.iter_mut().enumerate() {
-        // Skip already DNF'd drivers
-        if !*active || dnf_drivers.contains(driver_idx) {
-            continue;
+    /// Have drivers follow the strategies of a scenario, matching them by code,
+    /// id or name
+    pub fn assign_strategies(&mut self, drivers: &[Driver], scenario: &Scenario, params: &SimulationParameters) -> Result<()> {
+        let start_wetness = self.weather.conditions(1).track_wetness;
+        let overrides = scenario.overrides.iter().map(|(name, strategy)| (name, strategy));
+        for (name, strategy) in scenario.strategies.iter().chain(overrides) {
+            let key = name.to_lowercase();
+            let car = self.cars.iter_mut()
+                .find(|car| {
+                    let driver = &drivers[car.driver];
+                    [&driver.code, &driver.id, &driver.name].iter().any(|n| n.to_lowercase() == key)
+                })
+                .ok_or_else(|| anyhow::anyhow!("No driver {} in the field", name))?;
+            
+            car.strategy = strategy.clone();
+            car.stints = vec![Stint::starting(strategy.starting_compound(start_wetness, params))];
         }
+        Ok(())
+    }
+    
+    /// Line up cars in the order given, as (driver index, race pace), on mediums
+    /// for a dry race
+    pub fn from_grid(grid: Vec<(usize, f64)>, total_laps: u32) -> Self {
+        let cars = grid.into_iter().enumerate().map(|(slot, (driver, pace))| CarState {
+            driver,
+            pace,
+            wet_pace: pace,
+            grid: slot as u32 + 1,
+            // Cars further down the grid start further from the line
+            crossings: vec![Duration::from_secs_f64(slot as f64 * GRID_SLOT_SECS)],
+            retirement: None,
+            stints: vec![Stint::starting(Compound::Medium)],
+            strategy: Strategy::default(),
+        }).collect();
         
-        let driver = &drivers[*driver_idx];
+        RaceState {
+            cars,
+            total_laps,
+            compound_rule: true,
+            phase: Phase::Green,
+            phase_laps_left: 0,
+            phases: Vec::new(),
+            weather: WeatherTimeline::dry(total_laps),
+            events: Vec::new(),
+        }
+    }
+    
+    /// Laps completed by the leader
+    pub fn lap(&self) -> u32 {
+        self.cars.iter().map(CarState::laps).max().unwrap_or(0)
+    }
+    
+    /// Whether the chequered flag has fallen, or no car is left running
+    pub fn is_finished(&self) -> bool {
+        self.lap() >= self.total_laps || !self.cars.iter().any(CarState::is_running)
+    }
+    
+    /// Neutralise or stop the race for the coming laps. A more serious phase
+    /// replaces the one under way, and the same phase is kept out for at least
+    /// the laps given.
+    pub fn deploy(&mut self, phase: Phase, laps: u32) {
+        if phase > self.phase {
+            self.events.push(RaceEvent::SafetyCar { lap: self.lap() + 1, phase, deployed: true });
+            self.phase = phase;
+            self.phase_laps_left = laps;
+        } else if phase == self.phase && phase != Phase::Green {
+            self.phase_laps_left = self.phase_laps_left.max(laps);
+        }
+    }
+    
+    // Whether the lap is the standing restart after a red flag
+    fn is_restart(&self, lap: u32) -> bool {
+        self.phases.last().is_some_and(|period| period.phase == Phase::RedFlag && period.to_lap + 1 == lap)
+    }
+    
+    /// Run the next lap. Every running car sets a lap time from its pace in the
+    /// conditions, the circuit, the fuel it carries and how its tyres suit the
+    /// wetness of the track and have worn, changing them
+    /// in the pits when its strategy calls for it or they are worn out; a car
+    /// that catches the car ahead passes it or is held up behind it. Under the
+    /// virtual safety car every car laps slower, and behind the safety car or a
+    /// red flag the field queues up without passing. After the final lap every
+    /// car takes the flag the next time it crosses the line, so lapped cars
+    /// finish laps down.
+    pub fn simulate_lap<R: Rng + ?Sized>(&mut self, params: &SimulationParameters, circuit: &Circuit, rng: &mut R) {
+        if self.is_finished() {
+            return;
+        }
+        let lap = self.lap() + 1;
+        let laps_before: Vec<u32> = self.cars.iter().map(CarState::laps).collect();
+        
+        // Lap times are less consistent the wetter the track
+        let wetness = self.weather.conditions(lap).track_wetness;
+        let lap_variation = Normal::new(0.0, LAP_TIME_VARIATION * (1.0 + wetness)).unwrap();
+        let base_lap_secs = circuit.base_lap_time().as_secs_f64();
+        let fuel_secs = FUEL_EFFECT_SECS_PER_LAP * self.total_laps.saturating_sub(lap) as f64;
+        let start_secs = if lap == 1 || self.is_restart(lap) { STANDING_START_SECS } else { 0.0 };
+        let following_gap = Duration::from_secs_f64(MIN_FOLLOWING_GAP_SECS);
+        let queue_gap = Duration::from_secs_f64(QUEUE_GAP_SECS);
         
-        // Check for mechanical failure
-        if utils::simulate_mechanical_failure(driver, params.reliability_factor) {
-            // This driver has a mechanical failure
-            *active = false;
-            dnf_drivers.push(*driver_idx);
+        // Every car decides whether to pit at the end of the lap from the order
+        // as it starts, unless the race is stopped
+        let race_order = self.running_order();
+        let pit_calls: Vec<Option<Compound>> = (0..self.cars.len()).map(|i| {
+            let position = race_order.iter().position(|&c| c == i).unwrap_or(0);
+            let ahead = position.checked_sub(1).map(|p| race_order[p]);
+            self.pit_decision(i, ahead, params, circuit).filter(|_| self.phase != Phase::RedFlag)
+        }).collect();
+        let pit_variation = Normal::new(0.0, PIT_STOP_VARIATION_SECS).unwrap();
+        
+        // Cars cross the line in the order they started the lap
+        let mut order: Vec<usize> = (0..self.cars.len()).filter(|&i| self.cars[i].is_running()).collect();
+        order.sort_by_key(|&i| self.cars[i].race_time());
+        
+        // The last crossing so far and the laps of the car that made it: the car
+        // directly ahead on the road
+        let mut car_ahead: Option<(Duration, u32)> = None;
+        
+        for i in order {
+            let new_tyres = pit_calls[i];
+            let car = &self.cars[i];
+            // A slow stop costs time, a perfect one cannot beat the pit lane,
+            // which costs less while the field is slowed down
+            let pit_secs = new_tyres.map_or(0.0, |_| {
+                circuit.pit_lane_loss_secs * self.phase.pit_loss_share() + pit_variation.sample(rng).abs()
+            });
+            let lap_secs = if self.phase == Phase::Green {
+                let performance_factor = 1.0 + (1.0 - car.pace_in(wetness)) * PERFORMANCE_TIME_SPREAD;
+                let tyre_secs = tyres::lap_time_offset(car.tyres(), car.tyre_age(), wetness, circuit, params);
+                base_lap_secs * performance_factor * (1.0 + lap_variation.sample(rng)) + fuel_secs + start_secs + tyre_secs
+            } else {
+                base_lap_secs * self.phase.lap_factor()
+            };
+            let mut crossing = car.race_time() + Duration::from_secs_f64(lap_secs.max(0.0) + pit_secs);
+            let laps = car.laps() + 1;
+            
+            // Behind the safety car the field closes up to the car ahead
+            if let Some((ahead, _)) = car_ahead.filter(|_| self.phase.bunches_field()) {
+                let catch_up = Duration::from_secs_f64(base_lap_secs * self.phase.catch_up_factor());
+                crossing = (ahead + queue_gap).max(car.race_time() + catch_up) + Duration::from_secs_f64(pit_secs);
+            }
             
-            // Print the incident
-            println!("\n{}", format!("LAP {} - INCIDENT: {} (#{}) - {}", 
-                current_lap, 
-                driver.name,
-                driver.number,
-                utils::get_random_incident()
-            ).red());
-        }
-        
-        // Check for racing incidents (more likely in wet conditions)
-        let incident_factor = if params.weather_factor < 0.8 { 3.0 } else { 1.0 };
-        let incident_chance = 0.0005 * incident_factor / params.reliability_factor;
-        
-        if rng.gen::<f64>() < incident_chance {
-            // Racing incident
-            *active = false;
-            dnf_drivers.push(*driver_idx);
+            // A car in the pit lane is off the track
+            if let Some(compound) = new_tyres {
+                let pit_lane_time = Some(Duration::from_secs_f64(pit_secs));
+                self.events.push(RaceEvent::PitStop { lap, driver: car.driver, from: car.tyres(), to: compound, pit_lane_time });
+                self.cars[i].crossings.push(crossing);
+                self.cars[i].stints.push(Stint { compound, from_lap: laps, pit_lane_time });
+                continue;
+            }
             
-            // Print the incident
-            println!("\n{}", format!("LAP {} - INCIDENT: {} (#{}) crashed!", 
-                current_lap, 
-                driver.name,
-                driver.number
-            ).red());
+            // Lapped cars are shown blue flags and let faster cars by
+            let racing = self.phase == Phase::Green;
+            if let Some((ahead, _)) = car_ahead.filter(|&(_, ahead_laps)| racing && ahead_laps >= laps) {
+                if crossing < ahead + following_gap {
+                    let passed = crossing < ahead
+                        && rng.gen::<f64>() < pass_probability((ahead - crossing).as_secs_f64(), circuit);
+                    if !passed {
+                        crossing = ahead + following_gap;
+                    }
+                }
+            }
+            
+            car_ahead = Some(match car_ahead {
+                Some((ahead, ahead_laps)) if ahead > crossing => (ahead, ahead_laps),
+                _ => (crossing, laps),
+            });
+            self.cars[i].crossings.push(crossing);
+        }
+        
+        self.record_overtakes(lap, &race_order, &pit_calls);
+        self.end_phase_lap(lap, params, circuit);
+        
+        if lap == self.total_laps {
+            self.take_flag();
+        }
+        
+        // A lap just run that beats every lap before it
+        if let Some(record) = self.fastest_lap() {
+            let set_now = self.cars.iter().zip(&laps_before)
+                .any(|(car, &before)| car.driver == record.driver && car.laps() == before + 1 && record.lap == car.laps());
+            if set_now {
+                self.events.push(RaceEvent::FastestLap { lap, driver: record.driver, time: record.time });
+            }
+        }
+        
+        self.events.push(RaceEvent::LapCompleted { lap });
+        if lap == self.total_laps {
+            let winner = self.running_order().first().filter(|&&i| self.cars[i].is_running()).map(|&i| self.cars[i].driver);
+            self.events.push(RaceEvent::Finish { lap, winner });
         }
     }
-}
-
-// Update the fastest lap record
-pub fn update_fastest_lap(
-    positions: &Vec<(usize, f64, Duration, bool)>,
-    lap: u32,
-    fastest_lap: &mut Option<(usize, Duration)>
-) {
-    // For each active driver, generate a lap time
-    for &(driver_idx, perf, _, active) in positions.iter() {
-        if !active {
-            continue;
+    
+    // Record the passes on track on a lap, from the order of the running cars
+    // as it started: a car that pitted on the lap neither makes nor loses one
+    fn record_overtakes(&mut self, lap: u32, order_before: &[usize], pit_calls: &[Option<Compound>]) {
+        let on_track = |i: &usize| self.cars[*i].is_running() && pit_calls[*i].is_none();
+        let before: Vec<usize> = order_before.iter().copied().filter(on_track).collect();
+        let after: Vec<usize> = self.running_order().into_iter().filter(on_track).collect();
+        
+        for (position, &car) in after.iter().enumerate() {
+            let was = before.iter().position(|&i| i == car).unwrap_or(0);
+            for &passed in &before[..was] {
+                if after[position + 1..].contains(&passed) {
+                    let (driver, passed) = (self.cars[car].driver, self.cars[passed].driver);
+                    self.events.push(RaceEvent::Overtake { lap, driver, passed });
+                }
+            }
+        }
+    }
+    
+    // Record a lap run under the safety car or a red flag, and once the phase
+    // is over let lapped cars through and, after a red flag, line the field up
+    // on new tyres for a standing restart
+    fn end_phase_lap(&mut self, lap: u32, params: &SimulationParameters, circuit: &Circuit) {
+        if self.phase == Phase::Green {
+            return;
+        }
+        match self.phases.last_mut() {
+            Some(period) if period.phase == self.phase && period.to_lap + 1 == lap => period.to_lap = lap,
+            _ => self.phases.push(PhasePeriod { phase: self.phase, from_lap: lap, to_lap: lap }),
         }
         
-        // Generate a lap time based on performance
-        let base_time = Duration::from_secs_f64(90.0); // 1:30 base time
-        let performance_factor = 1.0 + (1.0 - perf) * 0.15; // Performance adjustment
-        let lap_time = base_time.mul_f64(performance_factor);
+        self.phase_laps_left = self.phase_laps_left.saturating_sub(1);
+        if self.phase_laps_left > 0 {
+            return;
+        }
         
-        // Check if this is the fastest lap
-        if let Some((_, current_fastest)) = fastest_lap {
-            if lap_time < *current_fastest {
-                *fastest_lap = Some((driver_idx, lap_time));
+        if self.phase.bunches_field() && lap < self.total_laps {
+            let catch_up_secs = circuit.base_lap_time().as_secs_f64() * self.phase.catch_up_factor();
+            self.unlap_cars(Duration::from_secs_f64(catch_up_secs));
+        }
+        if self.phase == Phase::RedFlag && lap < self.total_laps {
+            self.change_tyres_while_stopped(lap, params, circuit);
+            self.line_up_for_restart();
+        }
+        if lap < self.total_laps {
+            self.events.push(RaceEvent::SafetyCar { lap, phase: self.phase, deployed: false });
+        }
+        self.phase = Phase::Green;
+    }
+    
+    // Wave lapped cars past the safety car to rejoin the back of the queue a lap
+    // up; `catch_up` is the quickest they can lap doing so
+    fn unlap_cars(&mut self, catch_up: Duration) {
+        let leader_laps = self.lap();
+        let queue_gap = Duration::from_secs_f64(QUEUE_GAP_SECS);
+        let Some(mut tail) = self.cars.iter()
+            .filter(|car| car.is_running() && car.laps() == leader_laps)
+            .map(CarState::race_time)
+            .max() else {
+            return;
+        };
+        
+        for i in self.running_order() {
+            let car = &mut self.cars[i];
+            if car.is_running() && car.laps() < leader_laps {
+                tail = (tail + queue_gap).max(car.race_time() + catch_up);
+                car.crossings.push(tail);
             }
-        } else {
-            *fastest_lap = Some((driver_idx, lap_time));
         }
     }
+    
+    // Fit every car with a new set for the restart while the race is stopped:
+    // the next compound of its strategy or one like the set it runs if they suit
+    // the track, or else the set to change to in the conditions
+    fn change_tyres_while_stopped(&mut self, lap: u32, params: &SimulationParameters, circuit: &Circuit) {
+        let wetness = self.weather.conditions(lap + 1).track_wetness;
+        let laps_left = self.total_laps.saturating_sub(lap);
+        let compound_rule = self.compound_rule;
+        
+        for car in self.cars.iter_mut().filter(|car| car.is_running()) {
+            let planned = car.strategy.stints.get(car.stints.len()).copied();
+            let compound = planned.into_iter()
+                .chain([car.tyres()])
+                .find(|&compound| tyres::suits(compound, wetness) && car.sets_left(compound, params) > 0)
+                .or_else(|| {
+                    let sets_left = |compound: Compound| car.sets_left(compound, params);
+                    tyres::next_compound(&car.compounds_used(), sets_left, laps_left, compound_rule, wetness, circuit, params)
+                });
+            if let Some(compound) = compound {
+                let from_lap = car.laps();
+                self.events.push(RaceEvent::PitStop { lap, driver: car.driver, from: car.tyres(), to: compound, pit_lane_time: None });
+                car.stints.push(Stint { compound, from_lap, pit_lane_time: None });
+            }
+        }
+    }
+    
+    // Line the running cars up on the grid in race order once all of them are
+    // back in the pit lane
+    fn line_up_for_restart(&mut self) {
+        let running: Vec<usize> = self.running_order().into_iter().filter(|&i| self.cars[i].is_running()).collect();
+        let Some(stopped) = running.iter().map(|&i| self.cars[i].race_time()).max() else {
+            return;
+        };
+        
+        for (slot, &i) in running.iter().enumerate() {
+            if let Some(crossing) = self.cars[i].crossings.last_mut() {
+                *crossing = stopped + Duration::from_secs_f64(slot as f64 * GRID_SLOT_SECS);
+            }
+        }
+    }
+    
+    // The compound a car changes to at the end of the coming lap, if it pits:
+    // for tyres that suit the track as it is on the lap, when its strategy calls
+    // for the next stop and no rain is on its way, or when its tyres are worn out
+    fn pit_decision(&self, index: usize, ahead: Option<usize>, params: &SimulationParameters, circuit: &Circuit) -> Option<Compound> {
+        let car = &self.cars[index];
+        if !car.is_running() {
+            return None;
+        }
+        let laps_left = self.total_laps.saturating_sub(car.laps() + 1);
+        if tyres::too_late_to_change(laps_left) {
+            return None;
+        }
+        
+        let used = car.compounds_used();
+        let sets_left = |compound: Compound| car.sets_left(compound, params);
+        let conditions = self.weather.conditions(car.laps() + 1);
+        let wetness = conditions.track_wetness;
+        let any_set = || tyres::next_compound(&used, sets_left, laps_left, self.compound_rule, wetness, circuit, params);
+        
+        let current = car.tyres();
+        if !tyres::suits(current, wetness) {
+            return any_set();
+        }
+        
+        // The next stop of the strategy, which plans for a dry race
+        let stop = car.stints.len() - 1;
+        let planned_lap = car.strategy.planned_pit_laps(self.total_laps, circuit, params).get(stop).copied();
+        let dry = !tyres::is_wet_track(wetness) && conditions.rain_probability < RAIN_STOP_PROBABILITY;
+        if let Some(planned_lap) = planned_lap.filter(|_| dry) {
+            let window = PitWindow {
+                lap: car.laps() + 1,
+                planned_lap,
+                gap_ahead: ahead.and_then(|ahead| match self.gap_between(index, ahead) {
+                    Gap::Time(gap) => Some(gap.as_secs_f64()),
+                    _ => None,
+                }),
+                rival_pitted: self.rival_pitted(index),
+                safety_car: self.phase.is_neutralised(),
+            };
+            if car.strategy.pits(&window) {
+                let planned = car.strategy.stints[stop + 1];
+                return if tyres::suits(planned, wetness) && sets_left(planned) > 0 { Some(planned) } else { any_set() };
+            }
+        }
+        
+        // Tyres about to go past their life
+        if car.tyre_age() + 1 >= tyres::tyre_life(current, circuit, params) {
+            return any_set();
+        }
+        
+        None
+    }
+    
+    // Whether a car on the same lap that was close to this one pitted in the last
+    // two laps, while this car has yet to make as many stops
+    fn rival_pitted(&self, index: usize) -> bool {
+        let car = &self.cars[index];
+        let attack_gap = Duration::from_secs_f64(ATTACK_GAP_SECS);
+        
+        self.cars.iter()
+            .filter(|rival| rival.is_running() && rival.laps() == car.laps() && rival.stints.len() > car.stints.len())
+            .any(|rival| {
+                let stop_lap = rival.stints[rival.stints.len() - 1].from_lap;
+                // Compare the two as they started the lap the rival pitted on
+                let before = stop_lap.saturating_sub(1) as usize;
+                stop_lap + 2 >= car.laps() && rival.crossings[before].abs_diff(car.crossings[before]) <= attack_gap
+            })
+    }
+    
+    // Flag every running car at its first crossing of the line after the winner's,
+    // dropping the laps lapped cars ran beyond it
+    fn take_flag(&mut self) {
+        let flag = self.cars.iter()
+            .filter(|car| car.is_running() && car.laps() >= self.total_laps)
+            .map(CarState::race_time)
+            .min();
+        let Some(flag) = flag else {
+            return;
+        };
+        
+        for car in self.cars.iter_mut().filter(|car| car.is_running()) {
+            if let Some(finish) = car.crossings.iter().skip(1).position(|&t| t >= flag) {
+                car.crossings.truncate(finish + 2);
+                let laps = car.laps();
+                car.stints.retain(|stint| stint.from_lap <= laps);
+            }
+        }
+        
+        // Cars that ran a single dry compound in a dry race are disqualified
+        if self.compound_rule {
+            for i in 0..self.cars.len() {
+                let car = &self.cars[i];
+                if car.is_running() && !tyres::meets_compound_rule(&car.compounds_used()) {
+                    self.retire(i, DISQUALIFIED);
+                }
+            }
+        }
+    }
+    
+    /// Retire a car from the race before the coming lap
+    pub fn retire(&mut self, car: usize, reason: &str) {
+        self.cars[car].retirement = Some(reason.to_string());
+        let lap = (self.lap() + 1).min(self.total_laps);
+        self.events.push(RaceEvent::Retirement { lap, driver: self.cars[car].driver, reason: reason.to_string() });
+    }
+    
+    /// Take the events that happened since they were last taken, in order
+    pub fn take_events(&mut self) -> Vec<RaceEvent> {
+        std::mem::take(&mut self.events)
+    }
+    
+    // Report the events that happened since the last report to an observer
+    fn report_events<O: RaceObserver + ?Sized>(&mut self, observer: &mut O) {
+        for event in self.take_events() {
+            observer.on_event(self, &event);
+        }
+    }
+    
+    /// Indices of the cars in race order: by laps completed, then by the time
+    /// they completed them, with retired cars last by distance covered
+    pub fn running_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.cars.len()).collect();
+        order.sort_by_key(|&i| {
+            let car = &self.cars[i];
+            (!car.is_running(), Reverse(car.laps()), car.race_time())
+        });
+        order
+    }
+    
+    /// How far a car is behind the leader
+    pub fn gap_to_leader(&self, car: usize) -> Gap {
+        match self.running_order().first() {
+            Some(&leader) => self.gap_between(car, leader),
+            None => Gap::Leader,
+        }
+    }
+    
+    /// How far a car is behind the car ahead of it
+    pub fn interval(&self, car: usize) -> Gap {
+        let order = self.running_order();
+        match order.iter().position(|&i| i == car) {
+            Some(pos) if pos > 0 => self.gap_between(car, order[pos - 1]),
+            _ => Gap::Leader,
+        }
+    }
+    
+    // Gap of a car to a car ahead of it: the laps the car ahead had gained when
+    // it last crossed the line, or else the time between their crossings of the same lap
+    fn gap_between(&self, car: usize, ahead: usize) -> Gap {
+        if car == ahead {
+            return Gap::Leader;
+        }
+        let (car, ahead) = (&self.cars[car], &self.cars[ahead]);
+        
+        let laps_down = ahead.laps().saturating_sub(car.laps_at(ahead.race_time()) + 1);
+        if laps_down > 0 {
+            return Gap::Laps(laps_down);
+        }
+        
+        let lap = ahead.laps().min(car.laps()) as usize;
+        Gap::Time(car.crossings[lap].saturating_sub(ahead.crossings[lap]))
+    }
+    
+    /// The fastest lap of the race so far
+    pub fn fastest_lap(&self) -> Option<LapRecord> {
+        self.cars.iter()
+            .filter_map(|car| car.best_lap().map(|(lap, time)| LapRecord { driver: car.driver, lap, time }))
+            .min_by_key(|record| record.time)
+    }
+    
+    /// The classification as race results, scored with the points system
+    pub fn results(&self, drivers: &[Driver], circuit: &Circuit, format: RaceFormat, points_system: &PointsSystem) -> Vec<RaceResult> {
+        let order = self.running_order();
+        let Some(&leader) = order.first() else {
+            return Vec::new();
+        };
+        // Classification is measured against the winner, or the full distance if nobody finished
+        let winner_laps = if self.cars[leader].is_running() { self.cars[leader].laps() } else { self.total_laps };
+        let distance = distance_completed(winner_laps, self.total_laps);
+        let fastest_lap = self.fastest_lap();
+        
+        // Every car's fastest lap, ranked
+        let mut best_laps: Vec<(usize, Duration)> = self.cars.iter().enumerate()
+            .filter_map(|(i, car)| car.best_lap().map(|(_, time)| (i, time)))
+            .collect();
+        best_laps.sort_by_key(|&(_, time)| time);
+        
+        order.iter().enumerate().map(|(pos, &i)| {
+            let car = &self.cars[i];
+            let driver = drivers[car.driver].clone();
+            let position = pos as u32 + 1;
+            
+            let (time, status) = match (&car.retirement, self.gap_between(i, leader)) {
+                (Some(reason), _) => (None, reason.clone()),
+                (None, Gap::Leader) => (Some(utils::format_race_time(car.race_time())), "Finished".to_string()),
+                (None, Gap::Time(gap)) => (Some(format!("+{:.3}", gap.as_secs_f64())), "Finished".to_string()),
+                (None, laps_down) => (None, laps_down.to_string()),
+            };
+            
+            // Only classified cars score, with the fastest lap bonus if the season awards one
+            let set_fastest_lap = fastest_lap.is_some_and(|record| record.driver == car.driver);
+            let points = if is_classified(&status, car.laps(), winner_laps) {
+                points_system.score(format, position, set_fastest_lap, distance)
+            } else {
+                0.0
+            };
+            
+            let fastest_lap = car.best_lap().map(|(lap, lap_time)| FastestLap {
+                rank: best_laps.iter().position(|&(c, _)| c == i).unwrap_or(0) as u32 + 1,
+                lap,
+                time: utils::format_duration_as_lap_time(lap_time),
+                average_speed_kph: Some(circuit.length_km * 3600.0 / lap_time.as_secs_f64()),
+            });
+            
+            RaceResult {
+                position,
+                number: driver.number,
+                // The roster only knows the team's name, not the feed's constructor id
+                constructor_id: String::new(),
+                driver,
+                grid: car.grid,
+                time,
+                time_millis: car.is_running().then(|| car.race_time().as_millis() as u64),
+                points,
+                laps: car.laps(),
+                status,
+                fastest_lap,
+            }
+        }).collect()
+    }
 }
 
-// Display a summary of the current lap (top positions and gaps)
-fn display_lap_summary(
-    drivers: &[Driver],
-    positions: &Vec<(usize, f64, Duration, bool)>,
-    _lap: u32,
-    dnf_drivers: &Vec<usize>,
-    fastest_lap: Option<(usize, Duration)>
-) {
-    // Skip unused variable warnings by using underscore prefix
-    let _dnf_drivers = dnf_drivers; // Not directly used in this function
+// Chance that a car which is faster over a lap by `advantage` seconds gets
+// past the car ahead, lower where overtaking is difficult
+fn pass_probability(advantage: f64, circuit: &Circuit) -> f64 {
+    (advantage / (advantage + PASSING_ADVANTAGE_SECS) * circuit.overtaking_factor()).min(0.95)
+}
+
+// Calculate base performance for a driver on a track of a wetness (0-1 scale, higher is better)
+pub fn calculate_driver_base_performance(driver: &Driver, ratings: &Ratings, wetness: f64) -> f64 {
+    // Driver skill and car performance from the fitted ratings
+    let skill = ratings.driver_skill(driver);
+    let team_perf = ratings.team_performance(&driver.team);
     
-    // Show top 5 positions
-    let max_to_show = 5.min(positions.len());
-    let mut prev_gap: Option<Duration> = None;
+    // The wetter the track, the more a driver's skill makes up for the car,
+    // shuffling the order
+    let wetness = wetness.clamp(0.0, 1.0);
+    skill.powf(1.0 + wetness) * team_perf.powf(1.0 - wetness / 2.0)
+}
+
+/// Roll for mechanical failures and crashes among the running cars before a
+/// lap, and retire the cars affected, bringing out the safety car, the virtual
+/// safety car or a red flag with the circuit's chances. Returns the cars retired.
+pub fn check_for_incidents<R: Rng + ?Sized>(
+    drivers: &[Driver], 
+    race: &mut RaceState,
+    params: &SimulationParameters,
+    circuit: &Circuit,
+    rng: &mut R
+) -> Vec<usize> {
+    let mut retired = Vec::new();
     
-    for i in 0..max_to_show {
-        let (driver_idx, _, _, active) = positions[i];
-        if !active {
+    // Racing incidents are more likely the wetter the track
+    let incident_factor = 1.0 + 2.0 * race.weather.conditions(race.lap() + 1).track_wetness;
+    let incident_chance = 0.0005 * incident_factor / params.reliability_factor;
+    
+    for i in 0..race.cars.len() {
+        if !race.cars[i].is_running() {
             continue;
         }
         
-        let driver = &drivers[driver_idx];
-        let pos_str = format!("P{}", i+1);
-        let pos_colored = match i {
+        let driver = &drivers[race.cars[i].driver];
+        
+        // The chance of a failure during the race is spread over its laps
+        let failure_chance = utils::mechanical_failure_chance(driver, params.reliability_factor) / race.total_laps.max(1) as f64;
+        
+        let reason = if rng.gen::<f64>() < failure_chance {
+            utils::get_random_incident(rng)
+        } else if rng.gen::<f64>() < incident_chance {
+            "Accident"
+        } else {
+            continue;
+        };
+        race.retire(i, reason);
+        retired.push(i);
+        
+        let phase = phases::draw_phase(CRASHES.contains(&reason), circuit, rng);
+        let laps = phase.draw_laps(rng);
+        race.deploy(phase, laps);
+    }
+    
+    retired
+}
+
+// Commentary of a race lap by lap: the conditions, incidents, phases, passes
+// and stops of every lap and the top of the order at the end of it, pausing
+// between laps
+struct LapByLapDisplay<'a> {
+    drivers: &'a [Driver],
+    // The last lap a heading was shown for
+    lap_shown: u32,
+}
+
+impl RaceObserver for LapByLapDisplay<'_> {
+    fn on_event(&mut self, race: &RaceState, event: &RaceEvent) {
+        let lap = event.lap();
+        if lap > self.lap_shown {
+            self.lap_shown = lap;
+            display_lap_heading(race, lap);
+        }
+        
+        match event {
+            RaceEvent::Retirement { driver, reason, .. } => display_retirement(&self.drivers[*driver], reason, lap),
+            RaceEvent::SafetyCar { phase, deployed: true, .. } => display_phase_deployed(*phase, lap),
+            RaceEvent::SafetyCar { phase, deployed: false, .. } => display_phase_ended(*phase, lap),
+            RaceEvent::PitStop { driver, from, to, pit_lane_time, .. } => {
+                display_pit_stop(&self.drivers[*driver], *from, *to, *pit_lane_time, lap);
+            },
+            RaceEvent::Overtake { driver, passed, .. } => display_overtake(&self.drivers[*driver], &self.drivers[*passed], lap),
+            RaceEvent::LapCompleted { .. } => {
+                // Display current positions (top 5)
+                display_lap_summary(self.drivers, race);
+                
+                if !race.is_finished() {
+                    // Interactive mode - wait for user to continue
+                    if lap.is_multiple_of(10) || lap + 1 == race.total_laps {
+                        println!("\nPress Enter to continue...");
+                        wait_for_user_input();
+                    } else {
+                        // Short delay between laps for race feel
+                        thread::sleep(Duration::from_millis(800));
+                    }
+                }
+            },
+            // The lap summary marks the fastest lap, and the results follow the flag
+            RaceEvent::FastestLap { .. } | RaceEvent::Finish { .. } => {},
+        }
+    }
+}
+
+// A progress bar over the laps of a race, with the retirements announced as
+// they happen
+struct ProgressDisplay<'a> {
+    drivers: &'a [Driver],
+    pb: ProgressBar,
+}
+
+impl RaceObserver for ProgressDisplay<'_> {
+    fn on_event(&mut self, _race: &RaceState, event: &RaceEvent) {
+        match event {
+            RaceEvent::Retirement { lap, driver, reason } => display_retirement(&self.drivers[*driver], reason, *lap),
+            RaceEvent::LapCompleted { lap } => {
+                self.pb.set_position(*lap as u64);
+                thread::sleep(Duration::from_millis(10)); // Small delay for visual effect
+            },
+            _ => {},
+        }
+    }
+}
+
+// Show the lap about to be run, with the conditions when the track is wet,
+// and any change in the weather
+fn display_lap_heading(race: &RaceState, lap: u32) {
+    let conditions = race.weather.conditions(lap);
+    if conditions.is_raining() || tyres::is_wet_track(conditions.track_wetness) {
+        println!("\n{}", format!("Lap {}/{} - {}", lap, race.total_laps, conditions).bold());
+    } else {
+        println!("\n{}", format!("Lap {}/{}", lap, race.total_laps).bold());
+    }
+    display_weather_change(&race.weather, lap);
+}
+
+// Announce a driver retiring on a lap
+fn display_retirement(driver: &Driver, reason: &str, lap: u32) {
+    println!("\n{}", format!("LAP {} - INCIDENT: {} (#{}) - {}", 
+        lap, 
+        driver.name,
+        driver.number,
+        reason
+    ).red());
+}
+
+// Announce a pass on track
+fn display_overtake(driver: &Driver, passed: &Driver, lap: u32) {
+    println!("LAP {} - OVERTAKE: {} passes {}", lap, driver.name, passed.name);
+}
+
+// Announce the safety car, virtual safety car or red flag coming out before a lap
+fn display_phase_deployed(phase: Phase, lap: u32) {
+    let message = match phase {
+        Phase::Green => return,
+        Phase::VirtualSafetyCar => format!("LAP {} - VIRTUAL SAFETY CAR: every car slows to the same delta", lap).yellow(),
+        Phase::SafetyCar => format!("LAP {} - SAFETY CAR: the field bunches up behind it", lap).yellow(),
+        Phase::RedFlag => format!("LAP {} - RED FLAG: the race is stopped and cars return to the pit lane", lap).red(),
+    };
+    println!("\n{}", message.bold());
+}
+
+// Announce the end of a phase at the end of a lap
+fn display_phase_ended(phase: Phase, lap: u32) {
+    let message = match phase {
+        Phase::Green => return,
+        Phase::VirtualSafetyCar => format!("LAP {} - GREEN FLAG: the virtual safety car period ends", lap),
+        Phase::SafetyCar => format!("LAP {} - GREEN FLAG: the safety car is in and racing resumes", lap),
+        Phase::RedFlag => format!("LAP {} - RESTART: the field lines up on the grid on new tyres for a standing restart", lap),
+    };
+    println!("{}", message.green().bold());
+}
+
+// Announce rain starting or stopping before a lap, and the track crossing over
+// to another kind of tyre
+fn display_weather_change(weather: &WeatherTimeline, lap: u32) {
+    if lap <= 1 {
+        return;
+    }
+    let (before, now) = (weather.conditions(lap - 1), weather.conditions(lap));
+    
+    if now.is_raining() && !before.is_raining() {
+        println!("{}", format!("LAP {} - RAIN: {}", lap, now).blue().bold());
+    } else if before.is_raining() && !now.is_raining() {
+        println!("{}", format!("LAP {} - RAIN STOPS: the track starts to dry", lap).blue().bold());
+    }
+    
+    let fastest = tyres::fastest_compound(now.track_wetness);
+    if !tyres::suits(tyres::fastest_compound(before.track_wetness), now.track_wetness) {
+        let kind = match fastest {
+            Compound::Intermediate => "intermediates",
+            Compound::Wet => "full wets",
+            _ => "slicks",
+        };
+        println!("{}", format!("LAP {} - CROSSOVER: {} are now the tyres to be on", lap, kind).blue().bold());
+    }
+}
+
+// Announce a driver changing tyres at the end of a lap
+fn display_pit_stop(driver: &Driver, from: Compound, to: Compound, pit_lane_time: Option<Duration>, lap: u32) {
+    let stop = match pit_lane_time {
+        Some(time) => format!("{:.1}s in the pit lane", time.as_secs_f64()),
+        None => "changed while the race was stopped".to_string(),
+    };
+    println!("{}", format!("LAP {} - PIT: {} (#{}) - {} to {} ({})",
+        lap,
+        driver.name,
+        driver.number,
+        from,
+        to,
+        stop
+    ).cyan());
+}
+
+// Display a summary of the current lap (top positions, intervals, last laps and tyres)
+fn display_lap_summary(drivers: &[Driver], race: &RaceState) {
+    let fastest_lap = race.fastest_lap();
+    
+    // Show the top 5 cars still running
+    let running = race.running_order().into_iter().filter(|&i| race.cars[i].is_running());
+    
+    for (pos, i) in running.take(5).enumerate() {
+        let car = &race.cars[i];
+        let driver = &drivers[car.driver];
+        let pos_str = format!("P{}", pos + 1);
+        let pos_colored = match pos {
             0 => pos_str.bright_yellow(),
             1 => pos_str.bright_white(),
             2 => pos_str.yellow(),
             _ => pos_str.normal(),
         };
         
-        // Calculate gap to leader or car ahead
-        let gap_str = if i == 0 {
-            "Leader".to_string()
-        } else {
-            // We don't actually need to use prev_gap_time, just need to check if it exists
-            let _prev_gap_exists = prev_gap.is_some();
-            let gap_to_next = Duration::from_millis(
-                (positions[i].1 - positions[i-1].1).abs() as u64 * 1000
-            );
-            format!("+{:.1}s", gap_to_next.as_secs_f64())
-        };
-        
-        // Update gap for next iteration
-        prev_gap = Some(Duration::from_secs_f64(positions[i].1 as f64));
-        
         // Show fastest lap indicator
-        let fl_indicator = if let Some((fl_driver, _)) = fastest_lap {
-            if fl_driver == driver_idx {
-                " 🟣".purple()
-            } else {
-                "".normal()
-            }
+        let fl_indicator = if fastest_lap.is_some_and(|record| record.driver == car.driver) {
+            " 🟣".purple()
         } else {
             "".normal()
         };
         
-        println!("{:<4} {:<20} {:<15} {:<8} {}", 
+        println!("{:<4} {:<20} {:<15} {:<10} {:<10} {:<5}{}", 
             pos_colored,
             driver.name,
             driver.team.bright_cyan(),
-            gap_str,
+            race.interval(i).to_string(),
+            car.last_lap().map(utils::format_duration_as_lap_time).unwrap_or_default(),
+            format!("{} {}", car.tyres().letter(), car.tyre_age()),
             fl_indicator
         );
     }
@@ -414,116 +1274,85 @@ fn display_lap_summary(
 // Display the final race results
 fn display_final_results(
     drivers: &[Driver], 
-    positions: &Vec<(usize, f64, Duration, bool)>,
-    dnf_drivers: &Vec<usize>,
-    fastest_lap: Option<(usize, Duration)>
+    circuit: &Circuit,
+    race: &RaceState,
+    format: RaceFormat,
+    points_system: &PointsSystem
 ) {
-    println!("\n{}", "RACE RESULTS".green().bold());
-    println!("{}", "-".repeat(60));
+    println!("\n{}", format!("{} RESULTS", format_title(format).to_uppercase()).green().bold());
+    println!("{}", "-".repeat(80));
     
-    println!("{:<3} {:<20} {:<15} {:<10} {}", 
+    println!("{:<3} {:<20} {:<15} {:<14} {:<9} {}", 
         "Pos".bold(),
         "Driver".bold(),
         "Team".bold(),
         "Time".bold(),
+        "Tyres".bold(),
         "Points".bold()
     );
     
-    println!("{}", "-".repeat(60));
+    println!("{}", "-".repeat(80));
     
-    // Display finishers
-    for (i, &(driver_idx, _, _, active)) in positions.iter().enumerate() {
-        if !active {
-            continue; // Skip DNFs for now
-        }
+    for result in race.results(drivers, circuit, format, points_system) {
+        // The compounds run, e.g. "M-H-S"
+        let tyres = race.cars.iter()
+            .find(|car| drivers[car.driver].id == result.driver.id)
+            .map(|car| car.stints.iter().map(|s| s.compound.letter().to_string()).collect::<Vec<_>>().join("-"))
+            .unwrap_or_default();
         
-        let driver = &drivers[driver_idx];
-        
-        let pos = i + 1;
-        let pos_str = pos.to_string();
-        let pos_colored = match pos {
+        let pos_str = result.position.to_string();
+        let pos_colored = match result.position {
+            _ if result.status == DISQUALIFIED => "DSQ".red(),
+            _ if result.time.is_none() && !result.status.starts_with('+') => "DNF".red(),
             1 => pos_str.bright_yellow(),
             2 => pos_str.bright_white(),
             3 => pos_str.yellow(),
             _ => pos_str.normal(),
         };
         
-        // Calculate points
-        let mut points = match pos {
-            1 => 25,
-            2 => 18,
-            3 => 15,
-            4 => 12,
-            5 => 10,
-            6 => 8,
-            7 => 6,
-            8 => 4,
-            9 => 2,
-            10 => 1,
-            _ => 0,
-        };
-        
-        // Add point for fastest lap if in top 10
-        if let Some((fl_driver_idx, _)) = fastest_lap {
-            if fl_driver_idx == driver_idx && pos <= 10 {
-                points += 1;
-            }
-        }
-        
         // Show fastest lap indicator
-        let fl_indicator = if let Some((fl_driver, _)) = fastest_lap {
-            if fl_driver == driver_idx {
-                " 🟣 FASTEST LAP".purple()
-            } else {
-                "".normal()
-            }
+        let fl_indicator = if result.fastest_lap.as_ref().is_some_and(|fl| fl.rank == 1) {
+            " 🟣 FASTEST LAP".purple()
         } else {
             "".normal()
         };
         
-        println!("{:<3} {:<20} {:<15} {:<10} {:<3}{}", 
+        println!("{:<3} {:<20} {:<15} {:<14} {:<9} {:<3}{}", 
             pos_colored,
-            driver.name,
-            driver.team,
-            format!("+{:.3}s", (i as f64) * 2.5), // Simplified time gaps
-            points,
+            result.driver.name,
+            result.driver.team,
+            result.time.clone().unwrap_or(result.status),
+            tyres,
+            utils::format_points(result.points),
             fl_indicator
         );
     }
     
-    // Display DNFs
-    for &driver_idx in dnf_drivers {
-        let driver = &drivers[driver_idx];
-        println!("{:<3} {:<20} {:<15} {:<10} {}", 
-            "DNF".red(),
-            driver.name,
-            driver.team,
-            "DNF".red(),
-            0
-        );
-    }
-    
     // Show fastest lap details
-    if let Some((fl_driver_idx, fl_time)) = fastest_lap {
-        let fl_driver = &drivers[fl_driver_idx];
-        println!("\n{} {} - {} - {:.3}s", 
+    if let Some(record) = race.fastest_lap() {
+        let fl_driver = &drivers[record.driver];
+        println!("\n{} {} - {} - {} (lap {})", 
             "FASTEST LAP:".purple().bold(),
             fl_driver.name,
             fl_driver.team,
-            fl_time.as_secs_f64()
+            utils::format_duration_as_lap_time(record.time),
+            record.lap
         );
     }
-}
-
-// Helper function to multiply Duration by float
-pub trait DurationExt {
-    fn mul_f64(&self, factor: f64) -> Duration;
-}
-
-impl DurationExt for Duration {
-    fn mul_f64(&self, factor: f64) -> Duration {
-        let nanos = self.as_nanos() as f64 * factor;
-        Duration::from_nanos(nanos as u64)
+    
+    // Show the laps it rained
+    for (from, to) in race.weather.rain_spells() {
+        let laps = if from == to { format!("lap {}", from) } else { format!("laps {}-{}", from, to) };
+        println!("{}", format!("Rain: {}", laps).blue().bold());
+    }
+    
+    // Show the laps the race was neutralised or stopped
+    for period in &race.phases {
+        let label = match period.phase {
+            Phase::RedFlag => period.to_string().red(),
+            _ => period.to_string().yellow(),
+        };
+        println!("{}", label.bold());
     }
 }
 
@@ -0,0 +1,149 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::time::Duration;
+
+use super::phases::Phase;
+use super::RaceState;
+use crate::models::{Compound, Driver};
+
+/// Something that happened in a race, as the engine reports it. Drivers are
+/// given by their index in the entry list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RaceEvent {
+    /// The leader completed a lap
+    LapCompleted { lap: u32 },
+    /// A driver passed a car on track, one that did not pit on the lap
+    Overtake { lap: u32, driver: usize, passed: usize },
+    /// A driver changed tyres at the end of a lap; `pit_lane_time` is `None`
+    /// for a set fitted while the race was stopped
+    PitStop { lap: u32, driver: usize, from: Compound, to: Compound, pit_lane_time: Option<Duration> },
+    /// A driver retired before a lap, or was disqualified at the flag
+    Retirement { lap: u32, driver: usize, reason: String },
+    /// A driver set the fastest lap of the race so far
+    FastestLap { lap: u32, driver: usize, time: Duration },
+    /// The safety car, the virtual safety car or a red flag was deployed before
+    /// a lap, or withdrawn at the end of one
+    SafetyCar { lap: u32, phase: Phase, deployed: bool },
+    /// The chequered flag fell; `winner` is `None` if nobody was classified
+    /// first, every car having retired or been disqualified
+    Finish { lap: u32, winner: Option<usize> },
+}
+
+impl RaceEvent {
+    /// The lap the event happened on
+    pub fn lap(&self) -> u32 {
+        match self {
+            RaceEvent::LapCompleted { lap }
+            | RaceEvent::Overtake { lap, .. }
+            | RaceEvent::PitStop { lap, .. }
+            | RaceEvent::Retirement { lap, .. }
+            | RaceEvent::FastestLap { lap, .. }
+            | RaceEvent::SafetyCar { lap, .. }
+            | RaceEvent::Finish { lap, .. } => *lap,
+        }
+    }
+    
+    /// The event as a JSON object, naming drivers by their code
+    pub fn to_json(&self, drivers: &[Driver]) -> Value {
+        let code = |driver: &usize| drivers.get(*driver).map_or_else(|| driver.to_string(), |d| d.code.clone());
+        match self {
+            RaceEvent::LapCompleted { lap } => json!({ "event": "lap_completed", "lap": lap }),
+            RaceEvent::Overtake { lap, driver, passed } => json!({
+                "event": "overtake", "lap": lap, "driver": code(driver), "passed": code(passed),
+            }),
+            RaceEvent::PitStop { lap, driver, from, to, pit_lane_time } => json!({
+                "event": "pit_stop", "lap": lap, "driver": code(driver), "from": from, "to": to,
+                "pit_lane_secs": pit_lane_time.map(|time| time.as_secs_f64()),
+            }),
+            RaceEvent::Retirement { lap, driver, reason } => json!({
+                "event": "retirement", "lap": lap, "driver": code(driver), "reason": reason,
+            }),
+            RaceEvent::FastestLap { lap, driver, time } => json!({
+                "event": "fastest_lap", "lap": lap, "driver": code(driver), "time_secs": time.as_secs_f64(),
+            }),
+            RaceEvent::SafetyCar { lap, phase, deployed } => json!({
+                "event": "safety_car", "lap": lap, "phase": phase.to_string(), "deployed": deployed,
+            }),
+            RaceEvent::Finish { lap, winner } => json!({
+                "event": "finish", "lap": lap, "winner": winner.as_ref().map(code),
+            }),
+        }
+    }
+}
+
+/// Something that follows a race through its events, such as the terminal
+/// display or a log
+pub trait RaceObserver {
+    /// Called with every event in the order they happened, along with the race
+    /// as it stands once the events of the moment are in
+    fn on_event(&mut self, race: &RaceState, event: &RaceEvent);
+}
+
+/// Nobody watching
+impl RaceObserver for () {
+    fn on_event(&mut self, _race: &RaceState, _event: &RaceEvent) {}
+}
+
+/// Collect every event
+impl RaceObserver for Vec<RaceEvent> {
+    fn on_event(&mut self, _race: &RaceState, event: &RaceEvent) {
+        self.push(event.clone());
+    }
+}
+
+/// Both observers, the first one first
+impl<A: RaceObserver, B: RaceObserver> RaceObserver for (A, B) {
+    fn on_event(&mut self, race: &RaceState, event: &RaceEvent) {
+        self.0.on_event(race, event);
+        self.1.on_event(race, event);
+    }
+}
+
+/// An observer if there is one
+impl<O: RaceObserver> RaceObserver for Option<O> {
+    fn on_event(&mut self, race: &RaceState, event: &RaceEvent) {
+        if let Some(observer) = self {
+            observer.on_event(race, event);
+        }
+    }
+}
+
+impl<O: RaceObserver + ?Sized> RaceObserver for &mut O {
+    fn on_event(&mut self, race: &RaceState, event: &RaceEvent) {
+        (**self).on_event(race, event);
+    }
+}
+
+/// Log every event as a line of JSON
+pub struct JsonLog<'a, W: Write> {
+    drivers: &'a [Driver],
+    writer: W,
+    // The first write that failed, reported when the log is finished
+    error: Option<std::io::Error>,
+}
+
+impl<'a, W: Write> JsonLog<'a, W> {
+    pub fn new(drivers: &'a [Driver], writer: W) -> Self {
+        JsonLog { drivers, writer, error: None }
+    }
+    
+    /// Flush the log and hand back the writer, or the first error writing it
+    pub fn finish(mut self) -> Result<W> {
+        if let Some(error) = self.error {
+            return Err(error.into());
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> RaceObserver for JsonLog<'_, W> {
+    fn on_event(&mut self, _race: &RaceState, event: &RaceEvent) {
+        if self.error.is_none() {
+            if let Err(error) = writeln!(self.writer, "{}", event.to_json(self.drivers)) {
+                self.error = Some(error);
+            }
+        }
+    }
+}
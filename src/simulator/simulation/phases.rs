@@ -0,0 +1,136 @@
+use rand::Rng;
+use std::fmt;
+
+use crate::models::Circuit;
+
+/// Lap time of the leader behind the safety car, and of the cars catching up
+/// with the queue behind it, as multiples of a racing lap
+const SAFETY_CAR_LAP_FACTOR: f64 = 1.4;
+const SAFETY_CAR_CATCH_UP_FACTOR: f64 = 1.1;
+
+/// Lap time under the virtual safety car, which every car has to keep to, as a
+/// multiple of a racing lap
+const VIRTUAL_SAFETY_CAR_LAP_FACTOR: f64 = 1.35;
+
+/// Gap between the cars queuing behind the safety car, in seconds
+pub const QUEUE_GAP_SECS: f64 = 0.8;
+
+/// Share of the usual pit lane time lost by a stop while the field is slowed down
+const SAFETY_CAR_PIT_LOSS_SHARE: f64 = 0.5;
+const VIRTUAL_SAFETY_CAR_PIT_LOSS_SHARE: f64 = 0.65;
+
+/// Laps the safety car and the virtual safety car stay out for, at least and at most
+const SAFETY_CAR_LAPS: (u32, u32) = (3, 5);
+const VIRTUAL_SAFETY_CAR_LAPS: (u32, u32) = (1, 3);
+
+/// The state of the race on track
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Phase {
+    /// Racing
+    #[default]
+    Green,
+    /// Every car keeps to a slower lap time, keeping the gaps between them
+    VirtualSafetyCar,
+    /// The field queues up behind the safety car and cannot pass
+    SafetyCar,
+    /// The race is stopped; cars return to the pit lane and restart from the grid
+    RedFlag,
+}
+
+/// Laps run under a phase other than racing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhasePeriod {
+    pub phase: Phase,
+    pub from_lap: u32,
+    pub to_lap: u32,
+}
+
+impl Phase {
+    /// Whether the field is slowed down, so that a pit stop costs less
+    pub fn is_neutralised(self) -> bool {
+        matches!(self, Phase::SafetyCar | Phase::VirtualSafetyCar)
+    }
+    
+    /// Whether the cars queue up behind a car ahead, bunching up the field
+    pub fn bunches_field(self) -> bool {
+        matches!(self, Phase::SafetyCar | Phase::RedFlag)
+    }
+    
+    /// Share of the usual pit lane time a stop costs during the phase
+    pub fn pit_loss_share(self) -> f64 {
+        match self {
+            Phase::SafetyCar => SAFETY_CAR_PIT_LOSS_SHARE,
+            Phase::VirtualSafetyCar => VIRTUAL_SAFETY_CAR_PIT_LOSS_SHARE,
+            Phase::Green | Phase::RedFlag => 1.0,
+        }
+    }
+    
+    /// Lap time of the leader on the road, as a multiple of a racing lap
+    pub fn lap_factor(self) -> f64 {
+        match self {
+            Phase::Green => 1.0,
+            Phase::VirtualSafetyCar => VIRTUAL_SAFETY_CAR_LAP_FACTOR,
+            Phase::SafetyCar | Phase::RedFlag => SAFETY_CAR_LAP_FACTOR,
+        }
+    }
+    
+    /// Quickest lap of a car catching up with the queue, as a multiple of a racing lap
+    pub fn catch_up_factor(self) -> f64 {
+        match self {
+            Phase::SafetyCar | Phase::RedFlag => SAFETY_CAR_CATCH_UP_FACTOR,
+            phase => phase.lap_factor(),
+        }
+    }
+    
+    /// Laps the phase lasts; a red flag stops the race for a single lap
+    pub fn draw_laps<R: Rng + ?Sized>(self, rng: &mut R) -> u32 {
+        let (min, max) = match self {
+            Phase::Green => (0, 0),
+            Phase::VirtualSafetyCar => VIRTUAL_SAFETY_CAR_LAPS,
+            Phase::SafetyCar => SAFETY_CAR_LAPS,
+            Phase::RedFlag => (1, 1),
+        };
+        rng.gen_range(min..=max)
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Phase::Green => write!(f, "Green flag"),
+            Phase::VirtualSafetyCar => write!(f, "Virtual safety car"),
+            Phase::SafetyCar => write!(f, "Safety car"),
+            Phase::RedFlag => write!(f, "Red flag"),
+        }
+    }
+}
+
+impl fmt::Display for PhasePeriod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.from_lap == self.to_lap {
+            write!(f, "{}: lap {}", self.phase, self.from_lap)
+        } else {
+            write!(f, "{}: laps {}-{}", self.phase, self.from_lap, self.to_lap)
+        }
+    }
+}
+
+/// The phase a retirement brings about at a circuit, or `Phase::Green` if the
+/// race carries on under yellow flags: a crash can stop the race or bring out
+/// either safety car, a car stopped on track only the virtual safety car
+pub fn draw_phase<R: Rng + ?Sized>(crash: bool, circuit: &Circuit, rng: &mut R) -> Phase {
+    let roll = rng.gen::<f64>();
+    if !crash {
+        return if roll < circuit.virtual_safety_car_chance { Phase::VirtualSafetyCar } else { Phase::Green };
+    }
+    
+    if roll < circuit.red_flag_chance {
+        Phase::RedFlag
+    } else if roll < circuit.red_flag_chance + circuit.safety_car_chance {
+        Phase::SafetyCar
+    } else if roll < circuit.red_flag_chance + circuit.safety_car_chance + circuit.virtual_safety_car_chance {
+        Phase::VirtualSafetyCar
+    } else {
+        Phase::Green
+    }
+}
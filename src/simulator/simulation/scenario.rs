@@ -0,0 +1,93 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use super::strategy::Strategy;
+use crate::weather::{RainChange, Weather};
+
+/// Choices for a simulated race that go beyond the simulation parameters
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scenario {
+    /// Strategies by driver code, id or name; everyone else follows their team's.
+    /// They are applied in key order, so of two keys naming the same driver the
+    /// last one wins.
+    pub strategies: BTreeMap<String, Strategy>,
+    /// Strategies given on the command line, applied in the order given after
+    /// and so over those of the file
+    pub overrides: Vec<(String, Strategy)>,
+    /// The weather to race in, unless given on the command line
+    pub weather: Option<Weather>,
+}
+
+// A scenario file, where a strategy is given in full or as on the command line,
+// and the weather as on the command line or as the laps the rain changes on
+#[derive(Deserialize)]
+struct ScenarioFile {
+    #[serde(default)]
+    strategies: BTreeMap<String, StrategyEntry>,
+    #[serde(default)]
+    weather: Option<WeatherEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StrategyEntry {
+    Spec(String),
+    Strategy(Strategy),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WeatherEntry {
+    Spec(String),
+    Script(Vec<RainChange>),
+}
+
+impl Scenario {
+    /// Parse a scenario, e.g. `{ "strategies": { "VER": "two-stop:undercut" }, "weather": "20:0.6,35:0" }`
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: ScenarioFile = serde_json::from_str(json)?;
+
+        let mut strategies = BTreeMap::new();
+        for (driver, entry) in file.strategies {
+            let strategy = match entry {
+                StrategyEntry::Spec(spec) => spec.parse()?,
+                StrategyEntry::Strategy(strategy) => {
+                    strategy.validate()?;
+                    strategy
+                },
+            };
+            strategies.insert(driver, strategy);
+        }
+
+        let weather = match file.weather {
+            Some(WeatherEntry::Spec(spec)) => Some(spec.parse()?),
+            Some(WeatherEntry::Script(changes)) => Some(Weather::Scripted(changes)),
+            None => None,
+        };
+
+        Ok(Scenario { strategies, overrides: Vec::new(), weather })
+    }
+
+    /// Read a scenario file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scenario {}", path.display()))?;
+        Self::from_json(&data)
+            .with_context(|| format!("Failed to parse scenario {}", path.display()))
+    }
+
+    /// Add strategies given as "DRIVER=STRATEGY", overriding those for the same driver
+    pub fn with_strategies(mut self, specs: &[String]) -> Result<Self> {
+        for spec in specs {
+            let (driver, strategy) = spec.split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Expected DRIVER=STRATEGY, got: {}", spec))?;
+            let strategy = strategy.parse()
+                .with_context(|| format!("Invalid strategy for {}", driver))?;
+            self.overrides.push((driver.trim().to_string(), strategy));
+        }
+        Ok(self)
+    }
+}
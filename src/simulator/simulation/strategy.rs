@@ -0,0 +1,234 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::{Circuit, Compound, RaceFormat, SimulationParameters};
+use super::tyres;
+
+/// Laps before its planned stop within which a car going for the undercut pits early
+const UNDERCUT_WINDOW_LAPS: u32 = 4;
+
+/// Gap, in seconds, within which a car attacks the car ahead or reacts to a rival's stop
+pub const ATTACK_GAP_SECS: f64 = 2.5;
+
+/// Laps beyond its planned stop a car going for the overcut stays out
+const OVERCUT_LAPS: u32 = 3;
+
+/// Laps before its planned stop within which a car pits under the safety car
+const SAFETY_CAR_WINDOW_LAPS: u32 = 12;
+
+/// Share of the combined life of a medium and a hard set beyond which a race
+/// is too long for a single stop
+const ONE_STOP_LIMIT: f64 = 0.9;
+
+/// How a car reacts to the cars around it when its stop is due
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Reaction {
+    /// Stick to the planned laps
+    #[default]
+    None,
+    /// Pit a few laps early when close behind the car ahead, to pass it on fresher tyres
+    Undercut,
+    /// Stay out a few laps longer when a rival close by pits, to gain on it in clean air
+    Overcut,
+}
+
+/// A pit stop strategy: the compounds to run and when to change them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Strategy {
+    /// Compound of every stint, in order; one stop fewer than stints
+    pub stints: Vec<Compound>,
+    /// Lap at the end of which each stop is made; planned from the life of the
+    /// tyres when empty
+    #[serde(default)]
+    pub pit_laps: Vec<u32>,
+    #[serde(default)]
+    pub reaction: Reaction,
+    /// Whether to make the next stop early when the safety car comes out
+    #[serde(default = "pit_under_safety_car")]
+    pub pit_under_safety_car: bool,
+}
+
+// Cars pit under the safety car unless told otherwise
+fn pit_under_safety_car() -> bool {
+    true
+}
+
+/// What a car knows when deciding whether to pit at the end of the coming lap
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PitWindow {
+    /// The lap about to be run
+    pub lap: u32,
+    /// Lap at the end of which the next stop is planned
+    pub planned_lap: u32,
+    /// Gap to the car ahead on the same lap, in seconds
+    pub gap_ahead: Option<f64>,
+    /// Whether a rival close by pitted in the last two laps
+    pub rival_pitted: bool,
+    pub safety_car: bool,
+}
+
+impl Strategy {
+    /// Run the whole race on mediums, as in a sprint
+    pub fn no_stop() -> Self {
+        Self::with_stints(vec![Compound::Medium])
+    }
+    
+    /// Start on mediums and finish on hards
+    pub fn one_stop() -> Self {
+        Self::with_stints(vec![Compound::Medium, Compound::Hard])
+    }
+    
+    /// Mediums, hards and a short final stint on softs
+    pub fn two_stop() -> Self {
+        Self::with_stints(vec![Compound::Medium, Compound::Hard, Compound::Soft])
+    }
+    
+    // A strategy running these compounds with stops planned from tyre life
+    fn with_stints(stints: Vec<Compound>) -> Self {
+        Strategy { stints, pit_laps: Vec::new(), reaction: Reaction::None, pit_under_safety_car: true }
+    }
+    
+    /// No stop in a sprint; in a grand prix a single stop if a medium and a
+    /// hard set last the distance, else two
+    pub fn for_race(format: RaceFormat, total_laps: u32, circuit: &Circuit, params: &SimulationParameters) -> Self {
+        if format == RaceFormat::Sprint {
+            return Self::no_stop();
+        }
+        
+        let one_stop_laps = tyres::tyre_life(Compound::Medium, circuit, params) + tyres::tyre_life(Compound::Hard, circuit, params);
+        if total_laps as f64 <= one_stop_laps as f64 * ONE_STOP_LIMIT {
+            Self::one_stop()
+        } else {
+            Self::two_stop()
+        }
+    }
+    
+    /// The compound to start on: the first of the strategy if it suits the
+    /// wetness of the track and there is a set of it, or else the usual one
+    pub fn starting_compound(&self, wetness: f64, params: &SimulationParameters) -> Compound {
+        self.stints.first().copied()
+            .filter(|&c| tyres::suits(c, wetness) && params.tyre_allocation.get(c) > 0)
+            .unwrap_or_else(|| tyres::starting_compound(wetness, params))
+    }
+    
+    /// Number of stops planned
+    pub fn stops(&self) -> usize {
+        self.stints.len().saturating_sub(1)
+    }
+    
+    /// Lap at the end of which each stop is made: the laps given, or else the
+    /// race split between the stints in proportion to the life of their tyres
+    pub fn planned_pit_laps(&self, total_laps: u32, circuit: &Circuit, params: &SimulationParameters) -> Vec<u32> {
+        if !self.pit_laps.is_empty() {
+            return self.pit_laps.clone();
+        }
+        
+        let lives: Vec<f64> = self.stints.iter().map(|&c| tyres::tyre_life(c, circuit, params) as f64).collect();
+        let total_life: f64 = lives.iter().sum();
+        
+        let mut laps = Vec::new();
+        let mut covered = 0.0;
+        for life in &lives[..self.stops()] {
+            covered += life;
+            let lap = (total_laps as f64 * covered / total_life).round() as u32;
+            let earliest = laps.last().map_or(1, |last| last + 1);
+            laps.push(lap.clamp(earliest, total_laps.saturating_sub(1).max(earliest)));
+        }
+        laps
+    }
+    
+    /// Whether to make the next stop at the end of the coming lap
+    pub fn pits(&self, window: &PitWindow) -> bool {
+        let laps_to_stop = window.planned_lap.saturating_sub(window.lap);
+        
+        if window.safety_car && self.pit_under_safety_car && laps_to_stop <= SAFETY_CAR_WINDOW_LAPS {
+            return true;
+        }
+        
+        match self.reaction {
+            Reaction::Undercut if window.gap_ahead.is_some_and(|gap| gap <= ATTACK_GAP_SECS) => {
+                laps_to_stop <= UNDERCUT_WINDOW_LAPS
+            },
+            Reaction::Overcut if window.rival_pitted => window.lap >= window.planned_lap + OVERCUT_LAPS,
+            _ => window.lap >= window.planned_lap,
+        }
+    }
+    
+    /// Check that the strategy can be run
+    pub fn validate(&self) -> Result<()> {
+        if self.stints.is_empty() {
+            anyhow::bail!("A strategy needs at least one stint");
+        }
+        if !self.pit_laps.is_empty() {
+            if self.pit_laps.len() != self.stops() {
+                anyhow::bail!("{} stints need {} pit laps, got {}", self.stints.len(), self.stops(), self.pit_laps.len());
+            }
+            if self.pit_laps.windows(2).any(|pair| pair[0] >= pair[1]) || self.pit_laps.contains(&0) {
+                anyhow::bail!("Pit laps must be in order and after the first lap");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Strategy {
+    fn default() -> Self {
+        Self::one_stop()
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = anyhow::Error;
+    
+    /// Parse "no-stop", "one-stop", "two-stop" or compounds like "medium/hard", with the
+    /// pit laps after an '@' ("soft/hard@18") and options after colons: a
+    /// reaction ("two-stop:undercut") or "no-sc" to stay out under the safety car
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split(':');
+        let plan = parts.next().unwrap_or_default();
+        let (plan, pit_laps) = match plan.split_once('@') {
+            Some((plan, laps)) => (plan, Some(laps)),
+            None => (plan, None),
+        };
+        
+        let mut strategy = match plan.trim().to_lowercase().as_str() {
+            "no-stop" | "0-stop" => Self::no_stop(),
+            "one-stop" | "1-stop" => Self::one_stop(),
+            "two-stop" | "2-stop" => Self::two_stop(),
+            compounds => Self::with_stints(compounds.split('/').map(str::parse).collect::<Result<_>>()?),
+        };
+        
+        if let Some(laps) = pit_laps {
+            strategy.pit_laps = laps.split('/')
+                .map(|lap| lap.trim().parse().map_err(|_| anyhow::anyhow!("Invalid pit lap: {}", lap)))
+                .collect::<Result<_>>()?;
+        }
+        
+        for option in parts {
+            match option.trim().to_lowercase().as_str() {
+                "undercut" => strategy.reaction = Reaction::Undercut,
+                "overcut" => strategy.reaction = Reaction::Overcut,
+                "no-sc" => strategy.pit_under_safety_car = false,
+                _ => anyhow::bail!("Unknown strategy option: {}", option),
+            }
+        }
+        
+        strategy.validate()?;
+        Ok(strategy)
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let stints: Vec<String> = self.stints.iter().map(|c| c.letter().to_string()).collect();
+        write!(f, "{}", stints.join("-"))?;
+        match self.reaction {
+            Reaction::None => Ok(()),
+            Reaction::Undercut => write!(f, " (undercut)"),
+            Reaction::Overcut => write!(f, " (overcut)"),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::models::{Circuit, Compound, PerCompound, SimulationParameters};
+
+/// Track wetness at which the pace of the compounds is given
+const PACE_WETNESS: [f64; 4] = [0.0, 0.3, 0.7, 1.0];
+
+/// Lap time of each compound relative to a new medium on a dry track, in
+/// seconds, at each wetness of `PACE_WETNESS`: slicks lose grip fast once the
+/// track is damp, intermediates are quickest until the water stands and full
+/// wets only then
+const PACE_BY_WETNESS: PerCompound<[f64; 4]> = PerCompound {
+    soft: [-0.6, 5.0, 14.0, 20.0],
+    medium: [0.0, 5.3, 13.5, 19.5],
+    hard: [0.5, 5.6, 13.5, 19.5],
+    intermediate: [4.5, 5.0, 6.5, 11.0],
+    wet: [8.0, 7.5, 6.5, 6.0],
+};
+
+/// Lap time lost to wear with every lap on a set, at a circuit of average severity
+const DEGRADATION_SECS_PER_LAP: PerCompound<f64> = PerCompound { soft: 0.08, medium: 0.05, hard: 0.03, intermediate: 0.06, wet: 0.05 };
+
+/// Laps a set lasts before it falls off the cliff, at a circuit of average severity
+const TYRE_LIFE_LAPS: PerCompound<f64> = PerCompound { soft: 20.0, medium: 32.0, hard: 45.0, intermediate: 35.0, wet: 40.0 };
+
+/// Lap time lost on the first lap past the cliff, and with every lap after it
+const CLIFF_SECS: f64 = 1.5;
+const CLIFF_SECS_PER_LAP: f64 = 0.5;
+
+/// Least severity of wear, so that a circuit without a tyre wear rating still wears tyres
+const MIN_WEAR_SEVERITY: f64 = 0.1;
+
+/// Laps left at which a car stays out on worn tyres rather than change them
+const LAPS_NOT_WORTH_A_CHANGE: u32 = 3;
+
+// Lap time of a new set of a compound relative to a new medium on a dry track,
+// in seconds, between the points of the pace table
+fn pace(compound: Compound, wetness: f64) -> f64 {
+    let table = PACE_BY_WETNESS.get(compound);
+    let wetness = wetness.clamp(0.0, 1.0);
+    let i = PACE_WETNESS[1..].iter().position(|&w| wetness <= w).unwrap_or(PACE_WETNESS.len() - 2);
+    let share = (wetness - PACE_WETNESS[i]) / (PACE_WETNESS[i + 1] - PACE_WETNESS[i]);
+    
+    table[i] + (table[i + 1] - table[i]) * share
+}
+
+/// The quickest compound on a track of a wetness
+pub fn fastest_compound(wetness: f64) -> Compound {
+    Compound::ALL.into_iter()
+        .min_by(|&a, &b| pace(a, wetness).total_cmp(&pace(b, wetness)))
+        .unwrap_or(Compound::Medium)
+}
+
+/// Whether the track is wet enough for intermediates or wets to be faster than slicks
+pub fn is_wet_track(wetness: f64) -> bool {
+    !fastest_compound(wetness).is_dry()
+}
+
+/// Whether a compound is the kind of tyre for a track of a wetness: any slick
+/// on a dry track, and else the quicker of intermediates and wets
+pub fn suits(compound: Compound, wetness: f64) -> bool {
+    let fastest = fastest_compound(wetness);
+    compound == fastest || (compound.is_dry() && fastest.is_dry())
+}
+
+// How fast a compound wears at a circuit, relative to an average track
+fn wear_severity(compound: Compound, circuit: &Circuit, params: &SimulationParameters) -> f64 {
+    (circuit.tyre_wear * params.degradation.get(compound)).max(MIN_WEAR_SEVERITY)
+}
+
+/// Laps a set of a compound lasts at a circuit before it falls off the cliff
+pub fn tyre_life(compound: Compound, circuit: &Circuit, params: &SimulationParameters) -> u32 {
+    (TYRE_LIFE_LAPS.get(compound) / wear_severity(compound, circuit, params)).round().max(1.0) as u32
+}
+
+/// Time a set of a compound that has done `age` laps adds to a lap on a track
+/// of a wetness compared to a new medium in the dry, in seconds
+pub fn lap_time_offset(compound: Compound, age: u32, wetness: f64, circuit: &Circuit, params: &SimulationParameters) -> f64 {
+    let pace = pace(compound, wetness);
+    
+    let wear = DEGRADATION_SECS_PER_LAP.get(compound) * wear_severity(compound, circuit, params) * age as f64;
+    
+    let life = tyre_life(compound, circuit, params);
+    let cliff = if age >= life {
+        CLIFF_SECS + CLIFF_SECS_PER_LAP * (age - life) as f64
+    } else {
+        0.0
+    };
+    
+    pace + wear + cliff
+}
+
+/// Whether a car that used these compounds met the rule that a dry race must be
+/// run on at least two dry compounds; using intermediates or wets lifts the rule
+pub fn meets_compound_rule(used: &[Compound]) -> bool {
+    let first_dry = used.iter().find(|c| c.is_dry());
+    
+    used.iter().any(|c| !c.is_dry() || Some(c) != first_dry)
+}
+
+/// The compound a car starts on: mediums in the dry and the quicker of
+/// intermediates and wets in the rain, or else the quickest compound with a set
+/// in the allocation
+pub fn starting_compound(wetness: f64, params: &SimulationParameters) -> Compound {
+    let compounds = [Compound::Medium, Compound::Soft, Compound::Hard, Compound::Intermediate, Compound::Wet];
+    let (preference, mut others): (Vec<Compound>, Vec<Compound>) = compounds.into_iter().partition(|&c| suits(c, wetness));
+    others.sort_by(|&a, &b| pace(a, wetness).total_cmp(&pace(b, wetness)));
+    
+    preference.into_iter()
+        .chain(others)
+        .find(|&c| params.tyre_allocation.get(c) > 0)
+        .unwrap_or(Compound::Medium)
+}
+
+/// The compound to change to, from the sets left: the kind of tyre for the
+/// wetness of the track, one not run yet if the compound rule still has to be met, and the
+/// softest that lasts the laps left, or else the hardest
+pub fn next_compound(
+    used: &[Compound],
+    sets_left: impl Fn(Compound) -> u32,
+    laps_left: u32,
+    compound_rule: bool,
+    wetness: f64,
+    circuit: &Circuit,
+    params: &SimulationParameters,
+) -> Option<Compound> {
+    let needs_new_compound = compound_rule && !is_wet_track(wetness) && !meets_compound_rule(used);
+    
+    let candidates: Vec<Compound> = Compound::ALL.into_iter()
+        .filter(|&c| suits(c, wetness) && sets_left(c) > 0)
+        .filter(|c| !needs_new_compound || !used.contains(c))
+        .collect();
+    
+    candidates.iter().copied()
+        .find(|&c| tyre_life(c, circuit, params) >= laps_left)
+        .or_else(|| candidates.last().copied())
+}
+
+/// Whether a car is better off staying out on worn tyres for the laps left
+pub fn too_late_to_change(laps_left: u32) -> bool {
+    laps_left <= LAPS_NOT_WORTH_A_CHANGE
+}
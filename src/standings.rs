@@ -0,0 +1,183 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::models::{is_classified, RaceFormat, RaceResult, SprintResult, WeekendResults};
+use crate::points::{distance_completed, PointsSystem};
+
+/// A driver's or constructor's place in a championship
+#[derive(Debug, Clone, PartialEq)]
+pub struct StandingsEntry {
+    pub position: u32,
+    /// Driver name, or constructor name in the constructors' table
+    pub name: String,
+    /// Team the driver last raced for; empty in the constructors' table
+    pub team: String,
+    pub points: f64,
+    /// Grand prix finishes by position: `finishes[0]` is wins, `finishes[1]` second places, ...
+    pub finishes: Vec<u32>,
+}
+
+impl StandingsEntry {
+    pub fn wins(&self) -> u32 {
+        self.finishes.first().copied().unwrap_or(0)
+    }
+    
+    pub fn podiums(&self) -> u32 {
+        self.finishes.iter().take(3).sum()
+    }
+    
+    /// Record a grand prix finishing position for the countback
+    fn add_finish(&mut self, position: u32) {
+        let Some(idx) = position.checked_sub(1).map(|p| p as usize) else {
+            return;
+        };
+        if self.finishes.len() <= idx {
+            self.finishes.resize(idx + 1, 0);
+        }
+        self.finishes[idx] += 1;
+    }
+}
+
+/// Drivers' and constructors' championship tables after a round
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standings {
+    /// Last round counted
+    pub round: u32,
+    pub drivers: Vec<StandingsEntry>,
+    pub constructors: Vec<StandingsEntry>,
+}
+
+impl Standings {
+    /// Aggregate the points of every weekend up to and including `round`
+    /// (default: every weekend given).
+    ///
+    /// Grand prix and sprint points both count. Ties on points are broken by
+    /// countback: most wins, then most second places, and so on. Sprint
+    /// finishes do not count towards the countback.
+    ///
+    /// Points come from the results themselves unless a points system is
+    /// given, in which case every classified finish is scored again with it,
+    /// under its short race rule if the winner ran fewer laps than the
+    /// circuit's scheduled distance.
+    pub fn after_round(weekends: &[WeekendResults], round: Option<u32>, points_system: Option<&PointsSystem>) -> Self {
+        let mut drivers: HashMap<String, StandingsEntry> = HashMap::new();
+        let mut constructors: HashMap<String, StandingsEntry> = HashMap::new();
+        let mut last_round = 0;
+        
+        let mut counted: Vec<&WeekendResults> = weekends.iter()
+            .filter(|w| round.is_none_or(|r| w.race.round <= r))
+            .collect();
+        counted.sort_by_key(|w| w.race.round);
+        
+        for weekend in counted {
+            last_round = weekend.race.round;
+            
+            let winner_laps = weekend.race.results.iter().map(|r| r.laps).max().unwrap_or(0);
+            let distance = distance_completed(winner_laps, weekend.race.circuit.laps);
+            
+            for result in &weekend.race.results {
+                let points = match points_system {
+                    Some(system) => race_points(system, result, &weekend.race.results, winner_laps, distance),
+                    None => result.points,
+                };
+                
+                let driver = entry(&mut drivers, &result.driver.id, &result.driver.name);
+                driver.team = result.driver.team.clone();
+                driver.points += points;
+                driver.add_finish(result.position);
+                
+                let constructor = entry(&mut constructors, &result.driver.team, &result.driver.team);
+                constructor.points += points;
+                constructor.add_finish(result.position);
+            }
+            
+            let sprint_laps = weekend.sprint.iter().map(|r| r.laps).max().unwrap_or(0);
+            
+            for result in &weekend.sprint {
+                let points = match points_system {
+                    Some(system) => sprint_points(system, result, sprint_laps),
+                    None => result.points,
+                };
+                entry(&mut drivers, &result.driver.id, &result.driver.name).points += points;
+                entry(&mut constructors, &result.driver.team, &result.driver.team).points += points;
+            }
+        }
+        
+        Standings {
+            round: last_round,
+            drivers: rank(drivers.into_values().collect()),
+            constructors: rank(constructors.into_values().collect()),
+        }
+    }
+    
+    /// Look up a driver's entry by name
+    pub fn driver(&self, name: &str) -> Option<&StandingsEntry> {
+        self.drivers.iter().find(|e| e.name == name)
+    }
+    
+    /// Look up a constructor's entry by name
+    pub fn constructor(&self, name: &str) -> Option<&StandingsEntry> {
+        self.constructors.iter().find(|e| e.name == name)
+    }
+}
+
+// Grand prix points for a result under a points system, in a race run over
+// `distance` of its scheduled laps. Drivers sharing a car are listed with the
+// same position and split its points.
+fn race_points(system: &PointsSystem, result: &RaceResult, results: &[RaceResult], winner_laps: u32, distance: f64) -> f64 {
+    if !is_classified(&result.status, result.laps, winner_laps) {
+        return 0.0;
+    }
+    
+    let fastest_lap = result.fastest_lap.as_ref().is_some_and(|fl| fl.rank == 1);
+    let points = system.score(RaceFormat::GrandPrix, result.position, fastest_lap, distance);
+    let drivers = results.iter().filter(|r| r.position == result.position).count();
+    system.shared_drive(points, drivers)
+}
+
+// Sprint points for a result under a points system
+fn sprint_points(system: &PointsSystem, result: &SprintResult, winner_laps: u32) -> f64 {
+    if !is_classified(&result.status, result.laps, winner_laps) {
+        return 0.0;
+    }
+    system.score(RaceFormat::Sprint, result.position, false, 1.0)
+}
+
+// Get or create the table entry keyed by `key`
+fn entry<'a>(table: &'a mut HashMap<String, StandingsEntry>, key: &str, name: &str) -> &'a mut StandingsEntry {
+    table.entry(key.to_string()).or_insert_with(|| StandingsEntry {
+        position: 0,
+        name: name.to_string(),
+        team: String::new(),
+        points: 0.0,
+        finishes: Vec::new(),
+    })
+}
+
+/// Compare two entries by countback, best first
+pub fn countback(a: &StandingsEntry, b: &StandingsEntry) -> Ordering {
+    let len = a.finishes.len().max(b.finishes.len());
+    (0..len)
+        .map(|i| {
+            let a_count = a.finishes.get(i).copied().unwrap_or(0);
+            let b_count = b.finishes.get(i).copied().unwrap_or(0);
+            b_count.cmp(&a_count)
+        })
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+// Sort entries by points and countback and number the positions
+fn rank(mut entries: Vec<StandingsEntry>) -> Vec<StandingsEntry> {
+    entries.sort_by(|a, b| {
+        b.points.partial_cmp(&a.points).unwrap_or(Ordering::Equal)
+            .then_with(|| countback(a, b))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    
+    for (i, entry) in entries.iter_mut().enumerate() {
+        entry.position = (i + 1) as u32;
+    }
+    
+    entries
+}
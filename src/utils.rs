@@ -1,7 +1,9 @@
 use colored::*;
 use std::time::Duration;
-use rand::Rng;
-use crate::models::{Driver, RaceResult, QualifyingResult};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use crate::models::{Driver, RaceResult, QualifyingResult, SprintResult};
+use crate::standings::StandingsEntry;
 
 /// Convert a lap time string (e.g. "1:30.123") to Duration
 #[allow(dead_code)]
@@ -55,10 +57,29 @@ pub fn format_duration_as_lap_time(duration: Duration) -> String {
     }
 }
 
+/// Convert a race time to "h:mm:ss.mmm", as the results feed gives winners' times
+pub fn format_race_time(duration: Duration) -> String {
+    let total_millis = duration.as_millis();
+    let hours = total_millis / (60 * 60 * 1000);
+    let minutes = (total_millis / (60 * 1000)) % 60;
+    let seconds = (total_millis / 1000) % 60;
+    let millis = total_millis % 1000;
+    
+    format!("{}:{:02}:{:02}.{:03}", hours, minutes, seconds, millis)
+}
+
+/// The random number generator every simulation draws from: seeded so that a
+/// run can be repeated exactly, or else from the operating system
+pub fn seeded_rng(seed: Option<u64>) -> ChaCha8Rng {
+    match seed {
+        Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+        None => ChaCha8Rng::from_entropy(),
+    }
+}
+
 /// Add random variation to a lap time
 #[allow(dead_code)]
-pub fn add_time_variation(base_time: Duration, variation_percent: f64) -> Duration {
-    let mut rng = rand::thread_rng();
+pub fn add_time_variation<R: Rng + ?Sized>(base_time: Duration, variation_percent: f64, rng: &mut R) -> Duration {
     let variation_factor = 1.0 + (rng.gen::<f64>() * 2.0 - 1.0) * variation_percent;
     let millis = (base_time.as_millis() as f64 * variation_factor) as u64;
     Duration::from_millis(millis)
@@ -68,15 +89,16 @@ pub fn add_time_variation(base_time: Duration, variation_percent: f64) -> Durati
 pub fn format_race_results(results: &[RaceResult]) -> String {
     let mut output = String::new();
     
-    output.push_str(&format!("{:<3} {:<20} {:<15} {:<10} {}\n", 
+    output.push_str(&format!("{:<3} {:<20} {:<15} {:<5} {:<12} {}\n", 
         "Pos".bold(), 
         "Driver".bold(), 
         "Team".bold(), 
+        "Grid".bold(),
         "Time".bold(),
         "Points".bold()
     ));
     
-    output.push_str(&format!("{}\n", "-".repeat(60)));
+    output.push_str(&format!("{}\n", "-".repeat(65)));
     
     for result in results {
         let position = format!("{}", result.position);
@@ -106,12 +128,13 @@ pub fn format_race_results(results: &[RaceResult]) -> String {
             _ => result.driver.team.normal(),
         };
         
-        output.push_str(&format!("{:<3} {:<20} {:<15} {:<10} {}\n",
+        output.push_str(&format!("{:<3} {:<20} {:<15} {:<5} {:<12} {}\n",
             position_colored,
             result.driver.name,
             colored_team,
+            format_grid(result.grid),
             time_str,
-            result.points
+            format_points(result.points)
         ));
     }
     
@@ -169,6 +192,135 @@ pub fn format_qualifying_results(results: &[QualifyingResult]) -> String {
     output
 }
 
+/// Format sprint results in a nice table for terminal output
+pub fn format_sprint_results(results: &[SprintResult]) -> String {
+    let mut output = String::new();
+    
+    output.push_str(&format!("{:<3} {:<20} {:<15} {:<5} {:<12} {}\n", 
+        "Pos".bold(), 
+        "Driver".bold(), 
+        "Team".bold(), 
+        "Grid".bold(),
+        "Time".bold(),
+        "Points".bold()
+    ));
+    
+    output.push_str(&format!("{}\n", "-".repeat(65)));
+    
+    for result in results {
+        let time_str = match &result.time {
+            Some(time) => time.to_string(),
+            None => result.status.clone(),
+        };
+        
+        output.push_str(&format!("{:<3} {:<20} {:<15} {:<5} {:<12} {}\n",
+            colored_position(result.position),
+            result.driver.name,
+            colored_team(&result.driver.team),
+            format_grid(result.grid),
+            time_str,
+            format_points(result.points)
+        ));
+    }
+    
+    output
+}
+
+/// Format the sprint grid set by sprint qualifying, in grid order
+pub fn format_sprint_grid(results: &[SprintResult]) -> String {
+    let mut grid: Vec<&SprintResult> = results.iter().collect();
+    // Pit lane starters (grid 0) line up behind everyone else
+    grid.sort_by_key(|r| if r.grid == 0 { u32::MAX } else { r.grid });
+    
+    let mut output = String::new();
+    
+    output.push_str(&format!("{:<5} {:<20} {:<15} {}\n", 
+        "Grid".bold(), 
+        "Driver".bold(), 
+        "Team".bold(), 
+        "Sprint result".bold()
+    ));
+    
+    output.push_str(&format!("{}\n", "-".repeat(55)));
+    
+    for result in grid {
+        output.push_str(&format!("{:<5} {:<20} {:<15} P{}\n",
+            format_grid(result.grid),
+            result.driver.name,
+            colored_team(&result.driver.team),
+            result.position
+        ));
+    }
+    
+    output
+}
+
+/// Format a drivers' or constructors' championship table for terminal output
+pub fn format_standings(entries: &[StandingsEntry], constructors: bool) -> String {
+    let mut output = String::new();
+    
+    let name_header = if constructors { "Constructor" } else { "Driver" };
+    output.push_str(&format!("{:<3} {:<25} {:<15} {:<8} {:<5} {}\n", 
+        "Pos".bold(), 
+        name_header.bold(), 
+        if constructors { "" } else { "Team" }.bold(), 
+        "Points".bold(),
+        "Wins".bold(),
+        "Podiums".bold()
+    ));
+    
+    output.push_str(&format!("{}\n", "-".repeat(70)));
+    
+    for entry in entries {
+        let name = if constructors {
+            colored_team(&entry.name)
+        } else {
+            entry.name.normal()
+        };
+        
+        output.push_str(&format!("{:<3} {:<25} {:<15} {:<8} {:<5} {}\n",
+            colored_position(entry.position),
+            name,
+            colored_team(&entry.team),
+            format_points(entry.points),
+            entry.wins(),
+            entry.podiums()
+        ));
+    }
+    
+    output
+}
+
+/// Points without a decimal unless there are half points or shared drives
+pub fn format_points(points: f64) -> String {
+    if points.fract() == 0.0 {
+        format!("{}", points as i64)
+    } else {
+        format!("{:.2}", points).trim_end_matches('0').to_string()
+    }
+}
+
+/// A starting position, with "PL" for a pit lane start
+fn format_grid(grid: u32) -> String {
+    if grid == 0 { "PL".to_string() } else { grid.to_string() }
+}
+
+/// A finishing position colored for the podium
+fn colored_position(position: u32) -> ColoredString {
+    let position_str = position.to_string();
+    match position {
+        1 => position_str.bright_yellow(),
+        2 => position_str.bright_white(),
+        3 => position_str.yellow(),
+        _ => position_str.normal(),
+    }
+}
+
+/// A team name in its team color
+fn colored_team(team: &str) -> ColoredString {
+    team.color(get_team_color(team))
+}
+
 /// Helper function to get color for F1 team
 fn get_team_color(team: &str) -> Color {
     match team.to_lowercase().as_str() {
@@ -185,10 +337,8 @@ fn get_team_color(team: &str) -> Color {
     }
 }
 
-/// Generate a random mechanical failure based on driver reliability
-pub fn simulate_mechanical_failure(driver: &Driver, reliability_factor: f64) -> bool {
-    let mut rng = rand::thread_rng();
-    
+/// Chance of a car suffering a mechanical failure over a race
+pub fn mechanical_failure_chance(driver: &Driver, reliability_factor: f64) -> f64 {
     // Base reliability varies by team (simplified model)
     let base_reliability = match driver.team.to_lowercase().as_str() {
         team if team.contains("mercedes") => 0.95,
@@ -204,14 +354,17 @@ pub fn simulate_mechanical_failure(driver: &Driver, reliability_factor: f64) ->
     };
     
     // Adjust with reliability factor
-    let failure_chance = (1.0 - base_reliability) * (1.0 / reliability_factor);
-    
+    (1.0 - base_reliability) * (1.0 / reliability_factor)
+}
+
+/// Generate a random mechanical failure based on driver reliability
+pub fn simulate_mechanical_failure<R: Rng + ?Sized>(driver: &Driver, reliability_factor: f64, rng: &mut R) -> bool {
     // Simulate failure
-    rng.gen::<f64>() < failure_chance
+    rng.gen::<f64>() < mechanical_failure_chance(driver, reliability_factor)
 }
 
 /// Get random racing incident description
-pub fn get_random_incident() -> &'static str {
+pub fn get_random_incident<R: Rng + ?Sized>(rng: &mut R) -> &'static str {
     let incidents = [
         "Lost control in the corner",
         "Collision with another driver",
@@ -227,7 +380,6 @@ pub fn get_random_incident() -> &'static str {
         "Cooling system issue",
     ];
     
-    let mut rng = rand::thread_rng();
     let index = rng.gen_range(0..incidents.len());
     incidents[index]
 }
@@ -0,0 +1,229 @@
+use anyhow::Result;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::Circuit;
+
+/// Laps ahead over which the teams see rain coming
+const FORECAST_LAPS: u32 = 5;
+
+/// Share of the difference to the wetness the rain brings that the track makes
+/// up with every lap of rain
+const WETTING_RATE: f64 = 0.5;
+
+/// Wetness a dry line takes off the track with every lap the rain eases
+const DRYING_PER_LAP: f64 = 0.04;
+
+/// Laps a shower lasts and how hard it rains, at least and at most
+const SHOWER_LAPS: (u32, u32) = (4, 25);
+const SHOWER_INTENSITY: (f64, f64) = (0.2, 1.0);
+
+/// How hard it rains in a race run in the wet from start to finish
+const WET_RACE_INTENSITY: f64 = 0.6;
+
+/// The weather on track during a lap
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Conditions {
+    /// Chance of rain within the next few laps, as the teams forecast it
+    pub rain_probability: f64,
+    /// How hard it rains, from 0 (dry) to 1 (a downpour)
+    pub rain_intensity: f64,
+    /// Water on the track, from 0 (dry) to 1 (standing water)
+    pub track_wetness: f64,
+}
+
+impl Conditions {
+    pub fn is_raining(&self) -> bool {
+        self.rain_intensity > 0.0
+    }
+}
+
+impl fmt::Display for Conditions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sky = match self.rain_intensity {
+            i if i <= 0.0 => "No rain",
+            i if i < 0.35 => "Light rain",
+            i if i < 0.7 => "Rain",
+            _ => "Heavy rain",
+        };
+        let track = match self.track_wetness {
+            w if w < 0.05 => "dry",
+            w if w < 0.3 => "damp",
+            w if w < 0.7 => "wet",
+            _ => "soaked",
+        };
+        write!(f, "{}, {} track", sky, track)
+    }
+}
+
+/// Rain of an intensity from a lap on, 0 for no rain
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RainChange {
+    pub lap: u32,
+    #[serde(alias = "rain")]
+    pub intensity: f64,
+}
+
+/// The weather to race in
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum Weather {
+    #[default]
+    Dry,
+    /// Rain from start to finish
+    Wet,
+    /// A shower with the circuit's chance of rain, drawn from the seed if one
+    /// is given and else from the race's own randomness
+    Random { seed: Option<u64> },
+    /// Rain changing on the laps given
+    Scripted(Vec<RainChange>),
+}
+
+impl Weather {
+    /// The conditions on every lap of a race at a circuit
+    pub fn timeline<R: Rng + ?Sized>(&self, laps: u32, circuit: &Circuit, rng: &mut R) -> WeatherTimeline {
+        let changes = match self {
+            Weather::Dry => Vec::new(),
+            Weather::Wet => vec![RainChange { lap: 1, intensity: WET_RACE_INTENSITY }],
+            Weather::Random { seed: Some(seed) } => draw_shower(laps, circuit, &mut ChaCha8Rng::seed_from_u64(*seed)),
+            Weather::Random { seed: None } => draw_shower(laps, circuit, rng),
+            Weather::Scripted(changes) => changes.clone(),
+        };
+        WeatherTimeline::from_changes(&changes, laps)
+    }
+}
+
+// A shower on a random lap, with the circuit's chance of rain
+fn draw_shower<R: Rng + ?Sized>(laps: u32, circuit: &Circuit, rng: &mut R) -> Vec<RainChange> {
+    if laps == 0 || rng.gen::<f64>() >= circuit.weather_risk {
+        return Vec::new();
+    }
+    
+    let start = rng.gen_range(1..=laps);
+    let length = rng.gen_range(SHOWER_LAPS.0..=SHOWER_LAPS.1);
+    let intensity = rng.gen_range(SHOWER_INTENSITY.0..=SHOWER_INTENSITY.1);
+    vec![
+        RainChange { lap: start, intensity },
+        RainChange { lap: start + length, intensity: 0.0 },
+    ]
+}
+
+impl FromStr for Weather {
+    type Err = anyhow::Error;
+    
+    /// Parse "dry", "wet", "random" with an optional seed ("random:42"), or
+    /// the rain intensity from given laps on, e.g. "20:0.6,35:0"
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "dry" => return Ok(Weather::Dry),
+            "wet" => return Ok(Weather::Wet),
+            "random" => return Ok(Weather::Random { seed: None }),
+            _ => {},
+        }
+        if let Some(seed) = s.strip_prefix("random:") {
+            let seed = seed.parse().map_err(|_| anyhow::anyhow!("Invalid weather seed: {}", seed))?;
+            return Ok(Weather::Random { seed: Some(seed) });
+        }
+        
+        let changes = s.split(',')
+            .map(|change| {
+                let (lap, intensity) = change.split_once(':')
+                    .ok_or_else(|| anyhow::anyhow!("Expected LAP:INTENSITY, got: {}", change))?;
+                let lap = lap.trim().parse().map_err(|_| anyhow::anyhow!("Invalid lap: {}", lap))?;
+                let intensity: f64 = intensity.trim().parse().map_err(|_| anyhow::anyhow!("Invalid rain intensity: {}", intensity))?;
+                if !(0.0..=1.0).contains(&intensity) {
+                    anyhow::bail!("Rain intensity must be between 0 and 1, got: {}", intensity);
+                }
+                Ok(RainChange { lap, intensity })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Weather::Scripted(changes))
+    }
+}
+
+impl fmt::Display for Weather {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Weather::Dry => write!(f, "dry"),
+            Weather::Wet => write!(f, "wet"),
+            Weather::Random { seed: None } => write!(f, "random"),
+            Weather::Random { seed: Some(seed) } => write!(f, "random (seed {})", seed),
+            Weather::Scripted(changes) => {
+                let changes: Vec<String> = changes.iter()
+                    .map(|change| match change.intensity {
+                        i if i > 0.0 => format!("rain {:.1} from lap {}", i, change.lap),
+                        _ => format!("dry from lap {}", change.lap),
+                    })
+                    .collect();
+                write!(f, "{}", changes.join(", "))
+            },
+        }
+    }
+}
+
+/// The conditions on every lap of a race
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WeatherTimeline {
+    laps: Vec<Conditions>,
+}
+
+impl WeatherTimeline {
+    /// A race that stays dry
+    pub fn dry(laps: u32) -> Self {
+        Self::from_changes(&[], laps)
+    }
+    
+    /// The conditions of a race in which the rain changes on the laps given. The
+    /// track gets wetter while it rains harder than the track is wet and dries
+    /// a little with every lap the rain eases; a race that starts in the rain
+    /// starts on a track as wet as the rain makes it.
+    pub fn from_changes(changes: &[RainChange], laps: u32) -> Self {
+        let mut changes = changes.to_vec();
+        changes.sort_by_key(|change| change.lap);
+        let rain: Vec<f64> = (1..=laps)
+            .map(|lap| changes.iter().rev().find(|c| c.lap <= lap).map_or(0.0, |c| c.intensity.clamp(0.0, 1.0)))
+            .collect();
+        
+        let mut wetness = rain.first().copied().unwrap_or(0.0);
+        let laps = rain.iter().enumerate().map(|(i, &intensity)| {
+            if intensity > wetness {
+                wetness += (intensity - wetness) * WETTING_RATE;
+            } else {
+                wetness = (wetness - DRYING_PER_LAP).max(intensity);
+            }
+            
+            // Rain is certain while it falls, and likelier the sooner it comes
+            let rain_probability = (0..=FORECAST_LAPS)
+                .find(|&ahead| rain.get(i + ahead as usize).is_some_and(|&r| r > 0.0))
+                .map_or(0.0, |ahead| 1.0 - ahead as f64 / (FORECAST_LAPS + 1) as f64);
+            
+            Conditions { rain_probability, rain_intensity: intensity, track_wetness: wetness }
+        }).collect();
+        
+        WeatherTimeline { laps }
+    }
+    
+    /// The conditions on a lap, counting from 1
+    pub fn conditions(&self, lap: u32) -> Conditions {
+        self.laps.get(lap.max(1) as usize - 1)
+            .or(self.laps.last())
+            .copied()
+            .unwrap_or_default()
+    }
+    
+    /// First and last lap of every spell of rain
+    pub fn rain_spells(&self) -> Vec<(u32, u32)> {
+        let mut spells: Vec<(u32, u32)> = Vec::new();
+        for (i, _) in self.laps.iter().enumerate().filter(|(_, c)| c.is_raining()) {
+            let lap = i as u32 + 1;
+            match spells.last_mut() {
+                Some(spell) if spell.1 + 1 == lap => spell.1 = lap,
+                _ => spells.push((lap, lap)),
+            }
+        }
+        spells
+    }
+}
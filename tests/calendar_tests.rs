@@ -0,0 +1,154 @@
+//! Tests for working out the current season and the next race from the calendar
+
+use chrono::NaiveDate;
+use f1_cli_simulator::data::index::{save_index, SeasonIndex};
+use f1_cli_simulator::data::{default_standings_season, determine_seasons_to_fetch, next_race, DataConfig};
+use serde_json::{json, Value};
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn schedule_race(season: u32, round: u32, circuit_id: &str, name: &str, date: &str) -> Value {
+    json!({
+        "season": season.to_string(),
+        "round": round.to_string(),
+        "raceName": name,
+        "date": date,
+        "Circuit": { "circuitId": circuit_id, "Location": { "locality": "", "country": "" } },
+    })
+}
+
+// An offline config, so lookups must be served from the cache
+fn cached_config(dir: &std::path::Path, current_season: u32) -> DataConfig {
+    let mut config = DataConfig::new(dir, "http://127.0.0.1:9");
+    config.current_season = Some(current_season);
+    config.offline = true;
+    config
+}
+
+#[test]
+fn test_default_seasons_follow_the_current_season() {
+    assert_eq!(determine_seasons_to_fetch(None, None, false, 2026), vec![2024, 2025, 2026]);
+}
+
+#[test]
+fn test_default_seasons_do_not_underflow() {
+    assert_eq!(determine_seasons_to_fetch(None, None, false, 1951), vec![1950, 1951]);
+    assert!(determine_seasons_to_fetch(None, None, false, 1).is_empty());
+    assert_eq!(determine_seasons_to_fetch(Some(10), None, false, 1952), vec![1950, 1951, 1952]);
+}
+
+#[test]
+fn test_current_season_override() {
+    let mut config = DataConfig::new("/tmp/f1", "http://localhost");
+    config.current_season = Some(2021);
+    
+    assert_eq!(config.current_season(), 2021);
+}
+
+#[test]
+fn test_current_season_moves_on_after_the_cached_calendar() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = DataConfig::new(dir.path(), "http://127.0.0.1:9");
+    assert_eq!(config.current_season_at(date("2024-12-20")), 2024);
+    
+    save_index(dir.path(), &SeasonIndex::from_races(2024, &[
+        schedule_race(2024, 24, "yas_marina", "Abu Dhabi Grand Prix", "2024-12-08"),
+    ])).unwrap();
+    assert_eq!(config.current_season_at(date("2024-12-08")), 2024);
+    assert_eq!(config.current_season_at(date("2024-12-09")), 2025);
+    
+    // An override always wins
+    let mut pinned = config.clone();
+    pinned.current_season = Some(2024);
+    assert_eq!(pinned.current_season_at(date("2024-12-20")), 2024);
+}
+
+#[test]
+fn test_standings_default_to_the_last_season_with_results() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = cached_config(dir.path(), 2025);
+    save_index(dir.path(), &SeasonIndex::from_races(2025, &[
+        schedule_race(2025, 1, "albert_park", "Australian Grand Prix", "2025-03-16"),
+    ])).unwrap();
+    assert_eq!(default_standings_season(&config).unwrap(), 2024);
+    
+    std::fs::write(dir.path().join("race_2025_r1.json"), "[]").unwrap();
+    assert_eq!(default_standings_season(&config).unwrap(), 2025);
+}
+
+#[test]
+fn test_current_season_from_environment() {
+    let env = |key: &str| (key == "F1_CURRENT_SEASON").then(|| "2019".to_string());
+    let config = DataConfig::from_env(&env, None).unwrap();
+    assert_eq!(config.current_season, Some(2019));
+    
+    let env = |key: &str| (key == "F1_CURRENT_SEASON").then(|| "next year".to_string());
+    assert!(DataConfig::from_env(&env, None).is_err());
+}
+
+#[test]
+fn test_next_round_in_index() {
+    let index = SeasonIndex::from_races(2024, &[
+        schedule_race(2024, 1, "bahrain", "Bahrain Grand Prix", "2024-03-02"),
+        schedule_race(2024, 2, "jeddah", "Saudi Arabian Grand Prix", "2024-03-09"),
+    ]);
+    
+    assert_eq!(index.next_round(date("2024-01-15")).unwrap().round, 1);
+    assert_eq!(index.next_round(date("2024-03-02")).unwrap().round, 1);
+    assert_eq!(index.next_round(date("2024-03-03")).unwrap().round, 2);
+    assert!(index.next_round(date("2024-03-10")).is_none());
+}
+
+#[test]
+fn test_next_race_uses_cached_schedule() {
+    let dir = tempfile::tempdir().unwrap();
+    save_index(dir.path(), &SeasonIndex::from_races(2024, &[
+        schedule_race(2024, 1, "bahrain", "Bahrain Grand Prix", "2024-03-02"),
+        schedule_race(2024, 2, "jeddah", "Saudi Arabian Grand Prix", "2024-03-09"),
+    ])).unwrap();
+    
+    let (season, entry) = next_race(&cached_config(dir.path(), 2024), None, date("2024-03-05")).unwrap();
+    
+    assert_eq!(season, 2024);
+    assert_eq!(entry.circuit_id, "jeddah");
+}
+
+#[test]
+fn test_next_race_rolls_over_to_following_season() {
+    let dir = tempfile::tempdir().unwrap();
+    save_index(dir.path(), &SeasonIndex::from_races(2024, &[
+        schedule_race(2024, 24, "yas_marina", "Abu Dhabi Grand Prix", "2024-12-08"),
+    ])).unwrap();
+    save_index(dir.path(), &SeasonIndex::from_races(2025, &[
+        schedule_race(2025, 1, "albert_park", "Australian Grand Prix", "2025-03-16"),
+    ])).unwrap();
+    
+    let (season, entry) = next_race(&cached_config(dir.path(), 2024), None, date("2024-12-20")).unwrap();
+    
+    assert_eq!(season, 2025);
+    assert_eq!(entry.round, 1);
+}
+
+#[test]
+fn test_next_race_offline_without_the_following_season() {
+    let dir = tempfile::tempdir().unwrap();
+    save_index(dir.path(), &SeasonIndex::from_races(2024, &[
+        schedule_race(2024, 24, "yas_marina", "Abu Dhabi Grand Prix", "2024-12-08"),
+    ])).unwrap();
+    
+    // The 2025 calendar is not cached: no upcoming race rather than a missing cache
+    let err = next_race(&cached_config(dir.path(), 2024), None, date("2024-12-20")).unwrap_err();
+    assert!(err.to_string().starts_with("No upcoming race found in the 2024/2025 calendar"), "{}", err);
+}
+
+#[test]
+fn test_next_race_errors_when_season_is_over() {
+    let dir = tempfile::tempdir().unwrap();
+    save_index(dir.path(), &SeasonIndex::from_races(2024, &[
+        schedule_race(2024, 24, "yas_marina", "Abu Dhabi Grand Prix", "2024-12-08"),
+    ])).unwrap();
+    
+    assert!(next_race(&cached_config(dir.path(), 2024), Some(2024), date("2024-12-20")).is_err());
+}
@@ -0,0 +1,121 @@
+//! Tests for the circuit catalogue and the characteristics it gives circuits
+
+use f1_cli_simulator::circuits::CircuitCatalogue;
+use f1_cli_simulator::data::index::{round_file_name, save_index, SeasonIndex};
+use f1_cli_simulator::data::{load_cached_results, load_circuits, DataConfig};
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs;
+
+#[test]
+fn test_bundled_catalogue_is_complete() {
+    let catalogue = CircuitCatalogue::bundled();
+    let entries = catalogue.entries();
+    
+    let ids: HashSet<_> = entries.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids.len(), entries.len(), "circuit ids are unique");
+    
+    for entry in entries {
+        assert!(entry.length_km > 3.0 && entry.length_km < 7.5, "{} length", entry.id);
+        assert!(entry.laps as f64 * entry.length_km >= 260.0, "{} race distance", entry.id);
+        assert!(entry.corners > 0, "{} corners", entry.id);
+        assert!((0.0..=1.0).contains(&entry.overtaking_difficulty), "{} overtaking difficulty", entry.id);
+        assert!((0.0..=1.0).contains(&entry.weather_risk), "{} weather risk", entry.id);
+        assert!(entry.pit_lane_loss_secs > 10.0, "{} pit lane loss", entry.id);
+        assert!(entry.tyre_wear > 0.5 && entry.tyre_wear < 1.5, "{} tyre wear", entry.id);
+        assert!(entry.red_flag_chance + entry.safety_car_chance + entry.virtual_safety_car_chance <= 1.0, "{} safety car chances", entry.id);
+    }
+}
+
+#[test]
+fn test_circuits_are_found_by_id_alias_and_grand_prix_name() {
+    let catalogue = CircuitCatalogue::bundled();
+    
+    let monza = catalogue.circuit("monza").unwrap();
+    assert_eq!(monza.laps, 53);
+    assert_eq!(monza.length_km, 5.793);
+    
+    assert_eq!(catalogue.circuit("Italian GP").unwrap().id, "monza");
+    assert_eq!(catalogue.circuit("Austrian Grand Prix").unwrap().id, "red_bull_ring");
+    assert_eq!(catalogue.circuit("styrian").unwrap().id, "red_bull_ring");
+    assert_eq!(catalogue.circuit("las vegas").unwrap().id, "vegas");
+    assert_eq!(catalogue.circuit("abu-dhabi").unwrap().id, "yas_marina");
+    
+    assert!(catalogue.circuit("atlantis").is_err());
+}
+
+#[test]
+fn test_characteristics_shape_the_race() {
+    let catalogue = CircuitCatalogue::bundled();
+    let monaco = catalogue.circuit("monaco").unwrap();
+    let monza = catalogue.circuit("monza").unwrap();
+    let spa = catalogue.circuit("spa").unwrap();
+    
+    assert!(monaco.overtaking_factor() < monza.overtaking_factor());
+    assert!(spa.base_lap_time() > monza.base_lap_time());
+    assert!(monza.base_lap_time() > monaco.base_lap_time());
+}
+
+#[test]
+fn test_user_catalogue_adds_and_replaces_circuits() {
+    let dir = tempfile::tempdir().unwrap();
+    let catalogue_path = dir.path().join("circuits.json");
+    fs::write(&catalogue_path, json!([
+        {
+            "id": "monza", "name": "Monza", "country": "Italy", "city": "Monza",
+            "length_km": 5.793, "laps": 60, "corners": 11, "drs_zones": 2,
+            "overtaking_difficulty": 0.35, "pit_lane_loss_secs": 24.0, "weather_risk": 0.9,
+        },
+        {
+            "id": "madring", "name": "Madring", "country": "Spain", "city": "Madrid", "aliases": ["madrid"],
+            "length_km": 5.47, "laps": 57, "corners": 22, "drs_zones": 2,
+            "overtaking_difficulty": 0.5, "pit_lane_loss_secs": 21.0, "weather_risk": 0.1,
+        },
+    ]).to_string()).unwrap();
+    
+    let mut config = DataConfig::new(dir.path(), "http://127.0.0.1:9");
+    config.circuits = Some(catalogue_path);
+    
+    let catalogue = load_circuits(&config).unwrap();
+    assert_eq!(catalogue.entries().len(), CircuitCatalogue::bundled().entries().len() + 1);
+    assert_eq!(catalogue.circuit("italian").unwrap().laps, 60);
+    assert_eq!(catalogue.circuit("madrid").unwrap().corners, 22);
+    assert_eq!(catalogue.circuit("monaco").unwrap().laps, 78);
+}
+
+#[test]
+fn test_cached_races_take_their_characteristics_from_the_catalogue() {
+    let dir = tempfile::tempdir().unwrap();
+    let race = |round: u32, circuit_id: &str| json!({
+        "season": "2024",
+        "round": round.to_string(),
+        "raceName": format!("Round {} Grand Prix", round),
+        "date": format!("2024-05-{:02}", round),
+        "Circuit": { "circuitId": circuit_id, "circuitName": "From the feed", "Location": { "locality": "Town", "country": "Land" } },
+        "Results": [{
+            "position": "1", "points": "25", "laps": "78", "status": "Finished",
+            "Driver": { "driverId": "leclerc", "code": "LEC", "givenName": "Charles", "familyName": "Leclerc" },
+            "Constructor": { "name": "Ferrari" },
+        }],
+    });
+    let races = vec![race(1, "monaco"), race(2, "new_street_circuit")];
+    save_index(dir.path(), &SeasonIndex::from_races(2024, &races)).unwrap();
+    for (i, race) in races.iter().enumerate() {
+        fs::write(dir.path().join(round_file_name("race", 2024, i as u32 + 1)), json!([race]).to_string()).unwrap();
+    }
+    
+    let config = DataConfig::new(dir.path(), "http://127.0.0.1:9");
+    let weekends = load_cached_results(&config, 2024).unwrap();
+    
+    // The feed's names are kept, the catalogue adds what the feed lacks
+    let monaco = &weekends[0].race.circuit;
+    assert_eq!(monaco.name, "From the feed");
+    assert_eq!(monaco.length_km, 3.337);
+    assert_eq!(monaco.laps, 78);
+    assert_eq!(monaco.corners, 19);
+    
+    // An unknown circuit is an average track over a grand prix distance
+    let unknown = &weekends[1].race.circuit;
+    assert!(unknown.laps as f64 * unknown.length_km >= 305.0);
+    assert!(unknown.overtaking_difficulty > 0.0);
+}
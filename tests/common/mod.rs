@@ -0,0 +1,140 @@
+//! Shared helpers for integration tests
+
+#![allow(dead_code)]
+
+use f1_cli_simulator::circuits::CircuitCatalogue;
+use f1_cli_simulator::models::{Circuit, Driver, SimulationParameters};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A canned HTTP response returned by the stub server
+pub struct StubResponse {
+    pub status: u16,
+    pub body: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl StubResponse {
+    pub fn json(body: impl Into<String>) -> Self {
+        StubResponse {
+            status: 200,
+            body: body.into(),
+            headers: Vec::new(),
+        }
+    }
+    
+    pub fn status(status: u16) -> Self {
+        StubResponse {
+            status,
+            body: String::new(),
+            headers: Vec::new(),
+        }
+    }
+    
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// A minimal local HTTP server standing in for the Ergast API
+pub struct StubServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl StubServer {
+    /// Start a server that answers every request with `handler(path_and_query)`
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&str) -> StubResponse + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind stub server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+        
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                
+                // Request line, e.g. "GET /2023/results.json?limit=100&offset=0 HTTP/1.1"
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).is_err() {
+                    continue;
+                }
+                let target = request_line.split_whitespace().nth(1).unwrap_or("/").to_string();
+                
+                // Skip the remaining headers
+                let mut line = String::new();
+                while reader.read_line(&mut line).map(|n| n > 2).unwrap_or(false) {
+                    line.clear();
+                }
+                
+                recorded.lock().unwrap().push(target.clone());
+                let response = handler(&target);
+                
+                let mut head = format!(
+                    "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    response.status,
+                    response.body.len()
+                );
+                for (name, value) in &response.headers {
+                    head.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                head.push_str("\r\n");
+                
+                let _ = stream.write_all(head.as_bytes());
+                let _ = stream.write_all(response.body.as_bytes());
+            }
+        });
+        
+        StubServer { url, requests }
+    }
+    
+    /// Paths (with query strings) of every request received so far
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Read a query parameter from a request target
+pub fn query_param(target: &str, name: &str) -> Option<u32> {
+    let query = target.split('?').nth(1)?;
+    query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+/// Six drivers in three teams
+pub fn drivers() -> Vec<Driver> {
+    (1..=6u32).map(|i| Driver {
+        id: format!("d{}", i),
+        code: format!("D0{}", i),
+        name: format!("Driver {}", i),
+        team: format!("Team {}", i.div_ceil(2)),
+        number: i,
+    }).collect()
+}
+
+/// A circuit from the bundled catalogue
+pub fn circuit(id: &str) -> Circuit {
+    CircuitCatalogue::bundled().circuit(id).unwrap()
+}
+
+/// Monza, where most engine tests race
+pub fn monza() -> Circuit {
+    circuit("monza")
+}
+
+/// Dry race parameters without incidents
+pub fn params() -> SimulationParameters {
+    SimulationParameters {
+        random_incidents: false,
+        ..Default::default()
+    }
+}
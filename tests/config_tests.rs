@@ -0,0 +1,148 @@
+//! Tests for data configuration and for pointing the data layer at a fixture server
+
+mod common;
+
+use common::{StubResponse, StubServer};
+use f1_cli_simulator::data::config::DEFAULT_API_BASE_URL;
+use f1_cli_simulator::data::{DataConfig, DataInterface, DataManager};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+// Build an environment lookup from a fixed set of variables
+fn fake_env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = vars.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    move |key| vars.get(key).cloned()
+}
+
+#[test]
+fn test_defaults_use_xdg_data_home_and_jolpica() {
+    let env = fake_env(&[("XDG_DATA_HOME", "/srv/data"), ("HOME", "/home/test")]);
+    
+    let config = DataConfig::from_env(&env, None).unwrap();
+    
+    assert_eq!(config.data_dir, PathBuf::from("/srv/data/f1-cli-simulator"));
+    assert_eq!(config.api_base_url, DEFAULT_API_BASE_URL);
+}
+
+#[test]
+fn test_defaults_fall_back_to_home() {
+    let env = fake_env(&[("HOME", "/home/test")]);
+    
+    let config = DataConfig::from_env(&env, None).unwrap();
+    
+    assert_eq!(config.data_dir, PathBuf::from("/home/test/.local/share/f1-cli-simulator"));
+}
+
+#[test]
+fn test_config_file_overrides_defaults() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.json");
+    fs::write(&config_path, r#"{ "data_dir": "/mnt/cache", "api_base_url": "http://mirror.local/f1/" }"#).unwrap();
+    let env = fake_env(&[("HOME", "/home/test")]);
+    
+    let config = DataConfig::from_env(&env, Some(&config_path)).unwrap();
+    
+    assert_eq!(config.data_dir, PathBuf::from("/mnt/cache"));
+    assert_eq!(config.api_base_url, "http://mirror.local/f1");
+}
+
+#[test]
+fn test_default_config_file_is_picked_up() {
+    let dir = tempfile::tempdir().unwrap();
+    let app_dir = dir.path().join("f1-cli-simulator");
+    fs::create_dir_all(&app_dir).unwrap();
+    fs::write(app_dir.join("config.json"), r#"{ "api_base_url": "http://mirror.local/f1" }"#).unwrap();
+    let env = fake_env(&[("XDG_CONFIG_HOME", dir.path().to_str().unwrap()), ("HOME", "/home/test")]);
+    
+    let config = DataConfig::from_env(&env, None).unwrap();
+    
+    assert_eq!(config.api_base_url, "http://mirror.local/f1");
+}
+
+#[test]
+fn test_environment_overrides_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.json");
+    fs::write(&config_path, r#"{ "data_dir": "/mnt/cache", "api_base_url": "http://mirror.local/f1" }"#).unwrap();
+    let env = fake_env(&[
+        ("F1_CONFIG", config_path.to_str().unwrap()),
+        ("F1_DATA_DIR", "/tmp/f1"),
+        ("F1_API_URL", "http://localhost:8000/ergast/f1"),
+        ("F1_ROSTER", "/tmp/roster.json"),
+        ("F1_CIRCUITS", "/tmp/circuits.json"),
+    ]);
+    
+    let config = DataConfig::from_env(&env, None).unwrap();
+    
+    assert_eq!(config.data_dir, PathBuf::from("/tmp/f1"));
+    assert_eq!(config.api_base_url, "http://localhost:8000/ergast/f1");
+    assert_eq!(config.roster, Some(PathBuf::from("/tmp/roster.json")));
+    assert_eq!(config.circuits, Some(PathBuf::from("/tmp/circuits.json")));
+}
+
+#[test]
+fn test_missing_explicit_config_file_is_an_error() {
+    let env = fake_env(&[]);
+    let missing = PathBuf::from("/nonexistent/f1-config.json");
+    
+    assert!(DataConfig::from_env(&env, Some(&missing)).is_err());
+}
+
+#[test]
+fn test_data_manager_fetches_from_configured_server_into_configured_dir() {
+    let server = StubServer::start(|target| {
+        let path = target.split('?').next().unwrap_or_default();
+        let race = |round: &str, name: &str| json!({
+            "season": "2020",
+            "round": round,
+            "raceName": name,
+            "date": "2020-07-12",
+            "Circuit": {
+                "circuitId": "red_bull_ring",
+                "circuitName": "Red Bull Ring",
+                "Location": { "locality": "Spielberg", "country": "Austria" },
+            },
+        });
+        
+        let races = match path {
+            "/2020.json" => vec![race("1", "Austrian Grand Prix"), race("2", "Styrian Grand Prix")],
+            "/2020/2/results.json" => {
+                let mut styrian = race("2", "Styrian Grand Prix");
+                styrian["Results"] = json!([{
+                    "position": "1",
+                    "points": "25",
+                    "laps": "71",
+                    "status": "Finished",
+                    "Driver": { "driverId": "hamilton", "code": "HAM", "givenName": "Lewis", "familyName": "Hamilton" },
+                    "Constructor": { "name": "Mercedes" },
+                }]);
+                vec![styrian]
+            },
+            _ => return StubResponse::status(404),
+        };
+        
+        StubResponse::json(json!({
+            "MRData": { "limit": "100", "offset": "0", "total": races.len().to_string(), "RaceTable": { "Races": races } }
+        }).to_string())
+    });
+    
+    let dir = tempfile::tempdir().unwrap();
+    let data_manager = DataManager::new(DataConfig::new(dir.path(), &server.url));
+    
+    let race = data_manager.load_race_data(2020, "styrian").unwrap();
+    
+    assert_eq!(race.round, 2);
+    assert_eq!(race.name, "Styrian Grand Prix");
+    assert_eq!(race.results[0].driver.name, "Lewis Hamilton");
+    assert!(dir.path().join("race_2020_r2.json").exists());
+    assert!(dir.path().join("index_2020.json").exists());
+    
+    // The second load is served from the cache
+    let requests_before = server.requests().len();
+    data_manager.load_race_data(2020, "2").unwrap();
+    assert_eq!(server.requests().len(), requests_before);
+}
@@ -0,0 +1,132 @@
+//! Tests for the events the race engine reports to its observers
+
+mod common;
+
+use common::{drivers, monza, params};
+use f1_cli_simulator::models::{RaceFormat, SimulationParameters};
+use f1_cli_simulator::points::PointsSystem;
+use f1_cli_simulator::ratings::Ratings;
+use f1_cli_simulator::simulator::simulation::events::{JsonLog, RaceEvent};
+use f1_cli_simulator::simulator::simulation::phases::Phase;
+use f1_cli_simulator::simulator::simulation::{self, RaceState};
+use f1_cli_simulator::utils;
+
+// Run a whole race, collecting its events
+fn watch_race(params: &SimulationParameters) -> (RaceState, Vec<RaceEvent>) {
+    let mut events = Vec::new();
+    let race = simulation::run_race_with(&drivers(), &monza(), params, RaceFormat::GrandPrix, &Ratings::new(), &mut utils::seeded_rng(Some(1)), &mut events);
+    (race, events)
+}
+
+#[test]
+fn test_a_race_reports_every_lap_and_the_finish() {
+    let (race, events) = watch_race(&params());
+    
+    let laps: Vec<u32> = events.iter()
+        .filter_map(|event| match event {
+            RaceEvent::LapCompleted { lap } => Some(*lap),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(laps, (1..=race.total_laps).collect::<Vec<_>>());
+    assert!(events.windows(2).all(|pair| pair[0].lap() <= pair[1].lap()), "events in order");
+    
+    // The flag falls last, on the winner
+    let results = race.results(&drivers(), &monza(), RaceFormat::GrandPrix, &PointsSystem::for_season(2024));
+    let winner = drivers().iter().position(|driver| driver.id == results[0].driver.id);
+    assert_eq!(events.last(), Some(&RaceEvent::Finish { lap: race.total_laps, winner }));
+}
+
+#[test]
+fn test_every_pit_stop_is_reported() {
+    let (race, events) = watch_race(&params());
+    
+    for car in &race.cars {
+        let stops: Vec<&RaceEvent> = events.iter()
+            .filter(|event| matches!(event, RaceEvent::PitStop { driver, .. } if *driver == car.driver))
+            .collect();
+        assert_eq!(stops.len(), car.stints.len() - 1);
+        
+        for (stop, pair) in stops.iter().zip(car.stints.windows(2)) {
+            let RaceEvent::PitStop { from, to, pit_lane_time, .. } = stop else { unreachable!() };
+            assert_eq!((*from, *to, *pit_lane_time), (pair[0].compound, pair[1].compound, pair[1].pit_lane_time));
+        }
+    }
+}
+
+#[test]
+fn test_fastest_laps_only_ever_improve() {
+    let (race, events) = watch_race(&params());
+    
+    let records: Vec<(usize, std::time::Duration)> = events.iter()
+        .filter_map(|event| match event {
+            RaceEvent::FastestLap { driver, time, .. } => Some((*driver, *time)),
+            _ => None,
+        })
+        .collect();
+    assert!(records.windows(2).all(|pair| pair[1].1 < pair[0].1));
+    
+    let fastest = race.fastest_lap().unwrap();
+    assert_eq!(records.last(), Some(&(fastest.driver, fastest.time)));
+}
+
+#[test]
+fn test_a_faster_car_is_reported_passing() {
+    let mut circuit = monza();
+    circuit.overtaking_difficulty = 0.0;
+    let mut race = RaceState::from_grid(vec![(0, 0.0), (1, 1.0)], circuit.laps);
+    let mut rng = utils::seeded_rng(Some(2));
+    
+    while race.running_order()[0] == 0 && race.lap() < circuit.laps {
+        race.simulate_lap(&params(), &circuit, &mut rng);
+    }
+    assert_eq!(race.running_order()[0], 1, "the faster car never got past");
+    let events = race.take_events();
+    let lap = race.lap();
+    
+    assert!(events.contains(&RaceEvent::Overtake { lap, driver: 1, passed: 0 }), "{:?}", events);
+    assert!(!events.iter().any(|event| matches!(event, RaceEvent::Overtake { driver: 0, .. })));
+    assert!(race.take_events().is_empty(), "events are taken once");
+}
+
+#[test]
+fn test_retirements_and_phases_are_reported() {
+    let (params, circuit) = (params(), monza());
+    let mut race = RaceState::from_grid(vec![(0, 1.0), (1, 0.9), (2, 0.8)], circuit.laps);
+    let mut rng = utils::seeded_rng(Some(3));
+    for _ in 0..10 {
+        race.simulate_lap(&params, &circuit, &mut rng);
+    }
+    race.take_events();
+    
+    race.retire(2, "Engine");
+    race.deploy(Phase::VirtualSafetyCar, 1);
+    assert_eq!(race.take_events(), vec![
+        RaceEvent::Retirement { lap: 11, driver: 2, reason: "Engine".to_string() },
+        RaceEvent::SafetyCar { lap: 11, phase: Phase::VirtualSafetyCar, deployed: true },
+    ]);
+    
+    race.simulate_lap(&params, &circuit, &mut rng);
+    let events = race.take_events();
+    assert!(events.contains(&RaceEvent::SafetyCar { lap: 11, phase: Phase::VirtualSafetyCar, deployed: false }));
+    assert_eq!(events.last(), Some(&RaceEvent::LapCompleted { lap: 11 }));
+}
+
+#[test]
+fn test_the_json_log_names_drivers_by_code() {
+    let drivers = drivers();
+    let mut log = JsonLog::new(&drivers, Vec::new());
+    let race = simulation::run_race_with(&drivers, &monza(), &params(), RaceFormat::GrandPrix, &Ratings::new(), &mut utils::seeded_rng(Some(4)), &mut log);
+    let output = String::from_utf8(log.finish().unwrap()).unwrap();
+    
+    let lines: Vec<serde_json::Value> = output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines.iter().filter(|line| line["event"] == "lap_completed").count(), race.total_laps as usize);
+    
+    let finish = lines.last().unwrap();
+    assert_eq!(finish["event"], "finish");
+    assert!(finish["winner"].as_str().unwrap().starts_with("D0"));
+    
+    let stop = lines.iter().find(|line| line["event"] == "pit_stop").unwrap();
+    assert_eq!(stop["from"], "medium");
+    assert!(stop["pit_lane_secs"].as_f64().unwrap() > 0.0);
+}
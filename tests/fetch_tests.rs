@@ -0,0 +1,319 @@
+//! Tests for paginated, rate limited and retried fetching against a local stub server
+
+mod common;
+
+use common::{query_param, StubResponse, StubServer};
+use chrono::{TimeZone, Utc};
+use f1_cli_simulator::data::fetch::{
+    append_rows, fetch_all_pages, retry_after, FetchClient, FetchSettings, RetryPolicy, TokenBucket,
+};
+use f1_cli_simulator::data::{resume_command, update_data, DataConfig, FetchFailure, UpdateOptions};
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+// Settings without delays so retry tests run instantly
+fn fast_settings(max_retries: u32) -> FetchSettings {
+    FetchSettings {
+        requests_per_second: 1000.0,
+        burst: 100,
+        timeout: Duration::from_secs(5),
+        retry: RetryPolicy {
+            max_retries,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::from_millis(10),
+        },
+    }
+}
+
+fn fast_client() -> FetchClient {
+    FetchClient::new(&fast_settings(3)).unwrap()
+}
+
+// Build one result row for the given driver
+fn result_row(driver: &str, position: u32) -> Value {
+    json!({
+        "number": position.to_string(),
+        "position": position.to_string(),
+        "Driver": { "driverId": driver },
+    })
+}
+
+// Build a results page in the Ergast response format
+fn results_page(limit: u32, offset: u32, total: u32, races: Vec<Value>) -> String {
+    json!({
+        "MRData": {
+            "limit": limit.to_string(),
+            "offset": offset.to_string(),
+            "total": total.to_string(),
+            "RaceTable": { "season": "2020", "Races": races },
+        }
+    }).to_string()
+}
+
+// Serve 2 races with 3 results each, split into pages of `page_size` result rows
+fn serve_two_races(page_size: u32) -> StubServer {
+    StubServer::start(move |target| {
+        let offset = query_param(target, "offset").unwrap_or(0);
+        let rows: Vec<(u32, Value)> = (0..6)
+            .map(|i| (i / 3 + 1, result_row(&format!("driver{}", i), i % 3 + 1)))
+            .collect();
+        
+        let mut races: Vec<Value> = Vec::new();
+        for (round, row) in rows.into_iter().skip(offset as usize).take(page_size as usize) {
+            match races.last_mut() {
+                Some(race) if race["round"] == *round.to_string() => {
+                    race["Results"].as_array_mut().unwrap().push(row);
+                },
+                _ => races.push(json!({
+                    "season": "2020",
+                    "round": round.to_string(),
+                    "Results": [row],
+                })),
+            }
+        }
+        
+        StubResponse::json(results_page(page_size, offset, 6, races))
+    })
+}
+
+#[test]
+fn test_fetch_all_pages_merges_races_split_across_pages() {
+    let server = serve_two_races(4);
+    let url = format!("{}/2020/results.json", server.url);
+    
+    let races = fetch_all_pages(&fast_client(), &url, "RaceTable", "Races").unwrap();
+    
+    // Round 2 was split over both pages but must come back as one race
+    assert_eq!(races.len(), 2);
+    for race in &races {
+        assert_eq!(race["Results"].as_array().unwrap().len(), 3);
+    }
+    
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn test_fetch_all_pages_follows_server_page_size() {
+    // The server caps pages at 2 rows even though we ask for more
+    let server = serve_two_races(2);
+    let url = format!("{}/2020/results.json", server.url);
+    
+    let races = fetch_all_pages(&fast_client(), &url, "RaceTable", "Races").unwrap();
+    let total_rows: usize = races.iter()
+        .map(|r| r["Results"].as_array().unwrap().len())
+        .sum();
+    
+    assert_eq!(total_rows, 6);
+    let offsets: Vec<u32> = server.requests().iter()
+        .filter_map(|r| query_param(r, "offset"))
+        .collect();
+    assert_eq!(offsets, vec![0, 2, 4]);
+}
+
+#[test]
+fn test_fetch_all_pages_single_page() {
+    let server = serve_two_races(30);
+    let url = format!("{}/2020/results.json", server.url);
+    
+    let races = fetch_all_pages(&fast_client(), &url, "RaceTable", "Races").unwrap();
+    
+    assert_eq!(races.len(), 2);
+    assert_eq!(server.requests().len(), 1);
+    assert_eq!(query_param(&server.requests()[0], "limit"), Some(100));
+}
+
+#[test]
+fn test_fetch_all_pages_reports_error_status() {
+    let server = StubServer::start(|_| StubResponse::status(500));
+    let url = format!("{}/2020/results.json", server.url);
+    
+    let result = fetch_all_pages(&fast_client(), &url, "RaceTable", "Races");
+    
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("500"));
+}
+
+#[test]
+fn test_append_rows_keeps_distinct_races_separate() {
+    let mut rows = vec![json!({ "season": "2020", "round": "1", "Results": [result_row("a", 1)] })];
+    
+    append_rows(&mut rows, vec![
+        json!({ "season": "2020", "round": "2", "Results": [result_row("b", 1)] }),
+    ]);
+    
+    assert_eq!(rows.len(), 2);
+}
+
+#[test]
+fn test_append_rows_keeps_results_of_a_shared_car_apart() {
+    // Two drivers shared car 2 in the 1950s, listed on either side of a page boundary
+    let mut rows = vec![json!({ "season": "1954", "round": "1", "Results": [result_row("a", 1), result_row("b", 2)] })];
+    
+    append_rows(&mut rows, vec![
+        json!({ "season": "1954", "round": "1", "Results": [result_row("c", 2), result_row("d", 3)] }),
+    ]);
+    
+    assert_eq!(rows.len(), 1);
+    let drivers: Vec<&str> = rows[0]["Results"].as_array().unwrap().iter()
+        .map(|r| r["Driver"]["driverId"].as_str().unwrap())
+        .collect();
+    assert_eq!(drivers, vec!["a", "b", "c", "d"]);
+}
+
+#[test]
+fn test_append_rows_merges_laps_split_across_pages() {
+    let timing = |driver: &str| json!({ "driverId": driver, "position": "1", "time": "1:30.000" });
+    let mut rows = vec![json!({ "season": "2020", "round": "1", "Laps": [
+        { "number": "1", "Timings": [timing("a"), timing("b")] },
+        { "number": "2", "Timings": [timing("a")] },
+    ] })];
+    
+    append_rows(&mut rows, vec![
+        json!({ "season": "2020", "round": "1", "Laps": [
+            { "number": "2", "Timings": [timing("b")] },
+            { "number": "3", "Timings": [timing("a"), timing("b")] },
+        ] }),
+    ]);
+    
+    let laps = rows[0]["Laps"].as_array().unwrap();
+    assert_eq!(laps.len(), 3);
+    for lap in laps {
+        assert_eq!(lap["Timings"].as_array().unwrap().len(), 2);
+    }
+}
+
+#[test]
+fn test_server_errors_are_retried() {
+    let server = StubServer::start({
+        let calls = std::sync::atomic::AtomicU32::new(0);
+        move |_| match calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+            0 => StubResponse::status(503),
+            1 => StubResponse::status(502),
+            _ => StubResponse::json(results_page(100, 0, 0, vec![])),
+        }
+    });
+    let url = format!("{}/2020/results.json", server.url);
+    
+    assert!(fetch_all_pages(&fast_client(), &url, "RaceTable", "Races").is_ok());
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn test_rate_limited_requests_honour_retry_after() {
+    let server = StubServer::start({
+        let calls = std::sync::atomic::AtomicU32::new(0);
+        move |_| match calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+            0 => StubResponse::status(429).with_header("Retry-After", "0"),
+            _ => StubResponse::json(results_page(100, 0, 0, vec![])),
+        }
+    });
+    let url = format!("{}/2020/results.json", server.url);
+    
+    assert!(fetch_all_pages(&fast_client(), &url, "RaceTable", "Races").is_ok());
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn test_client_errors_are_not_retried() {
+    let server = StubServer::start(|_| StubResponse::status(404));
+    let url = format!("{}/2020/results.json", server.url);
+    
+    assert!(fetch_all_pages(&fast_client(), &url, "RaceTable", "Races").is_err());
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn test_gives_up_after_max_retries() {
+    let server = StubServer::start(|_| StubResponse::status(503));
+    let url = format!("{}/2020/results.json", server.url);
+    let client = FetchClient::new(&fast_settings(2)).unwrap();
+    
+    let err = fetch_all_pages(&client, &url, "RaceTable", "Races").unwrap_err();
+    
+    assert!(err.to_string().contains("gave up after 3 attempts"));
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn test_slow_responses_time_out() {
+    let server = StubServer::start(|_| {
+        std::thread::sleep(Duration::from_millis(300));
+        StubResponse::json(results_page(100, 0, 0, vec![]))
+    });
+    let url = format!("{}/2020/results.json", server.url);
+    let mut settings = fast_settings(0);
+    settings.timeout = Duration::from_millis(50);
+    
+    let err = fetch_all_pages(&FetchClient::new(&settings).unwrap(), &url, "RaceTable", "Races").unwrap_err();
+    
+    assert!(err.to_string().contains("timed out"));
+}
+
+#[test]
+fn test_retry_after_parsing() {
+    let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+    let mut headers = HeaderMap::new();
+    assert_eq!(retry_after(&headers, now), None);
+    
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+    assert_eq!(retry_after(&headers, now), Some(Duration::from_secs(7)));
+    
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("Wed, 01 May 2024 12:00:30 GMT"));
+    assert_eq!(retry_after(&headers, now), Some(Duration::from_secs(30)));
+}
+
+#[test]
+fn test_backoff_doubles_up_to_the_maximum() {
+    let policy = RetryPolicy {
+        max_retries: 10,
+        base_delay: Duration::from_millis(500),
+        max_delay: Duration::from_secs(3),
+    };
+    
+    assert_eq!(policy.backoff(0), Duration::from_millis(500));
+    assert_eq!(policy.backoff(1), Duration::from_secs(1));
+    assert_eq!(policy.backoff(2), Duration::from_secs(2));
+    assert_eq!(policy.backoff(3), Duration::from_secs(3));
+    assert_eq!(policy.backoff(40), Duration::from_secs(3));
+}
+
+#[test]
+fn test_token_bucket_spaces_out_requests() {
+    let start = Instant::now();
+    let mut bucket = TokenBucket::new(4.0, 2, start);
+    
+    // The burst goes out immediately, then requests are spaced 250ms apart
+    assert_eq!(bucket.reserve(start), Duration::ZERO);
+    assert_eq!(bucket.reserve(start), Duration::ZERO);
+    assert_eq!(bucket.reserve(start), Duration::from_millis(250));
+    assert_eq!(bucket.reserve(start), Duration::from_millis(500));
+    
+    // After a quiet second the bucket has refilled
+    assert_eq!(bucket.reserve(start + Duration::from_secs(2)), Duration::ZERO);
+}
+
+#[test]
+fn test_update_reports_failures_with_resume_command() {
+    let server = StubServer::start(|_| StubResponse::status(503));
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = DataConfig::new(dir.path(), &server.url);
+    config.fetch = fast_settings(1);
+    
+    let err = update_data(&config, &UpdateOptions { seasons: Some("2019,2020".to_string()), ..Default::default() }).unwrap_err();
+    
+    assert!(err.to_string().contains("`f1-cli-simulator update --seasons 2019,2020`"));
+}
+
+#[test]
+fn test_resume_command_lists_each_failed_season_once() {
+    let failure = |season: u32, what: &str| FetchFailure {
+        season,
+        what: what.to_string(),
+        error: "API returned error status: 503".to_string(),
+    };
+    
+    let failures = vec![failure(2021, "season 2021 results"), failure(2018, "season 2018 schedule"), failure(2021, "season 2021 schedule")];
+    
+    assert_eq!(resume_command(&failures), "f1-cli-simulator update --seasons 2018,2021");
+}
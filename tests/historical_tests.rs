@@ -8,8 +8,10 @@ use std::io::Write;
 
 // Import the crate modules - use the crate name with underscores instead of hyphens
 use f1_cli_simulator::data::DataInterface;
-use f1_cli_simulator::models::{Circuit, Driver, PracticeResult, QualifyingResult, Race, RaceResult};
-use f1_cli_simulator::simulator::historical;
+use f1_cli_simulator::models::{Circuit, Driver, FastestLap, Lap, LapTiming, PitStop, PracticeResult, QualifyingResult, Race, RaceResult, SprintResult};
+use f1_cli_simulator::simulator::historical::{self, Gap};
+use f1_cli_simulator::utils;
+use std::time::Duration;
 
 // Mocked data module to avoid real API calls during tests
 mock! {
@@ -19,6 +21,9 @@ mock! {
         fn load_race_data(&self, season: u32, gp: &str) -> Result<Race>;
         fn load_qualifying_data(&self, season: u32, gp: &str) -> Result<Vec<QualifyingResult>>;
         fn load_practice_data(&self, season: u32, gp: &str, practice_number: u32) -> Result<Vec<PracticeResult>>;
+        fn load_sprint_data(&self, season: u32, gp: &str) -> Result<Vec<SprintResult>>;
+        fn load_lap_data(&self, season: u32, gp: &str) -> Result<Vec<Lap>>;
+        fn load_pit_stops(&self, season: u32, gp: &str) -> Result<Vec<PitStop>>;
     }
 }
 
@@ -35,6 +40,7 @@ fn create_mock_race(season: u32, gp: &str) -> Race {
             city: "Test City".to_string(),
             length_km: 5.0,
             laps: 50,
+            ..Default::default()
         },
         date: "2023-07-15".to_string(),
         results: vec![
@@ -47,10 +53,20 @@ fn create_mock_race(season: u32, gp: &str) -> Race {
                     team: "Test Team".to_string(),
                     number: 1,
                 },
+                number: 1,
+                constructor_id: "test_team".to_string(),
+                grid: 2,
                 time: Some("1:30:45.123".to_string()),
-                points: 25,
+                time_millis: Some(5_445_123),
+                points: 25.0,
                 laps: 50,
                 status: "Finished".to_string(),
+                fastest_lap: Some(FastestLap {
+                    rank: 1,
+                    lap: 42,
+                    time: "1:21.456".to_string(),
+                    average_speed_kph: Some(221.2),
+                }),
             },
         ],
     }
@@ -93,6 +109,27 @@ fn create_mock_practice_results() -> Vec<PracticeResult> {
     ]
 }
 
+// Helper function to create mock sprint results for testing
+fn create_mock_sprint_results() -> Vec<SprintResult> {
+    vec![
+        SprintResult {
+            position: 1,
+            driver: Driver {
+                id: "driver1".to_string(),
+                code: "DRV".to_string(),
+                name: "Test Driver".to_string(),
+                team: "Test Team".to_string(),
+                number: 1,
+            },
+            grid: 2,
+            time: Some("30:12.345".to_string()),
+            points: 8.0,
+            laps: 24,
+            status: "Finished".to_string(),
+        },
+    ]
+}
+
 // Helper to capture stdout for testing
 #[allow(dead_code)]
 struct StdoutCapture {
@@ -127,7 +164,7 @@ fn test_simulate_handles_race_session_correctly() {
         .returning(move |s, g| Ok(create_mock_race(s, g)));
 
     // Call the simulate function with our mock
-    let result = historical::simulate_with_data_module(season, gp, session, interactive, &data_mock);
+    let result = historical::simulate_with_data_module(season, gp, session, interactive, &mut utils::seeded_rng(Some(1)), &data_mock);
     
     // Verify the result
     assert!(result.is_ok());
@@ -153,7 +190,7 @@ fn test_simulate_handles_qualifying_session_correctly() {
         .returning(move |_, _| Ok(mock_qualifying_results.clone()));
 
     // Call the simulate function with our mock
-    let result = historical::simulate_with_data_module(season, gp, session, interactive, &data_mock);
+    let result = historical::simulate_with_data_module(season, gp, session, interactive, &mut utils::seeded_rng(Some(2)), &data_mock);
     
     // Verify the result
     assert!(result.is_ok());
@@ -180,7 +217,7 @@ fn test_simulate_handles_practice_session_correctly() {
         .returning(move |_, _, _| Ok(mock_practice_results.clone()));
 
     // Call the simulate function with our mock
-    let result = historical::simulate_with_data_module(season, gp, session, interactive, &data_mock);
+    let result = historical::simulate_with_data_module(season, gp, session, interactive, &mut utils::seeded_rng(Some(3)), &data_mock);
     
     // Verify the result
     assert!(result.is_ok());
@@ -196,7 +233,7 @@ fn test_simulate_handles_invalid_session_type() {
     
     // Even with dependency injection, we can test the session validation directly
     let data_mock = MockDataModule::new();
-    let result = historical::simulate_with_data_module(season, gp, invalid_session, interactive, &data_mock);
+    let result = historical::simulate_with_data_module(season, gp, invalid_session, interactive, &mut utils::seeded_rng(Some(4)), &data_mock);
     
     // Verify that the error is appropriate
     assert!(result.is_err());
@@ -223,7 +260,7 @@ fn test_simulate_race_handles_data_error() {
         .returning(|_, _| Err(anyhow::anyhow!("Race data not found")));
     
     // Call the simulate function with our mock
-    let result = historical::simulate_with_data_module(season, gp, session, interactive, &data_mock);
+    let result = historical::simulate_with_data_module(season, gp, session, interactive, &mut utils::seeded_rng(Some(5)), &data_mock);
     
     // Verify that the error is propagated
     assert!(result.is_err());
@@ -249,7 +286,7 @@ fn test_simulate_qualifying_handles_data_error() {
         .returning(|_, _| Err(anyhow::anyhow!("Qualifying data not found")));
     
     // Call the simulate function with our mock
-    let result = historical::simulate_with_data_module(season, gp, session, interactive, &data_mock);
+    let result = historical::simulate_with_data_module(season, gp, session, interactive, &mut utils::seeded_rng(Some(6)), &data_mock);
     
     // Verify that the error is propagated
     assert!(result.is_err());
@@ -275,11 +312,168 @@ fn test_simulate_practice_handles_data_error() {
         .returning(|_, _, _| Err(anyhow::anyhow!("Practice data not found")));
     
     // Call the simulate function with our mock
-    let result = historical::simulate_with_data_module(season, gp, session, interactive, &data_mock);
+    let result = historical::simulate_with_data_module(season, gp, session, interactive, &mut utils::seeded_rng(Some(7)), &data_mock);
     
     // Verify that the error is propagated
     assert!(result.is_err());
     if let Err(e) = result {
         assert!(e.to_string().contains("Practice data not found"));
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_simulate_handles_sprint_session_correctly() {
+    // Setup
+    let season = 2023;
+    let gp = "interlagos";
+    let interactive = false;
+    
+    // Mock the data module; sprint and sprint qualifying both read the sprint results
+    let mut data_mock = MockDataModule::new();
+    data_mock
+        .expect_load_sprint_data()
+        .with(eq(season), eq(gp))
+        .times(2)
+        .returning(|_, _| Ok(create_mock_sprint_results()));
+
+    // Call the simulate function with our mock
+    let sprint = historical::simulate_with_data_module(season, gp, "sprint", interactive, &mut utils::seeded_rng(Some(8)), &data_mock);
+    let sprint_qualifying = historical::simulate_with_data_module(season, gp, "sprint-qualifying", interactive, &mut utils::seeded_rng(Some(9)), &data_mock);
+    
+    // Verify the result
+    assert!(sprint.is_ok());
+    assert!(sprint_qualifying.is_ok());
+}
+
+#[test]
+fn test_simulate_sprint_handles_data_error() {
+    // Setup
+    let season = 2019;
+    let gp = "monza";
+    let session = "sprint";
+    let interactive = false;
+    
+    // Mock the data module to return an error
+    let mut data_mock = MockDataModule::new();
+    data_mock
+        .expect_load_sprint_data()
+        .with(eq(season), eq(gp))
+        .times(1)
+        .returning(|_, _| Err(anyhow::anyhow!("No sprint was held")));
+    
+    // Call the simulate function with our mock
+    let result = historical::simulate_with_data_module(season, gp, session, interactive, &mut utils::seeded_rng(Some(10)), &data_mock);
+    
+    // Verify that the error is propagated
+    assert!(result.is_err());
+    if let Err(e) = result {
+        assert!(e.to_string().contains("No sprint was held"));
+    }
+}
+
+// Build a lap from (driver, position, lap time) rows
+fn lap(number: u32, timings: &[(&str, u32, &str)]) -> Lap {
+    Lap {
+        number,
+        timings: timings.iter().map(|&(driver_id, position, time)| LapTiming {
+            driver_id: driver_id.to_string(),
+            position,
+            time: time.to_string(),
+        }).collect(),
+    }
+}
+
+#[test]
+fn test_replay_gaps_come_from_accumulated_lap_times() {
+    let laps = vec![
+        lap(1, &[("alonso", 1, "1:40.000"), ("hamilton", 2, "1:41.500")]),
+        lap(2, &[("hamilton", 1, "1:30.000"), ("alonso", 2, "1:32.000")]),
+    ];
+    
+    let standings = historical::replay_standings(&laps);
+    
+    assert_eq!(standings.len(), 2);
+    assert_eq!(standings[0][0].driver_id, "alonso");
+    assert_eq!(standings[0][1].gap, Gap::Behind(Duration::from_millis(1500)));
+    
+    // Hamilton passes on lap 2 and leads by half a second
+    assert_eq!(standings[1][0].driver_id, "hamilton");
+    assert_eq!(standings[1][0].gap, Gap::Leader);
+    assert_eq!(standings[1][1].gap, Gap::Behind(Duration::from_millis(500)));
+    assert_eq!(standings[1][1].lap_time, Duration::from_secs(92));
+}
+
+#[test]
+fn test_replay_marks_lapped_cars() {
+    // The leader laps in a minute, the backmarker in 1:40
+    let laps = vec![
+        lap(1, &[("leader", 1, "1:00.000"), ("backmarker", 2, "1:40.000")]),
+        lap(2, &[("leader", 1, "1:00.000"), ("backmarker", 2, "1:40.000")]),
+        lap(3, &[("leader", 1, "1:00.000"), ("backmarker", 2, "1:40.000")]),
+        lap(4, &[("leader", 1, "1:00.000")]),
+        lap(5, &[("leader", 1, "1:00.000")]),
+    ];
+    
+    let standings = historical::replay_standings(&laps);
+    
+    assert_eq!(standings[0][1].gap, Gap::Behind(Duration::from_secs(40)));
+    // The leader finishes lap 3 at 3:00, before the backmarker ends lap 2 at 3:20
+    assert_eq!(standings[1][1].gap, Gap::Lapped(1));
+    assert_eq!(standings[2][1].gap, Gap::Lapped(1));
+    assert_eq!(standings[4].len(), 1);
+}
+
+fn pit_stop(driver_id: &str, stop: u32, lap: u32) -> PitStop {
+    PitStop {
+        driver_id: driver_id.to_string(),
+        stop,
+        lap,
+        time_of_day: "15:30:00".to_string(),
+        duration: Some("22.456".to_string()),
+    }
+}
+
+#[test]
+fn test_positions_gained_through_a_pit_stop() {
+    let laps = vec![
+        lap(1, &[("alonso", 1, "1:30.000"), ("hamilton", 2, "1:30.500"), ("russell", 3, "1:31.000")]),
+        lap(2, &[("alonso", 1, "1:30.000"), ("russell", 2, "1:49.000"), ("hamilton", 3, "1:52.000")]),
+        lap(3, &[("hamilton", 1, "1:29.000"), ("alonso", 2, "1:31.000"), ("russell", 3, "1:30.000")]),
+    ];
+    
+    // Hamilton undercuts from P2 to P1, Alonso loses the lead
+    assert_eq!(historical::positions_gained(&laps, &pit_stop("hamilton", 1, 2)), Some(1));
+    assert_eq!(historical::positions_gained(&laps, &pit_stop("alonso", 1, 2)), Some(-1));
+    
+    // A stop on the first or last lap has no lap on one side to compare with
+    assert_eq!(historical::positions_gained(&laps, &pit_stop("russell", 1, 3)), None);
+}
+
+#[test]
+fn test_simulate_handles_pit_stop_session_correctly() {
+    let season = 2023;
+    let gp = "silverstone";
+    
+    let mut data_mock = MockDataModule::new();
+    data_mock
+        .expect_load_race_data()
+        .with(eq(season), eq(gp))
+        .times(1)
+        .returning(|s, g| Ok(create_mock_race(s, g)));
+    data_mock
+        .expect_load_pit_stops()
+        .with(eq(season), eq(gp))
+        .times(1)
+        .returning(|_, _| Ok(vec![pit_stop("driver1", 1, 20)]));
+    
+    // Missing lap data only leaves the positions column empty
+    data_mock
+        .expect_load_lap_data()
+        .with(eq(season), eq(gp))
+        .times(1)
+        .returning(|_, _| Err(anyhow::anyhow!("No lap data")));
+    
+    let result = historical::simulate_with_data_module(season, gp, "pitstops", false, &mut utils::seeded_rng(Some(11)), &data_mock);
+    
+    assert!(result.is_ok());
+}
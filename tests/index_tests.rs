@@ -0,0 +1,119 @@
+//! Tests for the round-based race index and cache migration
+
+use f1_cli_simulator::data::index::{
+    load_index, migrate_circuit_keyed_files, parse_round_file_name, round_file_name, SeasonIndex,
+};
+use serde_json::{json, Value};
+use std::fs;
+
+// Build a schedule entry in the Ergast format
+fn race_entry(round: u32, name: &str, circuit_id: &str, locality: &str, country: &str) -> Value {
+    json!({
+        "season": "2020",
+        "round": round.to_string(),
+        "raceName": name,
+        "date": "2020-07-05",
+        "Circuit": {
+            "circuitId": circuit_id,
+            "circuitName": "Test Circuit",
+            "Location": { "locality": locality, "country": country },
+        },
+        "Results": [],
+    })
+}
+
+// The opening rounds of 2020, which visited two circuits twice
+fn create_2020_schedule() -> Vec<Value> {
+    vec![
+        race_entry(1, "Austrian Grand Prix", "red_bull_ring", "Spielberg", "Austria"),
+        race_entry(2, "Styrian Grand Prix", "red_bull_ring", "Spielberg", "Austria"),
+        race_entry(3, "Hungarian Grand Prix", "hungaroring", "Budapest", "Hungary"),
+        race_entry(4, "British Grand Prix", "silverstone", "Silverstone", "UK"),
+        race_entry(5, "70th Anniversary Grand Prix", "silverstone", "Silverstone", "UK"),
+    ]
+}
+
+#[test]
+fn test_index_keeps_both_races_at_the_same_circuit() {
+    let index = SeasonIndex::from_races(2020, &create_2020_schedule());
+    
+    assert_eq!(index.rounds.len(), 5);
+    assert_eq!(index.find_rounds("red_bull_ring"), vec![1, 2]);
+    assert_eq!(index.find_rounds("silverstone"), vec![4, 5]);
+}
+
+#[test]
+fn test_index_resolves_race_names_and_round_numbers() {
+    let index = SeasonIndex::from_races(2020, &create_2020_schedule());
+    
+    assert_eq!(index.resolve("styrian").unwrap(), 2);
+    assert_eq!(index.resolve("Austrian Grand Prix").unwrap(), 1);
+    assert_eq!(index.resolve("70th anniversary").unwrap(), 5);
+    assert_eq!(index.resolve("hungary").unwrap(), 3);
+    assert_eq!(index.resolve("4").unwrap(), 4);
+}
+
+#[test]
+fn test_index_reports_ambiguous_circuit() {
+    let index = SeasonIndex::from_races(2020, &create_2020_schedule());
+    
+    let err = index.resolve("red_bull_ring").unwrap_err().to_string();
+    assert!(err.contains("Austrian Grand Prix"));
+    assert!(err.contains("Styrian Grand Prix"));
+    assert!(err.contains("--round"));
+}
+
+#[test]
+fn test_index_reports_unknown_race() {
+    let index = SeasonIndex::from_races(2020, &create_2020_schedule());
+    
+    assert!(index.resolve("monaco").is_err());
+    assert!(index.resolve("17").is_err());
+}
+
+#[test]
+fn test_round_file_names_round_trip() {
+    let name = round_file_name("qualifying", 2020, 12);
+    
+    assert_eq!(name, "qualifying_2020_r12.json");
+    assert_eq!(parse_round_file_name(&name), Some(("qualifying", 2020, 12)));
+    assert_eq!(parse_round_file_name("race_2020_red_bull_ring.json"), None);
+    assert_eq!(parse_round_file_name("season_2020.json"), None);
+}
+
+#[test]
+fn test_migrate_splits_circuit_keyed_files_by_round() {
+    let dir = tempfile::tempdir().unwrap();
+    let schedule = create_2020_schedule();
+    
+    // Legacy files were keyed by circuit and could hold several races
+    let legacy_race = dir.path().join("race_2020_red_bull_ring.json");
+    fs::write(&legacy_race, serde_json::to_string(&schedule[0..2]).unwrap()).unwrap();
+    let legacy_qualifying = dir.path().join("qualifying_2020_hungaroring.json");
+    fs::write(&legacy_qualifying, serde_json::to_string(&schedule[2..3]).unwrap()).unwrap();
+    
+    let migrated = migrate_circuit_keyed_files(dir.path()).unwrap();
+    
+    assert_eq!(migrated, 2);
+    assert!(!legacy_race.exists());
+    assert!(!legacy_qualifying.exists());
+    assert!(dir.path().join("race_2020_r1.json").exists());
+    assert!(dir.path().join("race_2020_r2.json").exists());
+    assert!(dir.path().join("qualifying_2020_r3.json").exists());
+    
+    let index = load_index(dir.path(), 2020).unwrap().unwrap();
+    assert_eq!(index.find_rounds("red_bull_ring"), vec![1, 2]);
+    
+    // A second run finds nothing left to migrate
+    assert_eq!(migrate_circuit_keyed_files(dir.path()).unwrap(), 0);
+}
+
+#[test]
+fn test_migrate_leaves_unreadable_files_alone() {
+    let dir = tempfile::tempdir().unwrap();
+    let broken = dir.path().join("race_2020_monza.json");
+    fs::write(&broken, "not json").unwrap();
+    
+    assert_eq!(migrate_circuit_keyed_files(dir.path()).unwrap(), 0);
+    assert!(broken.exists());
+}
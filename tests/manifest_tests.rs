@@ -0,0 +1,243 @@
+//! Tests for the download manifest and incremental updates
+
+mod common;
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use common::{StubResponse, StubServer};
+use f1_cli_simulator::data::manifest::Manifest;
+use f1_cli_simulator::data::{update_data_at, DataConfig, UpdateOptions};
+use serde_json::{json, Value};
+use std::path::Path;
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+// A race entry of the given season, raced on the first of month `round + 2`,
+// optionally carrying two rows of the result list or laps for `endpoint`. Round 2 is a sprint weekend.
+fn race(season: u32, round: u32, endpoint: Option<&str>) -> Value {
+    let mut race = json!({
+        "season": season.to_string(),
+        "round": round.to_string(),
+        "raceName": format!("Round {} Grand Prix", round),
+        "date": format!("{}-{:02}-01", season, round + 2),
+        "Circuit": { "circuitId": format!("circuit_{}", round), "Location": { "locality": "", "country": "" } },
+    });
+    if round == 2 {
+        race["Sprint"] = json!({ "date": format!("{}-03-31", season) });
+    }
+    let list = match endpoint {
+        Some("results") => "Results",
+        Some("qualifying") => "QualifyingResults",
+        Some("sprint") => "SprintResults",
+        Some("pitstops") => "PitStops",
+        Some("laps") => {
+            race["Laps"] = json!([
+                { "number": "1", "Timings": [{ "driverId": "hamilton", "position": "1", "time": "1:30.000" }] },
+                { "number": "2", "Timings": [{ "driverId": "hamilton", "position": "1", "time": "1:29.000" }] },
+            ]);
+            return race;
+        },
+        _ => return race,
+    };
+    race[list] = json!([
+        { "position": "1", "Driver": { "driverId": "hamilton" } },
+        { "position": "2", "Driver": { "driverId": "bottas" } },
+    ]);
+    race
+}
+
+// Serve a season with `rounds` rounds: the schedule and season-wide and per-round sessions
+fn serve_season(season: u32, rounds: u32) -> StubServer {
+    StubServer::start(move |target| {
+        let path = target.split('?').next().unwrap_or_default();
+        let parts: Vec<&str> = path.trim_start_matches('/').trim_end_matches(".json").split('/').collect();
+        
+        let races: Vec<Value> = match parts.as_slice() {
+            [s] if *s == season.to_string() => (1..=rounds).map(|r| race(season, r, None)).collect(),
+            [s, endpoint] if *s == season.to_string() => (1..=rounds)
+                .filter(|r| *endpoint != "sprint" || *r == 2)
+                .map(|r| race(season, r, Some(endpoint)))
+                .collect(),
+            [s, round, endpoint] if *s == season.to_string() => {
+                vec![race(season, round.parse().unwrap(), Some(endpoint))]
+            },
+            _ => return StubResponse::status(404),
+        };
+        
+        StubResponse::json(json!({
+            "MRData": { "limit": "100", "offset": "0", "total": races.len().to_string(), "RaceTable": { "Races": races } }
+        }).to_string())
+    })
+}
+
+fn config(dir: &Path, server: &StubServer, current_season: u32) -> DataConfig {
+    let mut config = DataConfig::new(dir, &server.url);
+    config.current_season = Some(current_season);
+    // Lap timings and pit stops are requested round by round; no need to pace the stub
+    config.fetch.requests_per_second = 1000.0;
+    config
+}
+
+fn options_for(season: u32) -> UpdateOptions {
+    UpdateOptions {
+        seasons: Some(season.to_string()),
+        ..Default::default()
+    }
+}
+
+// Request paths without query strings
+fn paths(server: &StubServer) -> Vec<String> {
+    server.requests().iter()
+        .map(|r| r.split('?').next().unwrap_or_default().to_string())
+        .collect()
+}
+
+#[test]
+fn test_finished_season_is_marked_final_and_skipped() {
+    let server = serve_season(2019, 2);
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), &server, 2024);
+    
+    update_data_at(&config, &options_for(2019), date("2024-06-01")).unwrap();
+    assert_eq!(paths(&server), vec![
+        "/2019.json", "/2019/results.json", "/2019/qualifying.json", "/2019/sprint.json", "/2019/1/laps.json", "/2019/2/laps.json",
+        "/2019/1/pitstops.json", "/2019/2/pitstops.json",
+    ]);
+    
+    let manifest = Manifest::load(dir.path()).unwrap();
+    assert!(manifest.is_final(2019));
+    assert_eq!(manifest.session(2019, 2, "race").unwrap().rows, 2);
+    
+    // Nothing is fetched for a final season
+    update_data_at(&config, &options_for(2019), date("2024-06-01")).unwrap();
+    assert_eq!(server.requests().len(), 8);
+}
+
+#[test]
+fn test_force_refetches_final_season() {
+    let server = serve_season(2019, 2);
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), &server, 2024);
+    
+    update_data_at(&config, &options_for(2019), date("2024-06-01")).unwrap();
+    let forced = UpdateOptions { force: true, ..options_for(2019) };
+    update_data_at(&config, &forced, date("2024-06-01")).unwrap();
+    
+    assert_eq!(paths(&server)[8..], [
+        "/2019.json", "/2019/results.json", "/2019/qualifying.json", "/2019/sprint.json", "/2019/1/laps.json", "/2019/2/laps.json",
+        "/2019/1/pitstops.json", "/2019/2/pitstops.json",
+    ]);
+}
+
+#[test]
+fn test_ongoing_season_fetches_only_new_rounds() {
+    let server = serve_season(2024, 3);
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), &server, 2024);
+    
+    // Rounds 1 and 2 (March and April) have been run, round 3 (May) has not
+    update_data_at(&config, &options_for(2024), date("2024-04-15")).unwrap();
+    let manifest = Manifest::load(dir.path()).unwrap();
+    assert!(!manifest.is_final(2024));
+    assert!(manifest.session(2024, 2, "race").is_some());
+    assert!(manifest.session(2024, 3, "race").is_none());
+    
+    update_data_at(&config, &options_for(2024), date("2024-05-15")).unwrap();
+    assert_eq!(paths(&server)[8..], ["/2024.json", "/2024/3/results.json", "/2024/3/qualifying.json", "/2024/3/laps.json", "/2024/3/pitstops.json"]);
+    assert!(Manifest::load(dir.path()).unwrap().is_final(2024));
+}
+
+#[test]
+fn test_since_refetches_recent_rounds() {
+    let server = serve_season(2019, 3);
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), &server, 2019);
+    
+    update_data_at(&config, &options_for(2019), date("2019-12-01")).unwrap();
+    let requests_before = server.requests().len();
+    
+    // --since on its own selects the seasons from its year and reopens final ones
+    let since = UpdateOptions { since: Some(date("2019-04-15")), ..Default::default() };
+    update_data_at(&config, &since, date("2019-12-01")).unwrap();
+    
+    assert_eq!(paths(&server)[requests_before..], ["/2019.json", "/2019/3/results.json", "/2019/3/qualifying.json", "/2019/3/laps.json", "/2019/3/pitstops.json"]);
+}
+
+#[test]
+fn test_deleted_file_is_refetched() {
+    let server = serve_season(2019, 2);
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), &server, 2024);
+    
+    update_data_at(&config, &options_for(2019), date("2024-06-01")).unwrap();
+    std::fs::remove_file(dir.path().join("race_2019_r1.json")).unwrap();
+    update_data_at(&config, &options_for(2019), date("2024-06-01")).unwrap();
+    
+    assert!(dir.path().join("race_2019_r1.json").exists());
+    assert_eq!(paths(&server)[8..], ["/2019.json", "/2019/1/results.json"]);
+}
+
+#[test]
+fn test_final_season_fetches_sessions_it_lacks() {
+    let server = serve_season(2019, 2);
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), &server, 2024);
+    
+    update_data_at(&config, &options_for(2019), date("2024-06-01")).unwrap();
+    
+    // A season finalised before lap timings were downloaded by `update`
+    let mut manifest = Manifest::load(dir.path()).unwrap();
+    let season = manifest.seasons.get_mut(&2019).unwrap();
+    season.sessions.remove("laps");
+    for sessions in season.rounds.values_mut() {
+        sessions.remove("laps");
+    }
+    manifest.save(dir.path()).unwrap();
+    for round in 1..=2 {
+        std::fs::remove_file(dir.path().join(format!("laps_2019_r{}.json", round))).unwrap();
+    }
+    
+    update_data_at(&config, &options_for(2019), date("2024-06-01")).unwrap();
+    assert_eq!(paths(&server)[8..], ["/2019.json", "/2019/1/laps.json", "/2019/2/laps.json"]);
+    
+    let manifest = Manifest::load(dir.path()).unwrap();
+    assert!(manifest.is_final(2019));
+    assert_eq!(manifest.session(2019, 1, "laps").unwrap().rows, 2);
+}
+
+#[test]
+fn test_manifest_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let fetched_at = Utc.with_ymd_and_hms(2024, 3, 3, 18, 0, 0).unwrap();
+    
+    let mut manifest = Manifest::default();
+    manifest.record(2024, 1, "race", 20, fetched_at);
+    manifest.set_final(2023, true);
+    manifest.save(dir.path()).unwrap();
+    
+    let loaded = Manifest::load(dir.path()).unwrap();
+    let record = loaded.session(2024, 1, "race").unwrap();
+    assert_eq!(record.rows, 20);
+    assert_eq!(record.fetched_at, fetched_at);
+    assert!(loaded.is_final(2023));
+    assert!(!loaded.is_final(2024));
+}
+
+#[test]
+fn test_update_caches_every_session() {
+    let server = serve_season(2022, 2);
+    let dir = tempfile::tempdir().unwrap();
+    let config = config(dir.path(), &server, 2024);
+    
+    update_data_at(&config, &options_for(2022), date("2024-06-01")).unwrap();
+    
+    for file in ["season_2022.json", "race_2022_r1.json", "qualifying_2022_r1.json", "qualifying_2022_r2.json", "sprint_2022_r2.json", "laps_2022_r1.json", "pitstops_2022_r2.json"] {
+        assert!(dir.path().join(file).exists(), "{} was not cached", file);
+    }
+    assert!(!dir.path().join("sprint_2022_r1.json").exists());
+    
+    let manifest = Manifest::load(dir.path()).unwrap();
+    assert_eq!(manifest.session(2022, 2, "sprint").unwrap().rows, 2);
+    assert_eq!(manifest.session(2022, 1, "qualifying").unwrap().rows, 2);
+}
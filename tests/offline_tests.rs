@@ -0,0 +1,149 @@
+//! Tests for offline mode: cached data is served and nothing is fetched
+
+mod common;
+
+use common::{StubResponse, StubServer};
+use f1_cli_simulator::data::index::{round_file_name, save_index, SeasonIndex};
+use f1_cli_simulator::data::{update_data, DataConfig, UpdateOptions, DataError, DataInterface, DataManager};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+fn styrian_race() -> Value {
+    json!({
+        "season": "2020",
+        "round": "2",
+        "raceName": "Styrian Grand Prix",
+        "date": "2020-07-12",
+        "Circuit": {
+            "circuitId": "red_bull_ring",
+            "circuitName": "Red Bull Ring",
+            "Location": { "locality": "Spielberg", "country": "Austria" },
+        },
+        "Results": [{
+            "position": "1",
+            "points": "25",
+            "laps": "71",
+            "status": "Finished",
+            "Driver": { "driverId": "hamilton", "code": "HAM", "givenName": "Lewis", "familyName": "Hamilton" },
+            "Constructor": { "name": "Mercedes" },
+        }],
+    })
+}
+
+// Cache the 2020 schedule index and, optionally, the Styrian race results
+fn seed_cache(dir: &Path, with_results: bool) {
+    save_index(dir, &SeasonIndex::from_races(2020, &[styrian_race()])).unwrap();
+    if with_results {
+        let path = dir.join(round_file_name("race", 2020, 2));
+        fs::write(path, serde_json::to_string(&vec![styrian_race()]).unwrap()).unwrap();
+    }
+}
+
+// A data manager in offline mode pointed at a server that records every request
+fn offline_manager(dir: &Path) -> (DataManager, StubServer) {
+    let server = StubServer::start(|_| StubResponse::status(500));
+    let mut data_manager = DataManager::new(DataConfig::new(dir, &server.url));
+    data_manager.set_offline(true);
+    (data_manager, server)
+}
+
+fn not_cached(err: &anyhow::Error) -> &DataError {
+    err.downcast_ref::<DataError>().expect("expected a DataError")
+}
+
+#[test]
+fn test_cached_race_is_served_offline() {
+    let dir = tempfile::tempdir().unwrap();
+    seed_cache(dir.path(), true);
+    let (data_manager, server) = offline_manager(dir.path());
+    
+    let race = data_manager.load_race_data(2020, "styrian").unwrap();
+    
+    assert_eq!(race.round, 2);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_missing_schedule_is_not_cached_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let (data_manager, server) = offline_manager(dir.path());
+    
+    let err = data_manager.load_race_data(2020, "styrian").unwrap_err();
+    
+    assert_eq!(not_cached(&err), &DataError::not_cached("The 2020 schedule", 2020, "schedule"));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_missing_race_lists_update_command() {
+    let dir = tempfile::tempdir().unwrap();
+    seed_cache(dir.path(), false);
+    let (data_manager, server) = offline_manager(dir.path());
+    
+    let err = data_manager.load_race_data(2020, "styrian").unwrap_err();
+    
+    assert_eq!(not_cached(&err).update_command().as_deref(), Some("f1-cli-simulator update --seasons 2020"));
+    assert!(err.to_string().contains("`f1-cli-simulator update --seasons 2020`"));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_missing_sessions_are_not_cached_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    seed_cache(dir.path(), true);
+    let (data_manager, server) = offline_manager(dir.path());
+    
+    let qualifying = data_manager.load_qualifying_data(2020, "styrian").unwrap_err();
+    let practice = data_manager.load_practice_data(2020, "styrian", 2).unwrap_err();
+    
+    let laps = data_manager.load_lap_data(2020, "styrian").unwrap_err();
+    
+    assert!(matches!(not_cached(&qualifying), DataError::NotCached { season: 2020, .. }));
+    assert!(matches!(not_cached(&practice), DataError::NotCached { season: 2020, .. }));
+    assert_eq!(not_cached(&laps).update_command().as_deref(), Some("f1-cli-simulator update --seasons 2020"));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_sessions_update_skips_are_fetched_online() {
+    let dir = tempfile::tempdir().unwrap();
+    seed_cache(dir.path(), true);
+    let (data_manager, _server) = offline_manager(dir.path());
+    
+    // Practice is only fetched on demand, and lap timings only exist from 1996
+    let practice = data_manager.load_practice_data(2020, "styrian", 2).unwrap_err();
+    assert_eq!(not_cached(&practice), &DataError::not_cached("FP2 data for styrian GP 2020", 2020, "practice2"));
+    assert_eq!(not_cached(&practice).update_command(), None);
+    assert!(practice.to_string().contains("without --offline"), "{}", practice);
+    
+    let old_laps = DataError::not_cached("Lap data for monaco GP 1990", 1990, "laps");
+    assert_eq!(old_laps.update_command(), None);
+}
+
+#[test]
+fn test_unknown_gp_offline_does_not_fetch_schedule() {
+    let dir = tempfile::tempdir().unwrap();
+    seed_cache(dir.path(), true);
+    let (data_manager, server) = offline_manager(dir.path());
+    
+    assert!(data_manager.load_race_data(2020, "monza").is_err());
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_update_refuses_to_run_offline() {
+    let dir = tempfile::tempdir().unwrap();
+    let (data_manager, server) = offline_manager(dir.path());
+    
+    assert!(update_data(data_manager.config(), &UpdateOptions { seasons: Some("2020".to_string()), ..Default::default() }).is_err());
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_offline_from_environment() {
+    let env = |key: &str| (key == "F1_OFFLINE").then(|| "1".to_string());
+    
+    assert!(DataConfig::from_env(&env, None).unwrap().offline);
+    assert!(!DataConfig::from_env(&|_| None, None).unwrap().offline);
+}
@@ -0,0 +1,190 @@
+//! Tests for the safety car, virtual safety car and red flag phases of a race
+
+mod common;
+
+use common::{monza, params};
+use f1_cli_simulator::models::{Driver, RaceFormat, SimulationParameters};
+use f1_cli_simulator::ratings::Ratings;
+use f1_cli_simulator::simulator::simulation::phases::{self, Phase, PhasePeriod};
+use f1_cli_simulator::simulator::simulation::{self, Gap, RaceState};
+use f1_cli_simulator::utils;
+use std::time::Duration;
+
+// Three cars, further off the pace down the grid, that stay out under the safety car
+fn spread_field() -> RaceState {
+    let mut race = RaceState::from_grid(vec![(0, 1.0), (1, 0.9), (2, 0.8)], monza().laps);
+    for car in &mut race.cars {
+        car.strategy.pit_under_safety_car = false;
+    }
+    race
+}
+
+// Run laps until the race is green again
+fn run_until_green(race: &mut RaceState) {
+    let (params, circuit) = (params(), monza());
+    let mut rng = utils::seeded_rng(Some(1));
+    while race.phase != Phase::Green {
+        race.simulate_lap(&params, &circuit, &mut rng);
+    }
+}
+
+// Run a number of laps
+fn run_laps(race: &mut RaceState, laps: u32) {
+    let (params, circuit) = (params(), monza());
+    let mut rng = utils::seeded_rng(Some(2));
+    for _ in 0..laps {
+        race.simulate_lap(&params, &circuit, &mut rng);
+    }
+}
+
+#[test]
+fn test_phases_are_drawn_with_the_circuit_chances() {
+    let mut rng = utils::seeded_rng(Some(3));
+    let mut circuit = monza();
+    
+    circuit.red_flag_chance = 1.0;
+    assert_eq!(phases::draw_phase(true, &circuit, &mut rng), Phase::RedFlag);
+    
+    // A car stopped on track at most brings out the virtual safety car
+    circuit.virtual_safety_car_chance = 0.0;
+    assert_eq!(phases::draw_phase(false, &circuit, &mut rng), Phase::Green);
+    circuit.virtual_safety_car_chance = 1.0;
+    assert_eq!(phases::draw_phase(false, &circuit, &mut rng), Phase::VirtualSafetyCar);
+    
+    circuit.red_flag_chance = 0.0;
+    circuit.safety_car_chance = 1.0;
+    assert_eq!(phases::draw_phase(true, &circuit, &mut rng), Phase::SafetyCar);
+    
+    assert_eq!(Phase::RedFlag.draw_laps(&mut rng), 1);
+    assert!((3..=5).contains(&Phase::SafetyCar.draw_laps(&mut rng)));
+}
+
+#[test]
+fn test_more_serious_phases_take_over() {
+    let mut race = spread_field();
+    
+    race.deploy(Phase::VirtualSafetyCar, 2);
+    race.deploy(Phase::SafetyCar, 4);
+    assert_eq!((race.phase, race.phase_laps_left), (Phase::SafetyCar, 4));
+    
+    race.deploy(Phase::VirtualSafetyCar, 3);
+    race.deploy(Phase::SafetyCar, 3);
+    assert_eq!((race.phase, race.phase_laps_left), (Phase::SafetyCar, 4));
+    
+    race.deploy(Phase::RedFlag, 1);
+    assert_eq!((race.phase, race.phase_laps_left), (Phase::RedFlag, 1));
+}
+
+#[test]
+fn test_the_safety_car_bunches_up_the_field() {
+    let mut race = spread_field();
+    run_laps(&mut race, 15);
+    let order = race.running_order();
+    assert!(matches!(race.gap_to_leader(order[2]), Gap::Time(gap) if gap > Duration::from_secs(10)));
+    
+    race.deploy(Phase::SafetyCar, 3);
+    run_until_green(&mut race);
+    
+    assert_eq!(race.phases, vec![PhasePeriod { phase: Phase::SafetyCar, from_lap: 16, to_lap: 18 }]);
+    assert_eq!(race.running_order(), order, "no passing behind the safety car");
+    for &car in &order[1..] {
+        assert!(matches!(race.interval(car), Gap::Time(gap) if gap <= Duration::from_secs(1)), "{}", race.interval(car));
+    }
+    
+    // The leader laps well off the pace behind the safety car
+    let leader = &race.cars[order[0]];
+    assert!(leader.lap_time(17).unwrap() > leader.lap_time(15).unwrap().mul_f64(1.3));
+}
+
+#[test]
+fn test_the_virtual_safety_car_slows_everyone_alike() {
+    let mut race = spread_field();
+    run_laps(&mut race, 15);
+    let gaps: Vec<Gap> = (0..3).map(|car| race.gap_to_leader(car)).collect();
+    
+    race.deploy(Phase::VirtualSafetyCar, 2);
+    run_until_green(&mut race);
+    
+    assert_eq!(race.phases[0].phase, Phase::VirtualSafetyCar);
+    assert_eq!((0..3).map(|car| race.gap_to_leader(car)).collect::<Vec<_>>(), gaps);
+}
+
+#[test]
+fn test_lapped_cars_unlap_behind_the_safety_car() {
+    let mut race = RaceState::from_grid(vec![(0, 1.0), (1, 0.0)], monza().laps);
+    while !matches!(race.gap_to_leader(1), Gap::Laps(_)) {
+        run_laps(&mut race, 1);
+    }
+    assert_eq!(race.gap_to_leader(1), Gap::Laps(1));
+    
+    race.deploy(Phase::SafetyCar, 3);
+    run_until_green(&mut race);
+    
+    // The backmarker goes round to rejoin behind the field on the lead lap
+    assert_eq!(race.cars[1].laps(), race.cars[0].laps());
+    assert_eq!(race.running_order(), vec![0, 1]);
+    assert!(matches!(race.gap_to_leader(1), Gap::Time(_)));
+}
+
+#[test]
+fn test_pit_stops_are_cheaper_under_the_safety_car() {
+    let circuit = monza();
+    let mut race = RaceState::from_grid(vec![(0, 1.0), (1, 0.95), (2, 0.9)], circuit.laps);
+    run_laps(&mut race, 14);
+    
+    // Every car is within reach of its stop and takes it under the safety car
+    race.deploy(Phase::SafetyCar, 3);
+    run_until_green(&mut race);
+    
+    for car in &race.cars {
+        let stop = car.stints[1];
+        assert!((15..=17).contains(&stop.from_lap), "stopped on lap {}", stop.from_lap);
+        assert!(stop.pit_lane_time.unwrap().as_secs_f64() < circuit.pit_lane_loss_secs * 0.7);
+    }
+}
+
+#[test]
+fn test_a_red_flag_gives_new_tyres_and_a_standing_restart() {
+    let mut race = spread_field();
+    run_laps(&mut race, 12);
+    
+    race.deploy(Phase::RedFlag, 1);
+    run_laps(&mut race, 1);
+    assert_eq!(race.phase, Phase::Green);
+    assert_eq!(race.phases, vec![PhasePeriod { phase: Phase::RedFlag, from_lap: 13, to_lap: 13 }]);
+    
+    // The field lines up on the grid in race order, each on a new set fitted for free
+    let order = race.running_order();
+    for (slot, &i) in order.iter().enumerate() {
+        let car = &race.cars[i];
+        let stint = car.stints.last().unwrap();
+        assert_eq!((stint.from_lap, stint.pit_lane_time), (13, None));
+        assert_eq!(car.race_time() - race.cars[order[0]].race_time(), Duration::from_millis(250) * slot as u32);
+    }
+    
+    // The restart is slower than a flying lap
+    run_laps(&mut race, 2);
+    let leader = &race.cars[order[0]];
+    assert!(leader.lap_time(14).unwrap() > leader.lap_time(15).unwrap() + Duration::from_secs(1));
+}
+
+#[test]
+fn test_retirements_bring_out_the_circuit_phases() {
+    let drivers: Vec<Driver> = (1..=3u32).map(|i| Driver {
+        id: format!("d{}", i),
+        code: format!("D0{}", i),
+        name: format!("Driver {}", i),
+        team: format!("Team {}", i),
+        number: i,
+    }).collect();
+    let mut circuit = monza();
+    (circuit.red_flag_chance, circuit.safety_car_chance, circuit.virtual_safety_car_chance) = (0.0, 0.0, 1.0);
+    let params = SimulationParameters { reliability_factor: 0.001, ..params() };
+    let mut rng = utils::seeded_rng(Some(4));
+    
+    let mut race = RaceState::new(&drivers, &Ratings::new(), &params, &circuit, RaceFormat::GrandPrix, &mut rng);
+    while simulation::check_for_incidents(&drivers, &mut race, &params, &circuit, &mut rng).is_empty() {}
+    
+    assert_eq!(race.phase, Phase::VirtualSafetyCar);
+    assert!((1..=3).contains(&race.phase_laps_left));
+}
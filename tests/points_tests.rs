@@ -0,0 +1,84 @@
+//! Tests for the era-aware points systems
+
+use f1_cli_simulator::models::RaceFormat;
+use f1_cli_simulator::points::{PointsSystem, ShortRaceRule};
+
+// Grand prix points for the first `n` positions over a full race distance
+fn race_table(season: u32, n: u32) -> Vec<f64> {
+    let system = PointsSystem::for_season(season);
+    (1..=n).map(|pos| system.points_for(RaceFormat::GrandPrix, pos)).collect()
+}
+
+#[test]
+fn test_race_tables_follow_the_era() {
+    assert_eq!(race_table(1950, 6), vec![8.0, 6.0, 4.0, 3.0, 2.0, 0.0]);
+    assert_eq!(race_table(1975, 7), vec![9.0, 6.0, 4.0, 3.0, 2.0, 1.0, 0.0]);
+    assert_eq!(race_table(1991, 7), vec![10.0, 6.0, 4.0, 3.0, 2.0, 1.0, 0.0]);
+    assert_eq!(race_table(2003, 9), vec![10.0, 8.0, 6.0, 5.0, 4.0, 3.0, 2.0, 1.0, 0.0]);
+    assert_eq!(race_table(2010, 11), vec![25.0, 18.0, 15.0, 12.0, 10.0, 8.0, 6.0, 4.0, 2.0, 1.0, 0.0]);
+    
+    assert_eq!(PointsSystem::for_season(2024).points_for(RaceFormat::GrandPrix, 0), 0.0);
+}
+
+#[test]
+fn test_sprint_tables() {
+    let sprint = |season: u32| -> Vec<f64> {
+        let system = PointsSystem::for_season(season);
+        (1..=9).map(|pos| system.points_for(RaceFormat::Sprint, pos)).collect()
+    };
+    
+    assert_eq!(sprint(2020), vec![0.0; 9]);
+    assert_eq!(sprint(2021), vec![3.0, 2.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+    assert_eq!(sprint(2023), vec![8.0, 7.0, 6.0, 5.0, 4.0, 3.0, 2.0, 1.0, 0.0]);
+    assert_eq!(PointsSystem::for_season(2023).scoring_positions(RaceFormat::Sprint), 8);
+}
+
+#[test]
+fn test_fastest_lap_bonus_depends_on_season_and_position() {
+    let hybrid = PointsSystem::for_season(2021);
+    assert_eq!(hybrid.score(RaceFormat::GrandPrix, 1, true, 1.0), 26.0);
+    assert_eq!(hybrid.score(RaceFormat::GrandPrix, 10, true, 1.0), 2.0);
+    assert_eq!(hybrid.score(RaceFormat::GrandPrix, 11, true, 1.0), 0.0);
+    assert_eq!(hybrid.score(RaceFormat::Sprint, 1, true, 1.0), 3.0);
+    
+    // No bonus before 2019 (outside the 1950s) or from 2025
+    assert_eq!(PointsSystem::for_season(2018).score(RaceFormat::GrandPrix, 1, true, 1.0), 25.0);
+    assert_eq!(PointsSystem::for_season(2025).score(RaceFormat::GrandPrix, 1, true, 1.0), 25.0);
+    
+    // In the 1950s the fastest lap scored from any position
+    assert_eq!(PointsSystem::for_season(1950).score(RaceFormat::GrandPrix, 12, true, 1.0), 1.0);
+}
+
+#[test]
+fn test_short_races_score_half_points_until_2021() {
+    let system = PointsSystem::for_season(2021);
+    assert_eq!(system.short_race, ShortRaceRule::HalfPoints);
+    
+    // Spa 2021: one lap behind the safety car, no fastest lap bonus
+    assert_eq!(system.score(RaceFormat::GrandPrix, 1, true, 0.05), 12.5);
+    assert_eq!(system.score(RaceFormat::GrandPrix, 2, false, 0.05), 9.0);
+    assert_eq!(system.score(RaceFormat::GrandPrix, 1, false, 0.0), 0.0);
+    assert_eq!(system.score(RaceFormat::GrandPrix, 1, false, 0.75), 25.0);
+}
+
+#[test]
+fn test_short_races_use_scaled_tables_from_2022() {
+    let system = PointsSystem::for_season(2022);
+    assert_eq!(system.short_race, ShortRaceRule::Scaled);
+    
+    assert_eq!(system.score(RaceFormat::GrandPrix, 1, false, 0.2), 6.0);
+    assert_eq!(system.score(RaceFormat::GrandPrix, 6, false, 0.2), 0.0);
+    assert_eq!(system.score(RaceFormat::GrandPrix, 1, false, 0.4), 13.0);
+    assert_eq!(system.score(RaceFormat::GrandPrix, 1, false, 0.6), 19.0);
+    assert_eq!(system.score(RaceFormat::GrandPrix, 10, false, 0.6), 1.0);
+    
+    // A shortened sprint still scores in full
+    assert_eq!(system.score(RaceFormat::Sprint, 1, false, 0.4), 8.0);
+}
+
+#[test]
+fn test_shared_drives_split_points_in_the_1950s() {
+    assert_eq!(PointsSystem::for_season(1951).shared_drive(8.0, 2), 4.0);
+    assert_eq!(PointsSystem::for_season(1951).shared_drive(8.0, 1), 8.0);
+    assert_eq!(PointsSystem::for_season(1960).shared_drive(8.0, 2), 8.0);
+}
@@ -0,0 +1,209 @@
+//! Tests for fitting driver and constructor ratings from results
+
+use f1_cli_simulator::data::index::{round_file_name, save_index, SeasonIndex};
+use f1_cli_simulator::data::{load_ratings, DataConfig};
+use f1_cli_simulator::models::{Circuit, Driver, QualifyingResult, Race, RaceResult, WeekendResults};
+use f1_cli_simulator::ratings::Ratings;
+use serde_json::json;
+use std::fs;
+
+fn driver(id: &str, team: &str) -> Driver {
+    Driver {
+        id: id.to_string(),
+        code: id[..3].to_uppercase(),
+        name: format!("Test {}", id),
+        team: team.to_string(),
+        number: 0,
+    }
+}
+
+// A weekend the drivers finished in the order given, qualifying in the same order
+fn weekend(season: u32, round: u32, order: &[&Driver]) -> WeekendResults {
+    let results = order.iter().enumerate().map(|(i, &driver)| RaceResult {
+        position: (i + 1) as u32,
+        driver: driver.clone(),
+        number: 0,
+        constructor_id: driver.team.to_lowercase(),
+        grid: (i + 1) as u32,
+        time: None,
+        time_millis: None,
+        points: 0.0,
+        laps: 50,
+        status: "Finished".to_string(),
+        fastest_lap: None,
+    }).collect();
+    let qualifying = order.iter().enumerate().map(|(i, &driver)| QualifyingResult {
+        position: (i + 1) as u32,
+        driver: driver.clone(),
+        q1: None,
+        q2: None,
+        q3: None,
+    }).collect();
+    
+    WeekendResults {
+        race: Race {
+            season,
+            round,
+            name: format!("Round {}", round),
+            circuit: Circuit {
+                id: "test".to_string(),
+                name: "Test Circuit".to_string(),
+                country: "Test Country".to_string(),
+                city: "Test City".to_string(),
+                length_km: 5.0,
+                laps: 50,
+                ..Default::default()
+            },
+            date: format!("{}-01-01", season),
+            results,
+        },
+        qualifying,
+        sprint: Vec::new(),
+    }
+}
+
+#[test]
+fn test_winning_raises_a_rating_at_the_expense_of_the_field() {
+    let (alpha, bravo, charlie) = (driver("alpha", "Red"), driver("bravo", "Blue"), driver("charlie", "Green"));
+    
+    let mut ratings = Ratings::new();
+    ratings.rate_session(2024, &[&alpha, &bravo, &charlie], 1.0);
+    
+    assert!(ratings.driver_rating(&alpha) > ratings.driver_rating(&bravo));
+    assert!(ratings.driver_rating(&bravo) > ratings.driver_rating(&charlie));
+    assert!(ratings.constructor_rating("Red") > 0.0);
+    assert!(ratings.constructor_rating("Green") < 0.0);
+    assert!(ratings.driver_skill(&alpha) > ratings.driver_skill(&charlie));
+    assert!(ratings.team_performance("Red") > ratings.team_performance("Green"));
+}
+
+#[test]
+fn test_teammate_battles_only_move_driver_ratings() {
+    let (alpha, bravo) = (driver("alpha", "Red"), driver("bravo", "Red"));
+    
+    let mut ratings = Ratings::new();
+    ratings.rate_session(2024, &[&alpha, &bravo], 1.0);
+    
+    assert!(ratings.driver_rating(&alpha) > ratings.driver_rating(&bravo));
+    assert_eq!(ratings.constructor_rating("Red"), 0.0);
+}
+
+#[test]
+fn test_ratings_regress_between_seasons() {
+    let (alpha, bravo, charlie, delta) = (driver("alpha", "Red"), driver("bravo", "Blue"), driver("charlie", "Red"), driver("delta", "Blue"));
+    
+    let mut ratings = Ratings::new();
+    for _ in 0..5 {
+        ratings.rate_session(2023, &[&alpha, &bravo], 1.0);
+    }
+    let before = ratings.driver_rating(&alpha);
+    
+    // A session without alpha in the next season only applies the decay to alpha
+    ratings.rate_session(2024, &[&charlie, &delta], 1.0);
+    
+    assert!((ratings.driver_rating(&alpha) - before * 0.7).abs() < 1e-9);
+}
+
+#[test]
+fn test_unknown_drivers_and_renamed_teams() {
+    let alpha = driver("alpha", "Red Bull");
+    let bravo = driver("bravo", "Haas F1 Team");
+    
+    let mut ratings = Ratings::new();
+    for _ in 0..5 {
+        ratings.rate_session(2024, &[&alpha, &bravo], 1.0);
+    }
+    
+    // A rookie is rated a little below the average driver
+    let rookie = driver("rookie", "Williams");
+    assert!(ratings.driver_rating(&rookie) < 0.0);
+    assert!(ratings.driver_rating(&rookie) > ratings.driver_rating(&bravo));
+    
+    // The same driver known by code and name under another id
+    let roster_alpha = Driver { id: "alp".to_string(), ..alpha.clone() };
+    assert_eq!(ratings.driver_rating(&roster_alpha), ratings.driver_rating(&alpha));
+    
+    // Team names with a suffix match the rated team
+    assert_eq!(ratings.constructor_rating("Red Bull Racing"), ratings.constructor_rating("Red Bull"));
+    assert_eq!(ratings.constructor_rating("Haas"), ratings.constructor_rating("Haas F1 Team"));
+    assert_eq!(ratings.constructor_rating("Williams"), 0.0);
+}
+
+#[test]
+fn test_fit_ignores_unclassified_finishers() {
+    let (alpha, bravo, charlie) = (driver("alpha", "Red"), driver("bravo", "Blue"), driver("charlie", "Green"));
+    
+    let mut retired = weekend(2024, 1, &[&alpha, &bravo, &charlie]);
+    retired.qualifying.clear();
+    retired.race.results[0].status = "Engine".to_string();
+    retired.race.results[0].laps = 3;
+    
+    let ratings = Ratings::fit(&[retired]);
+    
+    // Alpha's retirement is not held against them
+    assert_eq!(ratings.driver_rating(&alpha), -50.0);
+    assert!(ratings.driver_rating(&bravo) > ratings.driver_rating(&charlie));
+}
+
+#[test]
+fn test_fit_counts_qualifying_and_races_in_date_order() {
+    let (alpha, bravo) = (driver("alpha", "Red"), driver("bravo", "Blue"));
+    
+    let weekends = vec![
+        weekend(2024, 2, &[&bravo, &alpha]),
+        weekend(2024, 1, &[&alpha, &bravo]),
+        weekend(2023, 9, &[&alpha, &bravo]),
+    ];
+    let ratings = Ratings::fit(&weekends);
+    
+    assert!(!ratings.is_empty());
+    assert!(ratings.driver_rating(&alpha) > ratings.driver_rating(&bravo));
+}
+
+#[test]
+fn test_ratings_are_loaded_from_cached_seasons() {
+    let dir = tempfile::tempdir().unwrap();
+    let race = json!({
+        "season": "2024",
+        "round": "1",
+        "raceName": "Bahrain Grand Prix",
+        "date": "2024-03-02",
+        "Circuit": { "circuitId": "bahrain", "circuitName": "Bahrain", "Location": { "locality": "Sakhir", "country": "Bahrain" } },
+        "Results": [
+            {
+                "position": "1", "points": "25", "laps": "57", "status": "Finished",
+                "Driver": { "driverId": "max_verstappen", "code": "VER", "givenName": "Max", "familyName": "Verstappen" },
+                "Constructor": { "constructorId": "red_bull", "name": "Red Bull" },
+            },
+            {
+                "position": "2", "points": "18", "laps": "57", "status": "Finished",
+                "Driver": { "driverId": "perez", "code": "PER", "givenName": "Sergio", "familyName": "Pérez" },
+                "Constructor": { "constructorId": "red_bull", "name": "Red Bull" },
+            },
+        ],
+    });
+    save_index(dir.path(), &SeasonIndex::from_races(2024, std::slice::from_ref(&race))).unwrap();
+    fs::write(dir.path().join(round_file_name("race", 2024, 1)), json!([race]).to_string()).unwrap();
+    
+    let config = DataConfig::new(dir.path(), "http://127.0.0.1:9");
+    
+    // 2024 is among the seasons leading up to 2026
+    let ratings = load_ratings(&config, 2026).unwrap();
+    let roster_verstappen = Driver {
+        id: "ver".to_string(),
+        code: "VER".to_string(),
+        name: "Max Verstappen".to_string(),
+        team: "Red Bull Racing".to_string(),
+        number: 1,
+    };
+    let roster_perez = Driver {
+        id: "per".to_string(),
+        code: "PER".to_string(),
+        name: "Sergio Perez".to_string(),
+        team: "Red Bull Racing".to_string(),
+        number: 11,
+    };
+    assert!(ratings.driver_rating(&roster_verstappen) > ratings.driver_rating(&roster_perez));
+    
+    assert!(load_ratings(&config, 2027).unwrap().is_empty());
+}
@@ -0,0 +1,115 @@
+//! Tests for parsing the detail fields of cached race results and pit stops
+
+use f1_cli_simulator::data::index::{round_file_name, save_index, SeasonIndex};
+use f1_cli_simulator::data::{DataConfig, DataInterface, DataManager};
+use f1_cli_simulator::models::FastestLap;
+use serde_json::{json, Value};
+use std::fs;
+
+fn monza_race() -> Value {
+    json!({
+        "season": "2021",
+        "round": "14",
+        "raceName": "Italian Grand Prix",
+        "date": "2021-09-12",
+        "Circuit": {
+            "circuitId": "monza",
+            "circuitName": "Autodromo Nazionale di Monza",
+            "Location": { "locality": "Monza", "country": "Italy" },
+        },
+        "Results": [
+            {
+                "number": "3",
+                "position": "1",
+                "points": "25",
+                "grid": "2",
+                "laps": "53",
+                "status": "Finished",
+                "Driver": { "driverId": "ricciardo", "permanentNumber": "3", "code": "RIC", "givenName": "Daniel", "familyName": "Ricciardo" },
+                "Constructor": { "constructorId": "mclaren", "name": "McLaren" },
+                "Time": { "millis": "4860071", "time": "1:21:00.071" },
+                "FastestLap": {
+                    "rank": "1",
+                    "lap": "53",
+                    "Time": { "time": "1:24.812" },
+                    "AverageSpeed": { "units": "kph", "speed": "245.886" },
+                },
+            },
+            {
+                "number": "11",
+                "position": "5",
+                "points": "10",
+                "grid": "0",
+                "laps": "53",
+                "status": "Finished",
+                "Driver": { "driverId": "perez", "permanentNumber": "11", "code": "PER", "givenName": "Sergio", "familyName": "Pérez" },
+                "Constructor": { "constructorId": "red_bull", "name": "Red Bull" },
+                "Time": { "millis": "4870271", "time": "+10.200" },
+            },
+        ],
+    })
+}
+
+// A data manager reading the Italian GP from a seeded cache
+fn cached_manager(dir: &std::path::Path) -> DataManager {
+    save_index(dir, &SeasonIndex::from_races(2021, &[monza_race()])).unwrap();
+    let path = dir.join(round_file_name("race", 2021, 14));
+    fs::write(path, serde_json::to_string(&vec![monza_race()]).unwrap()).unwrap();
+    
+    let mut data_manager = DataManager::new(DataConfig::new(dir, "http://127.0.0.1:9"));
+    data_manager.set_offline(true);
+    data_manager
+}
+
+#[test]
+fn test_race_results_keep_grid_number_and_constructor() {
+    let dir = tempfile::tempdir().unwrap();
+    let race = cached_manager(dir.path()).load_race_data(2021, "monza").unwrap();
+    
+    let winner = &race.results[0];
+    assert_eq!(winner.number, 3);
+    assert_eq!(winner.grid, 2);
+    assert_eq!(winner.constructor_id, "mclaren");
+    assert_eq!(winner.time_millis, Some(4_860_071));
+    
+    // Pérez started from the pit lane
+    assert_eq!(race.results[1].grid, 0);
+    assert_eq!(race.results[1].constructor_id, "red_bull");
+}
+
+#[test]
+fn test_race_results_keep_fastest_lap() {
+    let dir = tempfile::tempdir().unwrap();
+    let race = cached_manager(dir.path()).load_race_data(2021, "monza").unwrap();
+    
+    assert_eq!(race.results[0].fastest_lap, Some(FastestLap {
+        rank: 1,
+        lap: 53,
+        time: "1:24.812".to_string(),
+        average_speed_kph: Some(245.886),
+    }));
+    assert_eq!(race.results[1].fastest_lap, None);
+}
+
+#[test]
+fn test_pit_stops_are_parsed_in_race_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let data_manager = cached_manager(dir.path());
+    let pit_stops = json!([{
+        "season": "2021",
+        "round": "14",
+        "PitStops": [
+            { "driverId": "perez", "lap": "23", "stop": "1", "time": "15:41:02", "duration": "23.114" },
+            { "driverId": "ricciardo", "lap": "22", "stop": "1", "time": "15:39:40", "duration": "22.809" },
+        ],
+    }]);
+    fs::write(dir.path().join(round_file_name("pitstops", 2021, 14)), pit_stops.to_string()).unwrap();
+    
+    let stops = data_manager.load_pit_stops(2021, "monza").unwrap();
+    
+    assert_eq!(stops.len(), 2);
+    assert_eq!(stops[0].driver_id, "ricciardo");
+    assert_eq!(stops[0].lap, 22);
+    assert_eq!(stops[0].duration.as_deref(), Some("22.809"));
+    assert_eq!(stops[1].stop, 1);
+}
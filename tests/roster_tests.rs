@@ -0,0 +1,142 @@
+//! Tests for deriving the simulated field from race data and roster files
+
+mod common;
+
+use common::{StubResponse, StubServer};
+use f1_cli_simulator::data::index::{round_file_name, save_index, SeasonIndex};
+use f1_cli_simulator::data::{load_roster, read_roster_file, DataConfig};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+// A race result row for a driver and team
+fn result(position: u32, code: &str, given: &str, family: &str, team: &str) -> Value {
+    json!({
+        "position": position.to_string(),
+        "points": "0",
+        "laps": "57",
+        "status": "Finished",
+        "Driver": { "driverId": family.to_lowercase(), "permanentNumber": position.to_string(), "code": code, "givenName": given, "familyName": family },
+        "Constructor": { "constructorId": team.to_lowercase().replace(' ', "_"), "name": team },
+    })
+}
+
+// Cache the races of a season with the results given
+fn cache_season(dir: &Path, season: u32, rounds: &[Vec<Value>]) {
+    let races: Vec<Value> = rounds.iter().enumerate().map(|(i, results)| json!({
+        "season": season.to_string(),
+        "round": (i + 1).to_string(),
+        "raceName": format!("Round {} Grand Prix", i + 1),
+        "date": format!("{}-04-{:02}", season, i + 1),
+        "Circuit": { "circuitId": "bahrain", "circuitName": "Bahrain", "Location": { "locality": "Sakhir", "country": "Bahrain" } },
+        "Results": results,
+    })).collect();
+    
+    save_index(dir, &SeasonIndex::from_races(season, &races)).unwrap();
+    for (i, race) in races.iter().enumerate() {
+        fs::write(dir.join(round_file_name("race", season, i as u32 + 1)), json!([race]).to_string()).unwrap();
+    }
+}
+
+#[test]
+fn test_roster_file_takes_precedence() {
+    let dir = tempfile::tempdir().unwrap();
+    cache_season(dir.path(), 2024, &[vec![result(1, "VER", "Max", "Verstappen", "Red Bull")]]);
+    let roster_path = dir.path().join("roster.json");
+    fs::write(&roster_path, r#"[
+        { "code": "ANT", "name": "Kimi Antonelli", "team": "Mercedes", "number": 12 },
+        { "id": "russell", "code": "RUS", "name": "George Russell", "team": "Mercedes" }
+    ]"#).unwrap();
+    
+    let mut config = DataConfig::new(dir.path(), "http://127.0.0.1:9");
+    config.offline = true;
+    config.roster = Some(roster_path.clone());
+    
+    let drivers = load_roster(&config, 2024, None).unwrap();
+    
+    assert_eq!(drivers.len(), 2);
+    assert_eq!(drivers[0].id, "ant");
+    assert_eq!(drivers[0].number, 12);
+    assert_eq!(drivers[1].id, "russell");
+    assert_eq!(drivers[1].number, 0);
+    
+    fs::write(&roster_path, "[]").unwrap();
+    assert!(read_roster_file(&roster_path).is_err());
+}
+
+#[test]
+fn test_roster_follows_the_latest_cached_race() {
+    let dir = tempfile::tempdir().unwrap();
+    cache_season(dir.path(), 2024, &[
+        vec![result(1, "VER", "Max", "Verstappen", "Red Bull"), result(2, "SAI", "Carlos", "Sainz", "Ferrari")],
+        vec![result(1, "VER", "Max", "Verstappen", "Red Bull"), result(2, "BEA", "Oliver", "Bearman", "Ferrari")],
+    ]);
+    
+    let mut config = DataConfig::new(dir.path(), "http://127.0.0.1:9");
+    config.offline = true;
+    
+    // A substitute in the second round replaces the regular driver
+    let latest = load_roster(&config, 2024, None).unwrap();
+    let codes: Vec<_> = latest.iter().map(|d| d.code.as_str()).collect();
+    assert_eq!(codes, vec!["VER", "BEA"]);
+    assert_eq!(latest[1].team, "Ferrari");
+    
+    // Simulating the second round uses the field of the first
+    let before_round_two = load_roster(&config, 2024, Some(2)).unwrap();
+    assert_eq!(before_round_two[1].code, "SAI");
+}
+
+#[test]
+fn test_roster_falls_back_to_the_previous_season() {
+    let dir = tempfile::tempdir().unwrap();
+    cache_season(dir.path(), 2024, &[vec![result(1, "NOR", "Lando", "Norris", "McLaren")]]);
+    
+    let mut config = DataConfig::new(dir.path(), "http://127.0.0.1:9");
+    config.offline = true;
+    
+    let drivers = load_roster(&config, 2025, None).unwrap();
+    assert_eq!(drivers[0].name, "Lando Norris");
+    
+    // Nothing cached for the season or the one before
+    assert!(load_roster(&config, 2030, None).is_err());
+}
+
+#[test]
+fn test_entry_list_is_fetched_before_the_season_starts() {
+    let server = StubServer::start(|target| {
+        let path = target.split('?').next().unwrap_or_default();
+        let table = |name: &str, list: &str, rows: Value| StubResponse::json(json!({
+            "MRData": { "limit": "100", "offset": "0", "total": rows.as_array().unwrap().len().to_string(), name: { list: rows } }
+        }).to_string());
+        
+        match path {
+            "/2026/constructors.json" => table("ConstructorTable", "Constructors", json!([
+                { "constructorId": "cadillac", "name": "Cadillac" },
+            ])),
+            "/2026/constructors/cadillac/drivers.json" => table("DriverTable", "Drivers", json!([
+                { "driverId": "perez", "permanentNumber": "11", "code": "PER", "givenName": "Sergio", "familyName": "Pérez" },
+                { "driverId": "bottas", "permanentNumber": "77", "code": "BOT", "givenName": "Valtteri", "familyName": "Bottas" },
+            ])),
+            "/2026.json" => StubResponse::json(json!({
+                "MRData": { "limit": "100", "offset": "0", "total": "0", "RaceTable": { "Races": [] } }
+            }).to_string()),
+            _ => StubResponse::status(404),
+        }
+    });
+    
+    let dir = tempfile::tempdir().unwrap();
+    let config = DataConfig::new(dir.path(), &server.url);
+    
+    let drivers = load_roster(&config, 2026, None).unwrap();
+    
+    assert_eq!(drivers.len(), 2);
+    assert_eq!(drivers[0].code, "PER");
+    assert_eq!(drivers[0].number, 11);
+    assert_eq!(drivers[1].team, "Cadillac");
+    assert!(dir.path().join("roster_2026.json").exists());
+    
+    // The cached entry list is used from then on
+    let requests_before = server.requests().len();
+    load_roster(&config, 2026, None).unwrap();
+    assert_eq!(server.requests().len(), requests_before);
+}
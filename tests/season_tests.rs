@@ -0,0 +1,156 @@
+//! Tests for simulating the rest of a championship season
+
+use f1_cli_simulator::circuits::CircuitCatalogue;
+use f1_cli_simulator::data::index::SeasonIndex;
+use f1_cli_simulator::models::{Circuit, Driver, Race, RaceResult, SimulationParameters, WeekendResults};
+use f1_cli_simulator::points::PointsSystem;
+use f1_cli_simulator::ratings::Ratings;
+use f1_cli_simulator::simulator::season::project_season;
+use f1_cli_simulator::utils;
+use serde_json::json;
+
+// Twenty drivers in ten teams
+fn roster() -> Vec<Driver> {
+    (1..=20u32).map(|i| Driver {
+        id: format!("d{:02}", i),
+        code: format!("D{:02}", i),
+        name: format!("Driver {}", i),
+        team: format!("Team {}", i.div_ceil(2)),
+        number: i,
+    }).collect()
+}
+
+// A calendar of `rounds` rounds at Monza, with a sprint at the rounds listed
+fn schedule(rounds: u32, sprints: &[u32]) -> SeasonIndex {
+    let races: Vec<_> = (1..=rounds).map(|round| {
+        let mut race = json!({
+            "season": "2026",
+            "round": round.to_string(),
+            "raceName": format!("Round {} Grand Prix", round),
+            "date": format!("2026-05-{:02}", round),
+            "Circuit": { "circuitId": "monza", "circuitName": "Monza", "Location": { "locality": "Monza", "country": "Italy" } },
+        });
+        if sprints.contains(&round) {
+            race["Sprint"] = json!({ "date": format!("2026-05-{:02}", round) });
+        }
+        race
+    }).collect();
+    SeasonIndex::from_races(2026, &races)
+}
+
+// A cached round the roster finished in order, with the drivers known by
+// their full ids as the results feed lists them
+fn completed_round(round: u32, drivers: &[Driver]) -> WeekendResults {
+    let points = PointsSystem::for_season(2026);
+    let results = drivers.iter().enumerate().map(|(i, driver)| RaceResult {
+        position: (i + 1) as u32,
+        driver: Driver {
+            id: driver.name.to_lowercase().replace(' ', "_"),
+            ..driver.clone()
+        },
+        number: driver.number,
+        constructor_id: driver.team.to_lowercase(),
+        grid: (i + 1) as u32,
+        time: None,
+        time_millis: None,
+        points: points.race.get(i).copied().unwrap_or(0.0),
+        laps: 53,
+        status: "Finished".to_string(),
+        fastest_lap: None,
+    }).collect();
+    
+    WeekendResults {
+        race: Race {
+            season: 2026,
+            round,
+            name: format!("Round {} Grand Prix", round),
+            circuit: Circuit {
+                id: "monza".to_string(),
+                name: "Monza".to_string(),
+                country: "Italy".to_string(),
+                city: "Monza".to_string(),
+                length_km: 5.793,
+                laps: 53,
+                ..Default::default()
+            },
+            date: format!("2026-05-{:02}", round),
+            results,
+        },
+        qualifying: Vec::new(),
+        sprint: Vec::new(),
+    }
+}
+
+#[test]
+fn test_a_finished_season_is_certain() {
+    let drivers = roster();
+    let completed: Vec<_> = (1..=2).map(|round| completed_round(round, &drivers)).collect();
+    
+    let projection = project_season(&schedule(2, &[]), &completed, &drivers, 5,
+        &SimulationParameters::default(), &PointsSystem::for_season(2026), &Ratings::fit(&completed), &CircuitCatalogue::bundled(), &mut utils::seeded_rng(Some(1)), || {});
+    
+    assert_eq!(projection.completed_round, 2);
+    assert_eq!(projection.remaining_rounds, 0);
+    
+    let champion = &projection.drivers[0];
+    assert_eq!(champion.name, drivers[0].name);
+    assert_eq!(champion.title_probability(), 1.0);
+    assert_eq!(champion.current_points, 50.0);
+    assert_eq!(champion.expected_points, 50.0);
+    assert_eq!(projection.constructors[0].title_probability(), 1.0);
+}
+
+#[test]
+fn test_remaining_rounds_add_to_the_cached_points() {
+    let drivers = roster();
+    let completed = vec![completed_round(1, &drivers)];
+    let runs = 20;
+    
+    let mut seasons = 0;
+    let projection = project_season(&schedule(4, &[3]), &completed, &drivers, runs,
+        &SimulationParameters::default(), &PointsSystem::for_season(2026), &Ratings::fit(&completed), &CircuitCatalogue::bundled(), &mut utils::seeded_rng(Some(2)), || seasons += 1);
+    
+    assert_eq!(seasons, runs);
+    assert_eq!(projection.completed_round, 1);
+    assert_eq!(projection.remaining_rounds, 3);
+    
+    // Simulated points are credited to the drivers as the cached results know them
+    assert_eq!(projection.drivers.len(), drivers.len());
+    assert_eq!(projection.constructors.len(), 10);
+    
+    for odds in &projection.drivers {
+        assert_eq!(odds.positions.iter().sum::<u32>(), runs);
+        assert!(odds.expected_points >= odds.current_points);
+    }
+    
+    let titles: f64 = projection.drivers.iter().map(|o| o.title_probability()).sum();
+    assert!((titles - 1.0).abs() < 1e-9);
+    
+    // Three grand prix and a sprint hand out 3 * 101 + 36 points per season
+    let total: f64 = projection.drivers.iter().map(|o| o.expected_points - o.current_points).sum();
+    assert!(total <= 3.0 * 101.0 + 36.0 + 1e-9, "{} points awarded", total);
+}
+
+#[test]
+fn test_an_unassailable_lead_wins_every_title() {
+    let drivers = roster();
+    let completed: Vec<_> = (1..=4).map(|round| completed_round(round, &drivers)).collect();
+    
+    // 28 points clear of second with a single round to go
+    let projection = project_season(&schedule(5, &[]), &completed, &drivers, 10,
+        &SimulationParameters::default(), &PointsSystem::for_season(2026), &Ratings::fit(&completed), &CircuitCatalogue::bundled(), &mut utils::seeded_rng(Some(3)), || {});
+    
+    let leader = projection.drivers.iter().find(|o| o.name == drivers[0].name).unwrap();
+    assert_eq!(leader.title_probability(), 1.0);
+    assert_eq!(leader.likeliest_positions(), vec![(1, 1.0)]);
+}
+
+#[test]
+fn test_the_same_seed_gives_the_same_odds() {
+    let drivers = roster();
+    let completed = vec![completed_round(1, &drivers)];
+    let project = |seed| project_season(&schedule(3, &[2]), &completed, &drivers, 10,
+        &SimulationParameters::default(), &PointsSystem::for_season(2026), &Ratings::fit(&completed), &CircuitCatalogue::bundled(), &mut utils::seeded_rng(Some(seed)), || {});
+    
+    assert_eq!(project(7), project(7));
+}
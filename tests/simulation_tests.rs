@@ -1,8 +1,13 @@
 //! Unit tests for race simulation functionality
+#![allow(clippy::assertions_on_constants)]
 
-use f1_cli_simulator::models::{Driver, SimulationParameters};
-use f1_cli_simulator::simulator::simulation;
-use std::collections::HashMap;
+use f1_cli_simulator::circuits::CircuitCatalogue;
+use f1_cli_simulator::models::{Circuit, Driver, RaceFormat, SimulationParameters};
+use f1_cli_simulator::points::PointsSystem;
+use f1_cli_simulator::ratings::Ratings;
+use f1_cli_simulator::simulator::simulation::{self, Gap, RaceState};
+use f1_cli_simulator::utils;
+use f1_cli_simulator::weather::Weather;
 use std::time::Duration;
 
 // Helper function to create test drivers
@@ -32,184 +37,220 @@ fn create_test_drivers() -> Vec<Driver> {
     ]
 }
 
+// Helper function to create ratings in which the test drivers finished in order
+fn create_test_ratings() -> Ratings {
+    let drivers = create_test_drivers();
+    let order: Vec<&Driver> = drivers.iter().collect();
+    
+    let mut ratings = Ratings::new();
+    for _ in 0..10 {
+        ratings.rate_session(2024, &order, 1.0);
+    }
+    ratings
+}
+
+// Helper function to create a circuit from the bundled catalogue
+fn create_test_circuit() -> Circuit {
+    CircuitCatalogue::bundled().circuit("monza").unwrap()
+}
+
 // Helper function to create simulation parameters
-fn create_test_params(reliability: f64, weather: f64, incidents: bool) -> SimulationParameters {
+fn create_test_params(reliability: f64, weather: Weather, incidents: bool) -> SimulationParameters {
     SimulationParameters {
         reliability_factor: reliability,
-        weather_factor: weather,
+        weather,
         random_incidents: incidents,
+        ..Default::default()
     }
 }
 
 #[test]
-fn test_initialize_driver_positions() {
+fn test_race_starts_in_grid_order() {
     let drivers = create_test_drivers();
-    let params = create_test_params(1.0, 1.0, false);
+    let params = create_test_params(1.0, Weather::Dry, false);
+    let circuit = create_test_circuit();
     
-    // Access public function 
-    let positions = simulation::initialize_driver_positions(&drivers, &params);
+    let race = RaceState::new(&drivers, &create_test_ratings(), &params, &circuit, RaceFormat::GrandPrix, &mut utils::seeded_rng(Some(1)));
     
-    // Check that all drivers are included in the positions
-    assert_eq!(positions.len(), drivers.len());
+    // Check that all drivers are on the grid and running
+    assert_eq!(race.cars.len(), drivers.len());
+    assert_eq!(race.total_laps, circuit.laps);
+    assert_eq!(race.lap(), 0);
     
-    // Check that all drivers are marked as active
-    for pos in &positions {
-        assert!(pos.3); // The fourth element is the active flag
+    // Each car lines up further back from the line than the one ahead
+    for (i, car) in race.cars.iter().enumerate() {
+        assert_eq!(car.grid, i as u32 + 1);
+        assert!(car.is_running());
+        assert_eq!(car.laps(), 0);
     }
-    
-    // Verify positions are sorted (qualifying times should be in ascending order)
-    for i in 1..positions.len() {
-        assert!(positions[i-1].2 <= positions[i].2);
+    for pair in race.cars.windows(2) {
+        assert!(pair[0].race_time() < pair[1].race_time());
     }
 }
 
 #[test]
 fn test_calculate_driver_base_performance() {
+    let ratings = create_test_ratings();
     let drivers = create_test_drivers();
-    let params = create_test_params(1.0, 1.0, false);
     
     // Test normal conditions
     for driver in &drivers {
-        let perf = simulation::calculate_driver_base_performance(driver, &params);
+        let perf = simulation::calculate_driver_base_performance(driver, &ratings, 0.0);
         assert!(perf > 0.0 && perf <= 1.0, "Performance should be between 0 and 1");
     }
     
     // Test wet conditions
-    let wet_params = create_test_params(1.0, 0.7, false);
     for driver in &drivers {
-        let wet_perf = simulation::calculate_driver_base_performance(driver, &wet_params);
+        let wet_perf = simulation::calculate_driver_base_performance(driver, &ratings, 0.7);
         assert!(wet_perf > 0.0 && wet_perf <= 1.0, "Wet performance should be between 0 and 1");
     }
 }
 
 #[test]
-fn test_update_race_positions() {
-    let drivers = create_test_drivers();
-    let params = create_test_params(1.0, 1.0, false);
-    
-    // Setup initial positions
-    let mut positions = vec![
-        (0, 0.95, Duration::from_secs(90), true),  // Driver 1
-        (1, 0.90, Duration::from_secs(91), true),  // Driver 2
-        (2, 0.85, Duration::from_secs(92), true),  // Driver 3
-    ];
-    
-    // Setup driver performances
-    let mut performances = HashMap::new();
-    performances.insert(0, 0.95); // High performance
-    performances.insert(1, 0.90); // Medium performance
-    performances.insert(2, 0.85); // Lower performance
-    
-    // Initial positions before update
-    let initial_order: Vec<usize> = positions.iter().map(|p| p.0).collect();
-    
-    // Update positions multiple times to test position changes
-    let num_updates = 20; // Run multiple updates to increase chance of position changes
-    for _ in 0..num_updates {
-        simulation::update_race_positions(&mut positions, &performances, &params);
+fn test_race_order_follows_the_race_times() {
+    let params = create_test_params(1.0, Weather::Dry, false);
+    let circuit = create_test_circuit();
+    let mut rng = utils::seeded_rng(Some(2));
+    
+    // The fastest car starts at the back
+    let mut race = RaceState::from_grid(vec![(0, 0.80), (1, 0.85), (2, 0.95)], circuit.laps);
+    for _ in 0..20 {
+        race.simulate_lap(&params, &circuit, &mut rng);
+    }
+    
+    assert_eq!(race.lap(), 20);
+    assert!(!race.is_finished());
+    
+    // Cars are ordered by the time they completed the lap, each lap time
+    // adding to the race time
+    let order = race.running_order();
+    for pair in order.windows(2) {
+        let (ahead, behind) = (&race.cars[pair[0]], &race.cars[pair[1]]);
+        assert_eq!(ahead.laps(), behind.laps());
+        assert!(ahead.race_time() <= behind.race_time());
+    }
+    for car in &race.cars {
+        let total: Duration = (1..=car.laps()).filter_map(|lap| car.lap_time(lap)).sum();
+        assert_eq!(car.crossings[0] + total, car.race_time());
+        assert!(car.last_lap().unwrap() > circuit.base_lap_time().mul_f64(0.9));
+    }
+    
+    // A car with much more pace gets to the front over a race stint
+    assert_eq!(race.cars[order[0]].driver, 2);
+}
+
+#[test]
+fn test_gaps_and_intervals_are_measured_at_the_line() {
+    let params = create_test_params(1.0, Weather::Dry, false);
+    let circuit = create_test_circuit();
+    let mut rng = utils::seeded_rng(Some(3));
+    
+    let mut race = RaceState::from_grid(vec![(0, 0.95), (1, 0.90), (2, 0.85)], circuit.laps);
+    for _ in 0..10 {
+        race.simulate_lap(&params, &circuit, &mut rng);
     }
     
-    // Check that all drivers are still present
-    assert_eq!(positions.len(), drivers.len());
+    let order = race.running_order();
+    let crossing = |car: usize| race.cars[car].crossings[10];
     
-    // Check updated performance values
-    for pos in &positions {
-        assert!(pos.1 > 0.0 && pos.1 <= 1.0, "Updated performance should be between 0 and 1");
+    assert_eq!(race.gap_to_leader(order[0]), Gap::Leader);
+    assert_eq!(race.interval(order[0]), Gap::Leader);
+    assert_eq!(race.interval(order[1]), Gap::Time(crossing(order[1]) - crossing(order[0])));
+    assert_eq!(race.interval(order[2]), Gap::Time(crossing(order[2]) - crossing(order[1])));
+    assert_eq!(race.gap_to_leader(order[2]), Gap::Time(crossing(order[2]) - crossing(order[0])));
+    
+    assert_eq!(Gap::Time(Duration::from_millis(1500)).to_string(), "+1.500s");
+    assert_eq!(Gap::Laps(1).to_string(), "+1 Lap");
+    assert_eq!(Gap::Laps(3).to_string(), "+3 Laps");
+}
+
+#[test]
+fn test_slow_cars_are_lapped_and_finish_laps_down() {
+    let drivers = create_test_drivers();
+    let params = create_test_params(1.0, Weather::Dry, false);
+    let circuit = create_test_circuit();
+    let mut rng = utils::seeded_rng(Some(4));
+    
+    // Ten percent off the pace loses several laps over a grand prix
+    let mut race = RaceState::from_grid(vec![(0, 1.0), (1, 0.99), (2, 0.0)], circuit.laps);
+    while !race.is_finished() {
+        race.simulate_lap(&params, &circuit, &mut rng);
     }
     
-    // Extract final order to check if any overtaking happened
-    let final_order: Vec<usize> = positions.iter().map(|p| p.0).collect();
+    let order = race.running_order();
+    let (winner, backmarker) = (&race.cars[order[0]], &race.cars[order[2]]);
+    assert_eq!(winner.laps(), circuit.laps);
+    assert_eq!(race.cars[order[1]].laps(), circuit.laps);
+    assert_eq!(backmarker.driver, 2);
+    
+    let laps_down = winner.laps() - backmarker.laps();
+    assert!(laps_down >= 3, "{} laps down", laps_down);
+    assert_eq!(race.gap_to_leader(order[2]), Gap::Laps(laps_down));
+    
+    // The backmarker takes the flag the first time it crosses the line after the winner
+    assert!(backmarker.race_time() >= winner.race_time());
+    assert!(backmarker.crossings[backmarker.crossings.len() - 2] < winner.race_time());
     
-    // Log the orders (though in a real test we might not do this)
-    println!("Initial order: {:?}", initial_order);
-    println!("Final order: {:?}", final_order);
+    let results = race.results(&drivers, &circuit, RaceFormat::GrandPrix, &PointsSystem::for_season(2024));
+    assert_eq!(results[2].status, format!("+{} Laps", laps_down));
+    assert_eq!(results[2].time, None);
+    assert_eq!(results[2].laps, backmarker.laps());
+    assert!(results[1].time.as_ref().unwrap().starts_with('+'));
     
-    // Note: we don't assert that orders changed because it's probabilistic
-    // Instead, we just verify that the update function runs without errors
+    // Simulated results have no constructor id from the feed
+    assert!(results.iter().all(|r| r.constructor_id.is_empty()));
 }
 
 #[test]
 fn test_check_for_incidents() {
     let drivers = create_test_drivers();
-    let mut dnf_drivers = Vec::new();
-    
-    // Test with high reliability (should be few or no incidents)
-    let high_reliability_params = create_test_params(2.0, 1.0, true);
-    let mut high_reliability_positions = vec![
-        (0, 0.95, Duration::from_secs(90), true),  // Driver 1
-        (1, 0.90, Duration::from_secs(91), true),  // Driver 2
-        (2, 0.85, Duration::from_secs(92), true),  // Driver 3
-    ];
-    
-    // Run multiple incident checks with high reliability
-    for lap in 6..20 {  // Start at lap 6 since the function requires lap > 5
-        simulation::check_for_incidents(
-            &drivers, 
-            &mut high_reliability_positions, 
-            &mut dnf_drivers,
-            lap,
-            &high_reliability_params
-        );
+    let circuit = create_test_circuit();
+    let mut rng = utils::seeded_rng(Some(5));
+    
+    // Test with very high reliability (failures are practically impossible)
+    let high_reliability_params = create_test_params(1000.0, Weather::Dry, true);
+    let mut race = RaceState::new(&drivers, &create_test_ratings(), &high_reliability_params, &circuit, RaceFormat::GrandPrix, &mut rng);
+    for _ in 6..20 {
+        assert!(simulation::check_for_incidents(&drivers, &mut race, &high_reliability_params, &circuit, &mut rng).is_empty());
     }
-    
-    // Just verify that the function doesn't crash - incidents are random
-    
-    // Reset and Test with low reliability and bad weather (should have higher chance of incidents)
-    dnf_drivers.clear();
-    let low_reliability_params = create_test_params(0.5, 0.5, true);
-    let mut low_reliability_positions = vec![
-        (0, 0.95, Duration::from_secs(90), true),  // Driver 1
-        (1, 0.90, Duration::from_secs(91), true),  // Driver 2
-        (2, 0.85, Duration::from_secs(92), true),  // Driver 3
-    ];
-    
-    // Run multiple incident checks with low reliability
-    for lap in 6..50 {  // More laps to increase chance of incidents
-        simulation::check_for_incidents(
-            &drivers, 
-            &mut low_reliability_positions, 
-            &mut dnf_drivers,
-            lap,
-            &low_reliability_params
-        );
+    assert!(race.cars.iter().all(|car| car.is_running()));
+    
+    // Test with terrible reliability (every car fails within a few laps)
+    let low_reliability_params = create_test_params(0.001, Weather::Wet, true);
+    let mut race = RaceState::new(&drivers, &create_test_ratings(), &low_reliability_params, &circuit, RaceFormat::GrandPrix, &mut rng);
+    let mut retired = Vec::new();
+    for _ in 6..50 {
+        retired.extend(simulation::check_for_incidents(&drivers, &mut race, &low_reliability_params, &circuit, &mut rng));
     }
     
-    // Print how many incidents occurred (for information)
-    println!("DNF count with low reliability: {}", dnf_drivers.len());
+    // Each car retires once, with a reason
+    retired.sort();
+    assert_eq!(retired, vec![0, 1, 2]);
+    assert!(race.cars.iter().all(|car| car.retirement.as_ref().is_some_and(|reason| !reason.is_empty())));
+    assert!(race.is_finished());
 }
 
 #[test]
-fn test_update_fastest_lap() {
-    let positions = vec![
-        (0, 0.95, Duration::from_secs(90), true),   // Driver 1 - active
-        (1, 0.90, Duration::from_secs(91), true),   // Driver 2 - active
-        (2, 0.85, Duration::from_secs(92), false),  // Driver 3 - inactive (DNF)
-    ];
-    
-    // Initial - no fastest lap
-    let mut fastest_lap: Option<(usize, Duration)> = None;
-    simulation::update_fastest_lap(&positions, 1, &mut fastest_lap);
-    
-    // After first update, fastest lap should be set to the fastest active driver (driver 1)
-    assert!(fastest_lap.is_some());
-    assert_eq!(fastest_lap.unwrap().0, 0); // Driver 1 should have fastest lap
-    
-    // Keep the initial fastest lap
-    let initial_fastest = fastest_lap.clone();
-    
-    // Test with a faster lap time
-    let faster_positions = vec![
-        (0, 0.95, Duration::from_secs(90), true),   // Driver 1 - same as before
-        (1, 0.99, Duration::from_secs(89), true),   // Driver 2 - faster than anyone
-        (2, 0.85, Duration::from_secs(92), false),  // Driver 3 - inactive (DNF)
-    ];
-    
-    simulation::update_fastest_lap(&faster_positions, 2, &mut fastest_lap);
-    
-    // Fastest lap should now be updated to driver 2
-    assert!(fastest_lap.is_some());
-    assert_eq!(fastest_lap.unwrap().0, 1); // Driver 2 should now have fastest lap
-    assert!(fastest_lap.unwrap().1 < initial_fastest.unwrap().1); // New time should be faster
+fn test_fastest_lap_is_the_quickest_lap_set() {
+    let drivers = create_test_drivers();
+    let params = create_test_params(1.0, Weather::Dry, false);
+    let circuit = create_test_circuit();
+    
+    let race = simulation::run_race(&drivers, &circuit, &params, RaceFormat::GrandPrix, &create_test_ratings(), &mut utils::seeded_rng(Some(6)));
+    
+    let fastest_lap = race.fastest_lap().unwrap();
+    let quickest = race.cars.iter()
+        .flat_map(|car| (1..=car.laps()).filter_map(move |lap| car.lap_time(lap)))
+        .min()
+        .unwrap();
+    assert_eq!(fastest_lap.time, quickest);
+    
+    let car = race.cars.iter().find(|car| car.driver == fastest_lap.driver).unwrap();
+    assert_eq!(car.best_lap(), Some((fastest_lap.lap, fastest_lap.time)));
+    assert_eq!(car.lap_time(fastest_lap.lap), Some(fastest_lap.time));
+    
+    // Cars are heaviest with fuel on the opening laps, which include the start
+    assert!(fastest_lap.lap > 1);
 }
 
 #[test]
@@ -240,7 +281,7 @@ fn test_interactive_and_instant_race_parameters() {
 #[test]
 fn test_simulate_function_returns_ok() {
     // This would normally be an integration test
-    // For unit testing, we need to mock create_circuit_for_gp and create_current_drivers
+    // For unit testing, we need to mock data::load_circuits and data::load_roster
     
     // Since we can't easily mock these without changing the code,
     // we're just checking that the function can be called
@@ -249,7 +290,7 @@ fn test_simulate_function_returns_ok() {
     // The actual test would look like:
     // let season = 2023;
     // let gp = "monza";
-    // let params = create_test_params(1.0, 1.0, false);
+    // let params = create_test_params(1.0, Weather::Dry, false);
     // let result = simulation::simulate(season, gp, params, false);
     // assert!(result.is_ok());
     
@@ -261,22 +302,25 @@ fn test_simulate_function_returns_ok() {
 fn test_edge_case_empty_drivers_list() {
     // Test what happens with an empty drivers list
     let empty_drivers: Vec<Driver> = vec![];
-    let params = create_test_params(1.0, 1.0, false);
-    
-    // Should return an empty positions list
-    let positions = simulation::initialize_driver_positions(&empty_drivers, &params);
-    assert_eq!(positions.len(), 0);
+    let params = create_test_params(1.0, Weather::Dry, false);
+    let circuit = create_test_circuit();
+    
+    // Should return an empty race that is over before it starts
+    let race = simulation::run_race(&empty_drivers, &circuit, &params, RaceFormat::GrandPrix, &create_test_ratings(), &mut utils::seeded_rng(Some(7)));
+    assert_eq!(race.cars.len(), 0);
+    assert!(race.is_finished());
+    assert!(race.fastest_lap().is_none());
+    assert!(race.results(&empty_drivers, &circuit, RaceFormat::GrandPrix, &PointsSystem::for_season(2024)).is_empty());
 }
 
 #[test]
 fn test_edge_case_extreme_weather() {
+    let ratings = create_test_ratings();
     let drivers = create_test_drivers();
     
-    // Test with extreme weather conditions (very wet)
-    let extreme_weather_params = create_test_params(1.0, 0.1, true);
-    
+    // Test with extreme weather conditions (standing water)
     for driver in &drivers {
-        let perf = simulation::calculate_driver_base_performance(driver, &extreme_weather_params);
+        let perf = simulation::calculate_driver_base_performance(driver, &ratings, 1.0);
         // Even in extreme conditions, performance should be reasonable
         assert!(perf > 0.3 && perf < 1.0);
     }
@@ -285,36 +329,34 @@ fn test_edge_case_extreme_weather() {
 #[test]
 fn test_all_drivers_dnf() {
     let drivers = create_test_drivers();
-    let mut dnf_drivers = vec![];
-    
-    // Create a positions list where all drivers will soon be DNF
-    let mut positions = vec![
-        (0, 0.95, Duration::from_secs(90), true), 
-        (1, 0.90, Duration::from_secs(91), true), 
-        (2, 0.85, Duration::from_secs(92), true),
-    ];
-    
-    // Manually mark all drivers as DNF
-    for i in 0..positions.len() {
-        let driver_idx = positions[i].0;
-        positions[i].3 = false;
-        dnf_drivers.push(driver_idx);
-    }
+    let params = create_test_params(1.0, Weather::Dry, true);
+    let circuit = create_test_circuit();
+    let mut rng = utils::seeded_rng(Some(8));
     
-    // Now test update functions with all DNF drivers
-    let params = create_test_params(1.0, 1.0, true);
-    let mut driver_performance = HashMap::new();
-    for i in 0..drivers.len() {
-        driver_performance.insert(i, 0.9);
+    let mut race = RaceState::new(&drivers, &create_test_ratings(), &params, &circuit, RaceFormat::GrandPrix, &mut rng);
+    for _ in 0..3 {
+        race.simulate_lap(&params, &circuit, &mut rng);
     }
     
-    // These should not crash even with all drivers DNF
-    simulation::update_race_positions(&mut positions, &driver_performance, &params);
-    simulation::update_fastest_lap(&positions, 1, &mut None);
+    // Manually retire every car
+    for car in 0..race.cars.len() {
+        race.retire(car, "Engine");
+    }
     
-    // Check positions weren't modified
-    for pos in &positions {
-        assert!(!pos.3, "All drivers should remain DNF");
+    // The race is over and further laps change nothing
+    assert!(race.is_finished());
+    let before = race.clone();
+    race.simulate_lap(&params, &circuit, &mut rng);
+    assert_eq!(race, before);
+    
+    // Nobody is classified
+    let results = race.results(&drivers, &circuit, RaceFormat::GrandPrix, &PointsSystem::for_season(2024));
+    assert_eq!(results.len(), drivers.len());
+    for result in &results {
+        assert_eq!(result.status, "Engine");
+        assert_eq!(result.time, None);
+        assert_eq!(result.laps, 3);
+        assert_eq!(result.points, 0.0);
     }
 }
 
@@ -338,74 +380,71 @@ fn test_realistic_race_scenario() {
         number: 11,
     });
     
-    let params = create_test_params(0.8, 0.9, true);
+    let params = create_test_params(0.8, Weather::Dry, true);
+    let circuit = create_test_circuit();
+    let points_system = PointsSystem::for_season(2024);
     
-    // Initialize positions
-    let mut positions = simulation::initialize_driver_positions(&drivers, &params);
-    let initial_positions = positions.clone();
+    let race = simulation::run_race(&drivers, &circuit, &params, RaceFormat::GrandPrix, &create_test_ratings(), &mut utils::seeded_rng(Some(9)));
+    let results = race.results(&drivers, &circuit, RaceFormat::GrandPrix, &points_system);
     
-    // Initialize driver performance map
-    let mut driver_performance = HashMap::new();
-    for (i, driver) in drivers.iter().enumerate() {
-        let perf = simulation::calculate_driver_base_performance(driver, &params);
-        driver_performance.insert(i, perf);
+    // 1. Every driver is in the results once, in position order
+    assert_eq!(results.len(), drivers.len());
+    for (i, result) in results.iter().enumerate() {
+        assert_eq!(result.position, i as u32 + 1);
+        assert!(result.grid >= 1 && result.grid <= drivers.len() as u32);
     }
     
-    // Record DNFs
-    let mut dnf_drivers = Vec::new();
-    let mut fastest_lap: Option<(usize, Duration)> = None;
-    
-    // Run a mini simulation for 20 laps
-    for lap in 1..=20 {
-        // Update positions
-        simulation::update_race_positions(&mut positions, &driver_performance, &params);
-        
-        // Check for incidents after lap 5
-        if lap > 5 {
-            simulation::check_for_incidents(
-                &drivers, 
-                &mut positions, 
-                &mut dnf_drivers,
-                lap,
-                &params
-            );
-        }
-        
-        // Update fastest lap
-        simulation::update_fastest_lap(&positions, lap, &mut fastest_lap);
+    // 2. Unless everyone retired, the winner has the race time and the full distance
+    let winner = &results[0];
+    if winner.status == "Finished" {
+        assert_eq!(winner.laps, circuit.laps);
+        assert!(winner.time.as_ref().unwrap().contains(':'));
     }
     
-    // Verify the simulation produced reasonable results
-    
-    // 1. Make sure we still have the correct number of entries in positions
-    assert_eq!(positions.len(), drivers.len());
-    
-    // 2. Check we have a fastest lap
-    assert!(fastest_lap.is_some());
-    
-    // 3. Check the positions have likely changed from initial state
-    // Compare each driver's current position with their initial position
-    let mut position_changes = 0;
-    for (i, pos) in positions.iter().enumerate() {
-        // Find this driver's original position
-        for (j, initial_pos) in initial_positions.iter().enumerate() {
-            if pos.0 == initial_pos.0 && i != j {
-                position_changes += 1;
+    // 3. Retired cars are classified behind the cars still running and score nothing
+    // unless they covered 90% of the distance
+    for result in &results {
+        if result.time.is_none() && !result.status.starts_with('+') {
+            assert!(result.laps < circuit.laps);
+            if result.laps * 10 < circuit.laps * 9 {
+                assert_eq!(result.points, 0.0);
             }
         }
     }
     
-    // Print position changes for information
-    println!("Position changes in simulation: {}", position_changes);
-    
-    // 4. We don't assert on DNF count because it's random and may vary between test runs
-    println!("DNF count in simulation: {}", dnf_drivers.len());
-    // This test is mostly to ensure the simulation runs without crashing
-    // The specific outcomes will vary due to randomization
+    // 4. Exactly one driver has the fastest lap
+    assert_eq!(results.iter().filter(|r| r.fastest_lap.as_ref().is_some_and(|fl| fl.rank == 1)).count(), 1);
+}
+
+#[test]
+fn test_results_are_scored_from_the_race() {
+    let drivers = create_test_drivers();
+    let params = create_test_params(1.0, Weather::Dry, false);
+    let circuit = create_test_circuit();
+    
+    let race = simulation::run_race(&drivers, &circuit, &params, RaceFormat::GrandPrix, &create_test_ratings(), &mut utils::seeded_rng(Some(10)));
+    let results = race.results(&drivers, &circuit, RaceFormat::GrandPrix, &PointsSystem::for_season(2024));
+    
+    // Everyone finishes on the lead lap; the winner's time is the race time,
+    // everyone else's the gap to the winner
+    let winner = race.cars.iter().min_by_key(|car| car.race_time()).unwrap();
+    assert_eq!(results[0].time_millis, Some(winner.race_time().as_millis() as u64));
+    assert_eq!(results[0].time, Some(utils::format_race_time(winner.race_time())));
+    assert!(results[1].time.as_ref().unwrap().starts_with('+'));
+    assert!(results.iter().all(|r| r.status == "Finished" && r.laps == circuit.laps));
+    
+    // 25, 18 and 15 points, and a point for the fastest lap
+    let total: f64 = results.iter().map(|r| r.points).sum();
+    assert_eq!(total, 25.0 + 18.0 + 15.0 + 1.0);
+    
+    let fastest_lap = results.iter().find_map(|r| r.fastest_lap.as_ref().filter(|fl| fl.rank == 1)).unwrap();
+    assert_eq!(fastest_lap.time, utils::format_duration_as_lap_time(race.fastest_lap().unwrap().time));
+    assert!(fastest_lap.average_speed_kph.unwrap() > 200.0);
 }
 
 #[test]
 fn test_performance_consistency() {
+    let ratings = create_test_ratings();
     // Test that driver performance calculations are consistent
     let driver = Driver {
         id: "test_driver".to_string(),
@@ -415,11 +454,9 @@ fn test_performance_consistency() {
         number: 1,
     };
     
-    let params = create_test_params(1.0, 1.0, false);
-    
     // Calculate performance multiple times
     let performances: Vec<f64> = (0..10)
-        .map(|_| simulation::calculate_driver_base_performance(&driver, &params))
+        .map(|_| simulation::calculate_driver_base_performance(&driver, &ratings, 0.0))
         .collect();
     
     // Ensure all performance values are the same (deterministic)
@@ -437,9 +474,37 @@ fn test_performance_consistency() {
         number: 44,
     };
     
-    let another_performance = simulation::calculate_driver_base_performance(&another_driver, &params);
+    let another_performance = simulation::calculate_driver_base_performance(&another_driver, &ratings, 0.0);
     
     // Different drivers should have different base performance
     assert!((performances[0] - another_performance).abs() > f64::EPSILON,
             "Different drivers should have different performance values");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_sprint_distance_is_about_100_km() {
+    let interlagos = Circuit {
+        id: "interlagos".to_string(),
+        name: "Autódromo José Carlos Pace".to_string(),
+        country: "Brazil".to_string(),
+        city: "São Paulo".to_string(),
+        length_km: 4.309,
+        laps: 71,
+        ..Default::default()
+    };
+    
+    assert_eq!(RaceFormat::GrandPrix.laps(&interlagos), 71);
+    assert_eq!(RaceFormat::Sprint.laps(&interlagos), 24);
+}
+
+#[test]
+fn test_the_same_seed_runs_the_same_race() {
+    let drivers = create_test_drivers();
+    let params = create_test_params(0.5, Weather::Random { seed: None }, true);
+    let circuit = create_test_circuit();
+    let run = |seed| simulation::run_race(&drivers, &circuit, &params, RaceFormat::GrandPrix, &create_test_ratings(), &mut utils::seeded_rng(Some(seed)));
+    
+    // Weather, grid, lap times and retirements are all drawn from the seed
+    assert_eq!(run(42), run(42));
+    assert_ne!(run(42).cars, run(43).cars);
+}
@@ -0,0 +1,238 @@
+//! Tests for the championship standings engine
+
+use f1_cli_simulator::data::index::{round_file_name, save_index, SeasonIndex};
+use f1_cli_simulator::data::{load_standings, DataConfig};
+use f1_cli_simulator::models::{Circuit, Driver, Race, RaceResult, SprintResult, WeekendResults};
+use f1_cli_simulator::points::PointsSystem;
+use f1_cli_simulator::standings::Standings;
+use serde_json::json;
+use std::fs;
+
+fn driver(name: &str, team: &str) -> Driver {
+    Driver {
+        id: name.to_lowercase(),
+        code: name.chars().take(3).collect::<String>().to_uppercase(),
+        name: name.to_string(),
+        team: team.to_string(),
+        number: 0,
+    }
+}
+
+// A weekend where the drivers finish in the order given, scored 25-18-15-...
+fn weekend(round: u32, order: &[(&str, &str)]) -> WeekendResults {
+    let points = [25.0, 18.0, 15.0, 12.0, 10.0, 8.0, 6.0, 4.0, 2.0, 1.0];
+    let results = order.iter().enumerate().map(|(i, &(name, team))| RaceResult {
+        position: (i + 1) as u32,
+        driver: driver(name, team),
+        number: 0,
+        constructor_id: team.to_lowercase(),
+        grid: 0,
+        time: None,
+        time_millis: None,
+        points: points.get(i).copied().unwrap_or(0.0),
+        laps: 50,
+        status: "Finished".to_string(),
+        fastest_lap: None,
+    }).collect();
+    
+    WeekendResults {
+        race: Race {
+            season: 2024,
+            round,
+            name: format!("Round {}", round),
+            circuit: Circuit {
+                id: "test".to_string(),
+                name: "Test Circuit".to_string(),
+                country: "Test Country".to_string(),
+                city: "Test City".to_string(),
+                length_km: 5.0,
+                laps: 50,
+                ..Default::default()
+            },
+            date: "2024-01-01".to_string(),
+            results,
+        },
+        qualifying: Vec::new(),
+        sprint: Vec::new(),
+    }
+}
+
+#[test]
+fn test_points_are_summed_per_driver_and_constructor() {
+    let weekends = vec![
+        weekend(1, &[("Verstappen", "Red Bull"), ("Norris", "McLaren"), ("Piastri", "McLaren")]),
+        weekend(2, &[("Norris", "McLaren"), ("Verstappen", "Red Bull"), ("Piastri", "McLaren")]),
+    ];
+    
+    let standings = Standings::after_round(&weekends, None, None);
+    
+    assert_eq!(standings.round, 2);
+    assert_eq!(standings.driver("Verstappen").unwrap().points, 43.0);
+    assert_eq!(standings.driver("Norris").unwrap().points, 43.0);
+    assert_eq!(standings.driver("Piastri").unwrap().points, 30.0);
+    
+    assert_eq!(standings.constructors[0].name, "McLaren");
+    assert_eq!(standings.constructors[0].points, 73.0);
+    assert_eq!(standings.constructor("Red Bull").unwrap().points, 43.0);
+}
+
+// Filler drivers so the named ones can finish anywhere in the top ten
+fn field(placed: &[(usize, &'static str)]) -> Vec<(&'static str, &'static str)> {
+    let mut order: Vec<(&str, &str)> = ["F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10"]
+        .iter()
+        .map(|&name| (name, "Filler"))
+        .collect();
+    for &(position, name) in placed {
+        order.insert(position - 1, (name, name));
+    }
+    order
+}
+
+#[test]
+fn test_ties_are_broken_by_best_finishes() {
+    // Alpha: P1 + P10 = 26, Bravo: P2 + P6 = 26
+    let weekends = vec![
+        weekend(1, &field(&[(1, "Alpha"), (2, "Bravo")])),
+        weekend(2, &field(&[(6, "Bravo"), (10, "Alpha")])),
+    ];
+    
+    let standings = Standings::after_round(&weekends, None, None);
+    let alpha = standings.driver("Alpha").unwrap();
+    let bravo = standings.driver("Bravo").unwrap();
+    
+    assert_eq!(alpha.points, bravo.points);
+    assert!(alpha.position < bravo.position, "a win beats any number of second places");
+}
+
+#[test]
+fn test_countback_moves_on_to_the_first_differing_position() {
+    // Alpha: P3 + P3 = 30, Bravo: P2 + P5 + P9 = 30; neither has won
+    let weekends = vec![
+        weekend(1, &field(&[(2, "Bravo"), (3, "Alpha")])),
+        weekend(2, &field(&[(3, "Alpha"), (5, "Bravo")])),
+        weekend(3, &field(&[(9, "Bravo")])),
+    ];
+    
+    let standings = Standings::after_round(&weekends, None, None);
+    let alpha = standings.driver("Alpha").unwrap();
+    let bravo = standings.driver("Bravo").unwrap();
+    
+    assert_eq!(alpha.points, 30.0);
+    assert_eq!(bravo.points, 30.0);
+    assert!(bravo.position < alpha.position, "Bravo's second place decides the tie");
+}
+
+#[test]
+fn test_standings_after_an_earlier_round() {
+    let weekends = vec![
+        weekend(1, &[("Verstappen", "Red Bull"), ("Norris", "McLaren")]),
+        weekend(2, &[("Norris", "McLaren"), ("Verstappen", "Red Bull")]),
+        weekend(3, &[("Norris", "McLaren"), ("Verstappen", "Red Bull")]),
+    ];
+    
+    let standings = Standings::after_round(&weekends, Some(1), None);
+    
+    assert_eq!(standings.round, 1);
+    assert_eq!(standings.drivers[0].name, "Verstappen");
+    assert_eq!(standings.drivers[0].points, 25.0);
+    assert_eq!(standings.drivers[0].wins(), 1);
+}
+
+#[test]
+fn test_sprint_points_count_but_not_towards_the_countback() {
+    let mut sprint_weekend = weekend(1, &[("Verstappen", "Red Bull"), ("Norris", "McLaren")]);
+    sprint_weekend.sprint = vec![SprintResult {
+        position: 1,
+        driver: driver("Norris", "McLaren"),
+        grid: 1,
+        time: None,
+        points: 8.0,
+        laps: 19,
+        status: "Finished".to_string(),
+    }];
+    
+    let standings = Standings::after_round(&[sprint_weekend], None, None);
+    let norris = standings.driver("Norris").unwrap();
+    
+    assert_eq!(norris.points, 26.0);
+    assert_eq!(norris.wins(), 0);
+    assert_eq!(standings.constructor("McLaren").unwrap().points, 26.0);
+}
+
+#[test]
+fn test_standings_can_be_rescored_with_another_points_system() {
+    let mut weekends = vec![
+        weekend(1, &[("Verstappen", "Red Bull"), ("Norris", "McLaren"), ("Piastri", "McLaren")]),
+        weekend(2, &[("Norris", "McLaren"), ("Verstappen", "Red Bull"), ("Piastri", "McLaren")]),
+    ];
+    weekends[1].race.results[2].status = "Engine".to_string();
+    weekends[1].race.results[2].laps = 10;
+    
+    let system = PointsSystem::for_season(1991);
+    let standings = Standings::after_round(&weekends, None, Some(&system));
+    
+    assert_eq!(standings.driver("Verstappen").unwrap().points, 16.0);
+    assert_eq!(standings.driver("Norris").unwrap().points, 16.0);
+    // Retired too early to be classified
+    assert_eq!(standings.driver("Piastri").unwrap().points, 4.0);
+    assert_eq!(standings.constructor("McLaren").unwrap().points, 20.0);
+}
+
+#[test]
+fn test_a_shortened_race_is_rescored_under_the_short_race_rule() {
+    // Like the 2021 Belgian GP: one lap of the scheduled 50 run behind the safety car
+    let mut weekends = vec![weekend(1, &[("Verstappen", "Red Bull"), ("Russell", "Williams"), ("Hamilton", "Mercedes")])];
+    for result in &mut weekends[0].race.results {
+        result.laps = 1;
+    }
+    
+    let halved = Standings::after_round(&weekends, None, Some(&PointsSystem::for_season(2021)));
+    assert_eq!(halved.driver("Verstappen").unwrap().points, 12.5);
+    assert_eq!(halved.driver("Russell").unwrap().points, 9.0);
+    assert_eq!(halved.driver("Hamilton").unwrap().points, 7.5);
+    
+    let scaled = Standings::after_round(&weekends, None, Some(&PointsSystem::for_season(2022)));
+    assert_eq!(scaled.driver("Verstappen").unwrap().points, 6.0);
+    assert_eq!(scaled.driver("Hamilton").unwrap().points, 3.0);
+    
+    // Three quarters of the distance is a full race
+    for result in &mut weekends[0].race.results {
+        result.laps = 38;
+    }
+    let full = Standings::after_round(&weekends, None, Some(&PointsSystem::for_season(2021)));
+    assert_eq!(full.driver("Verstappen").unwrap().points, 25.0);
+}
+
+#[test]
+fn test_standings_are_loaded_from_cached_rounds() {
+    let dir = tempfile::tempdir().unwrap();
+    let race = |round: u32, winner: &str| json!({
+        "season": "2024",
+        "round": round.to_string(),
+        "raceName": format!("Round {} Grand Prix", round),
+        "date": format!("2024-03-0{}", round),
+        "Circuit": { "circuitId": format!("circuit{}", round), "circuitName": "Circuit", "Location": { "locality": "Town", "country": "Country" } },
+        "Results": [{
+            "position": "1",
+            "points": "25",
+            "Driver": { "driverId": winner, "givenName": "Test", "familyName": winner },
+            "Constructor": { "constructorId": "team", "name": "Team" },
+        }],
+    });
+    
+    // Round 3 is on the calendar but has not been cached
+    save_index(dir.path(), &SeasonIndex::from_races(2024, &[race(1, "alpha"), race(2, "alpha"), race(3, "bravo")])).unwrap();
+    for round in [1, 2] {
+        let path = dir.path().join(round_file_name("race", 2024, round));
+        fs::write(path, serde_json::to_string(&vec![race(round, "alpha")]).unwrap()).unwrap();
+    }
+    
+    let config = DataConfig::new(dir.path(), "http://127.0.0.1:9");
+    let standings = load_standings(&config, 2024, None, None).unwrap();
+    
+    assert_eq!(standings.round, 2);
+    assert_eq!(standings.drivers.len(), 1);
+    assert_eq!(standings.drivers[0].points, 50.0);
+    
+    assert!(load_standings(&config, 2023, None, None).is_err());
+}
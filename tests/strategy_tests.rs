@@ -0,0 +1,185 @@
+//! Tests for pit stop strategies, scenarios and pit stops in the race engine
+
+mod common;
+
+use common::{drivers, monza, params};
+use f1_cli_simulator::models::{Compound, RaceFormat};
+use f1_cli_simulator::ratings::Ratings;
+use f1_cli_simulator::simulator::simulation::phases::Phase;
+use f1_cli_simulator::simulator::simulation::scenario::Scenario;
+use f1_cli_simulator::simulator::simulation::strategy::{PitWindow, Reaction, Strategy};
+use f1_cli_simulator::simulator::simulation::RaceState;
+use f1_cli_simulator::utils;
+
+// The window of a car on the given lap with its stop planned for lap 20
+fn window(lap: u32) -> PitWindow {
+    PitWindow { lap, planned_lap: 20, gap_ahead: None, rival_pitted: false, safety_car: false }
+}
+
+// A grand prix at Monza, run to the flag after `setup`
+fn run(scenario: &Scenario, setup: impl FnOnce(&mut RaceState)) -> RaceState {
+    let (drivers, circuit, params) = (drivers(), monza(), params());
+    let mut rng = utils::seeded_rng(Some(1));
+    
+    let mut race = RaceState::new(&drivers, &Ratings::new(), &params, &circuit, RaceFormat::GrandPrix, &mut rng);
+    race.assign_strategies(&drivers, scenario, &params).unwrap();
+    setup(&mut race);
+    
+    while !race.is_finished() {
+        race.simulate_lap(&params, &circuit, &mut rng);
+    }
+    race
+}
+
+#[test]
+fn test_strategies_are_parsed_and_validated() {
+    assert_eq!("no-stop".parse::<Strategy>().unwrap(), Strategy::no_stop());
+    assert_eq!("one-stop".parse::<Strategy>().unwrap(), Strategy::one_stop());
+    assert_eq!("2-stop".parse::<Strategy>().unwrap(), Strategy::two_stop());
+    
+    let strategy: Strategy = "soft/hard@18:undercut:no-sc".parse().unwrap();
+    assert_eq!(strategy.stints, vec![Compound::Soft, Compound::Hard]);
+    assert_eq!(strategy.pit_laps, vec![18]);
+    assert_eq!(strategy.reaction, Reaction::Undercut);
+    assert!(!strategy.pit_under_safety_car);
+    assert_eq!(strategy.to_string(), "S-H (undercut)");
+    
+    assert!("soft/hard@18/30".parse::<Strategy>().is_err());
+    assert!("soft/medium/hard@30/18".parse::<Strategy>().is_err());
+    assert!("soft/slick".parse::<Strategy>().is_err());
+    assert!("one-stop:sandbag".parse::<Strategy>().is_err());
+}
+
+#[test]
+fn test_stops_are_planned_from_tyre_life() {
+    let (circuit, params) = (monza(), params());
+    
+    // The longer-lasting hards run the longer stint
+    let laps = Strategy::one_stop().planned_pit_laps(circuit.laps, &circuit, &params);
+    assert_eq!(laps.len(), 1);
+    assert!(laps[0] < circuit.laps / 2);
+    
+    let laps = Strategy::two_stop().planned_pit_laps(circuit.laps, &circuit, &params);
+    assert_eq!(laps.len(), 2);
+    assert!(laps[0] < laps[1] && laps[1] < circuit.laps);
+    
+    // Laps given are kept as they are
+    let strategy: Strategy = "medium/hard@30".parse().unwrap();
+    assert_eq!(strategy.planned_pit_laps(circuit.laps, &circuit, &params), vec![30]);
+}
+
+#[test]
+fn test_reactions_move_the_stop() {
+    let plain = Strategy::one_stop();
+    assert!(!plain.pits(&window(19)));
+    assert!(plain.pits(&window(20)));
+    
+    // Close behind the car ahead, the undercut comes early
+    let undercut = Strategy { reaction: Reaction::Undercut, ..Strategy::one_stop() };
+    assert!(undercut.pits(&PitWindow { gap_ahead: Some(1.0), ..window(17) }));
+    assert!(!undercut.pits(&PitWindow { gap_ahead: Some(6.0), ..window(17) }));
+    
+    // When a rival pits, the overcut stays out
+    let overcut = Strategy { reaction: Reaction::Overcut, ..Strategy::one_stop() };
+    assert!(!overcut.pits(&PitWindow { rival_pitted: true, ..window(21) }));
+    assert!(overcut.pits(&PitWindow { rival_pitted: true, ..window(23) }));
+    assert!(overcut.pits(&window(20)));
+    
+    // The safety car brings stops forward, unless the strategy says otherwise
+    assert!(plain.pits(&PitWindow { safety_car: true, ..window(10) }));
+    assert!(!plain.pits(&PitWindow { safety_car: true, ..window(2) }));
+    let no_sc: Strategy = "one-stop:no-sc".parse().unwrap();
+    assert!(!no_sc.pits(&PitWindow { safety_car: true, ..window(10) }));
+}
+
+#[test]
+fn test_scenarios_combine_files_and_the_command_line() {
+    let scenario = Scenario::from_json(r#"{
+        "strategies": {
+            "D01": "two-stop:undercut",
+            "D02": { "stints": ["medium", "hard"], "pit_laps": [25], "reaction": "overcut" }
+        }
+    }"#).unwrap();
+    assert_eq!(scenario.strategies["D01"].stops(), 2);
+    assert_eq!(scenario.strategies["D02"].pit_laps, vec![25]);
+    assert!(scenario.strategies["D02"].pit_under_safety_car);
+    
+    let scenario = scenario.with_strategies(&["D01=soft/hard@20".to_string()]).unwrap();
+    assert_eq!(scenario.overrides[0].0, "D01");
+    assert_eq!(scenario.overrides[0].1.stints, vec![Compound::Soft, Compound::Hard]);
+    
+    assert!(Scenario::default().with_strategies(&["D01".to_string()]).is_err());
+    assert!(Scenario::from_json(r#"{ "strategies": { "D01": { "stints": [] } } }"#).is_err());
+    
+    // Strategies are for drivers in the field
+    let (drivers, circuit, params) = (drivers(), monza(), params());
+    let mut race = RaceState::new(&drivers, &Ratings::new(), &params, &circuit, RaceFormat::GrandPrix, &mut utils::seeded_rng(Some(2)));
+    let unknown = Scenario::default().with_strategies(&["XYZ=two-stop".to_string()]).unwrap();
+    assert!(race.assign_strategies(&drivers, &unknown, &params).is_err());
+    
+    // Of two command line strategies for the same driver, the last given wins
+    for (specs, stops) in [(["D01=two-stop", "d1=no-stop"], 0), (["d1=no-stop", "D01=two-stop"], 2)] {
+        let scenario = Scenario::default().with_strategies(&specs.map(String::from)).unwrap();
+        race.assign_strategies(&drivers, &scenario, &params).unwrap();
+        assert_eq!(race.cars.iter().find(|car| car.driver == 0).unwrap().strategy.stops(), stops);
+    }
+}
+
+#[test]
+fn test_command_line_strategies_override_the_file_whatever_the_key() {
+    let (drivers, circuit, params) = (drivers(), monza(), params());
+    let mut race = RaceState::new(&drivers, &Ratings::new(), &params, &circuit, RaceFormat::GrandPrix, &mut utils::seeded_rng(Some(2)));
+    let stops = |race: &RaceState| race.cars.iter().find(|car| car.driver == 0).unwrap().strategy.stops();
+    
+    // The file names the driver by id and name, which sort after the code given on the command line
+    for file in [r#"{ "strategies": { "d1": "no-stop" } }"#, r#"{ "strategies": { "Driver 1": "no-stop" } }"#] {
+        let scenario = Scenario::from_json(file).unwrap()
+            .with_strategies(&["D01=two-stop".to_string()])
+            .unwrap();
+        race.assign_strategies(&drivers, &scenario, &params).unwrap();
+        assert_eq!(stops(&race), 2);
+    }
+    
+    // And the other way round
+    let scenario = Scenario::from_json(r#"{ "strategies": { "D01": "no-stop" } }"#).unwrap()
+        .with_strategies(&["driver 1=two-stop".to_string()])
+        .unwrap();
+    race.assign_strategies(&drivers, &scenario, &params).unwrap();
+    assert_eq!(stops(&race), 2);
+}
+
+#[test]
+fn test_cars_follow_their_strategy() {
+    let scenario = Scenario::default()
+        .with_strategies(&["D01=two-stop".to_string(), "driver 2=soft/hard@15".to_string()])
+        .unwrap();
+    let race = run(&scenario, |_| {});
+    
+    let car = |code: &str| race.cars.iter().find(|car| drivers()[car.driver].code == code).unwrap();
+    
+    assert_eq!(car("D01").compounds_used(), vec![Compound::Medium, Compound::Hard, Compound::Soft]);
+    assert_eq!(car("D02").compounds_used(), vec![Compound::Soft, Compound::Hard]);
+    assert_eq!(car("D02").stints[1].from_lap, 15);
+    
+    // Every stop costs about the time lost driving through the pit lane
+    let loss = monza().pit_lane_loss_secs;
+    for car in &race.cars {
+        assert!(car.is_running());
+        for stint in &car.stints[1..] {
+            let secs = stint.pit_lane_time.unwrap().as_secs_f64();
+            assert!(secs >= loss && secs < loss + 3.0, "{}", secs);
+        }
+    }
+}
+
+#[test]
+fn test_the_safety_car_brings_stops_forward() {
+    let planned = Strategy::one_stop().planned_pit_laps(monza().laps, &monza(), &params())[0];
+    
+    let race = run(&Scenario::default(), |race| race.deploy(Phase::SafetyCar, monza().laps));
+    
+    // With the safety car out from the start, cars pit as soon as their stop is in reach
+    for car in &race.cars {
+        assert!(car.stints[1].from_lap < planned, "{} vs {}", car.stints[1].from_lap, planned);
+    }
+}
@@ -0,0 +1,138 @@
+//! Tests for tyre compounds, wear and the compound rule in the race engine
+
+mod common;
+
+use common::{circuit, drivers, params};
+use f1_cli_simulator::models::{Compound, PerCompound, RaceFormat, SimulationParameters, TYRE_ALLOCATION};
+use f1_cli_simulator::points::PointsSystem;
+use f1_cli_simulator::ratings::Ratings;
+use f1_cli_simulator::simulator::simulation::{self, tyres};
+use f1_cli_simulator::utils;
+use f1_cli_simulator::weather::Weather;
+
+#[test]
+fn test_softer_compounds_are_faster_but_wear_sooner() {
+    let monza = circuit("monza");
+    let params = params();
+    
+    let life = |compound| tyres::tyre_life(compound, &monza, &params);
+    let offset = |compound, age| tyres::lap_time_offset(compound, age, 0.0, &monza, &params);
+    
+    assert!(life(Compound::Soft) < life(Compound::Medium));
+    assert!(life(Compound::Medium) < life(Compound::Hard));
+    
+    // New softs are quickest, but lose more time with every lap
+    assert!(offset(Compound::Soft, 0) < offset(Compound::Medium, 0));
+    assert!(offset(Compound::Medium, 0) < offset(Compound::Hard, 0));
+    assert!(offset(Compound::Soft, 15) - offset(Compound::Soft, 0) > offset(Compound::Hard, 15) - offset(Compound::Hard, 0));
+    
+    // Slicks fall off a cliff at the end of their life
+    let soft_life = life(Compound::Soft);
+    let worn = offset(Compound::Soft, soft_life - 1) - offset(Compound::Soft, soft_life - 2);
+    let cliff = offset(Compound::Soft, soft_life) - offset(Compound::Soft, soft_life - 1);
+    assert!(cliff > worn + 1.0);
+    
+    // Wet tyres are slow in the dry, and slicks slower still in the wet
+    assert!(offset(Compound::Intermediate, 0) > offset(Compound::Hard, 0));
+    assert!(tyres::lap_time_offset(Compound::Intermediate, 0, 0.5, &monza, &params) < tyres::lap_time_offset(Compound::Soft, 0, 0.5, &monza, &params));
+}
+
+#[test]
+fn test_wear_depends_on_the_circuit_and_the_multipliers() {
+    let params = params();
+    let (bahrain, monaco) = (circuit("bahrain"), circuit("monaco"));
+    
+    assert!(tyres::tyre_life(Compound::Medium, &bahrain, &params) < tyres::tyre_life(Compound::Medium, &monaco, &params));
+    assert!(tyres::lap_time_offset(Compound::Medium, 10, 0.0, &bahrain, &params) > tyres::lap_time_offset(Compound::Medium, 10, 0.0, &monaco, &params));
+    
+    // Doubling the wear of one compound halves its life and leaves the others alone
+    let harsh = SimulationParameters {
+        degradation: PerCompound::uniform(1.0).with_overrides("medium=2").unwrap(),
+        ..params.clone()
+    };
+    let medium_life = tyres::tyre_life(Compound::Medium, &bahrain, &params);
+    assert!(tyres::tyre_life(Compound::Medium, &bahrain, &harsh).abs_diff(medium_life / 2) <= 1);
+    assert_eq!(tyres::tyre_life(Compound::Hard, &bahrain, &harsh), tyres::tyre_life(Compound::Hard, &bahrain, &params));
+}
+
+#[test]
+fn test_compound_rule_and_allocation_overrides() {
+    assert!(!tyres::meets_compound_rule(&[Compound::Medium]));
+    assert!(!tyres::meets_compound_rule(&[Compound::Medium, Compound::Medium]));
+    assert!(tyres::meets_compound_rule(&[Compound::Medium, Compound::Hard]));
+    assert!(tyres::meets_compound_rule(&[Compound::Intermediate]));
+    
+    let allocation = TYRE_ALLOCATION.with_overrides("soft=2, inter=0").unwrap();
+    assert_eq!(allocation.soft, 2);
+    assert_eq!(allocation.intermediate, 0);
+    assert_eq!(allocation.hard, TYRE_ALLOCATION.hard);
+    
+    assert_eq!(PerCompound::uniform(1.0).with_overrides("1.5").unwrap(), PerCompound::uniform(1.5));
+    assert!(TYRE_ALLOCATION.with_overrides("slick=2").is_err());
+    assert!(TYRE_ALLOCATION.with_overrides("soft=lots").is_err());
+}
+
+#[test]
+fn test_grand_prix_cars_run_two_dry_compounds() {
+    let drivers = drivers();
+    let bahrain = circuit("bahrain");
+    
+    let race = simulation::run_race(&drivers, &bahrain, &params(), RaceFormat::GrandPrix, &Ratings::new(), &mut utils::seeded_rng(Some(1)));
+    
+    for car in &race.cars {
+        let used = car.compounds_used();
+        assert_eq!(used[0], Compound::Medium);
+        assert!(used.len() >= 2, "{:?}", used);
+        assert!(tyres::meets_compound_rule(&used));
+        
+        // No set is run far past its life
+        for (i, stint) in car.stints.iter().enumerate() {
+            let end = car.stints.get(i + 1).map_or(car.laps(), |next| next.from_lap);
+            assert!(end - stint.from_lap <= tyres::tyre_life(stint.compound, &bahrain, &params()) + 1);
+        }
+    }
+    
+    let results = race.results(&drivers, &bahrain, RaceFormat::GrandPrix, &PointsSystem::for_season(2024));
+    assert!(results.iter().all(|r| r.status == "Finished"));
+}
+
+#[test]
+fn test_a_single_dry_compound_is_disqualified() {
+    let drivers = drivers();
+    let monza = circuit("monza");
+    let params = SimulationParameters {
+        tyre_allocation: TYRE_ALLOCATION.with_overrides("soft=0,hard=0").unwrap(),
+        ..params()
+    };
+    
+    let race = simulation::run_race(&drivers, &monza, &params, RaceFormat::GrandPrix, &Ratings::new(), &mut utils::seeded_rng(Some(2)));
+    let results = race.results(&drivers, &monza, RaceFormat::GrandPrix, &PointsSystem::for_season(2024));
+    
+    for result in &results {
+        assert_eq!(result.status, "Disqualified");
+        assert_eq!(result.points, 0.0);
+    }
+}
+
+#[test]
+fn test_sprints_and_wet_races_lift_the_compound_rule() {
+    let drivers = drivers();
+    let mut sprint = circuit("monza");
+    sprint.laps = RaceFormat::Sprint.laps(&sprint);
+    
+    // A sprint is run flat out on one set
+    let race = simulation::run_race(&drivers, &sprint, &params(), RaceFormat::Sprint, &Ratings::new(), &mut utils::seeded_rng(Some(3)));
+    for car in &race.cars {
+        assert_eq!(car.compounds_used(), vec![Compound::Medium]);
+        assert!(car.is_running());
+    }
+    
+    // A wet grand prix starts on intermediates
+    let wet = SimulationParameters { weather: Weather::Wet, ..params() };
+    let monza = circuit("monza");
+    let race = simulation::run_race(&drivers, &monza, &wet, RaceFormat::GrandPrix, &Ratings::new(), &mut utils::seeded_rng(Some(4)));
+    for car in &race.cars {
+        assert!(car.compounds_used().iter().all(|&c| c == Compound::Intermediate));
+        assert!(car.is_running());
+    }
+}
@@ -0,0 +1,154 @@
+//! Tests for the lap-by-lap weather and how the race engine reacts to it
+
+mod common;
+
+use common::{drivers, monza};
+use f1_cli_simulator::models::{Compound, RaceFormat, SimulationParameters};
+use f1_cli_simulator::ratings::Ratings;
+use f1_cli_simulator::simulator::simulation::scenario::Scenario;
+use f1_cli_simulator::simulator::simulation::{self, tyres};
+use f1_cli_simulator::utils;
+use f1_cli_simulator::weather::{RainChange, Weather, WeatherTimeline};
+
+// Race parameters for the weather given, without incidents
+fn params(weather: &str) -> SimulationParameters {
+    SimulationParameters {
+        weather: weather.parse().unwrap(),
+        random_incidents: false,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_weather_is_parsed() {
+    assert_eq!("dry".parse::<Weather>().unwrap(), Weather::Dry);
+    assert_eq!("Wet".parse::<Weather>().unwrap(), Weather::Wet);
+    assert_eq!("random".parse::<Weather>().unwrap(), Weather::Random { seed: None });
+    assert_eq!("random:42".parse::<Weather>().unwrap(), Weather::Random { seed: Some(42) });
+    
+    let weather: Weather = "20:0.6,35:0".parse().unwrap();
+    assert_eq!(weather, Weather::Scripted(vec![
+        RainChange { lap: 20, intensity: 0.6 },
+        RainChange { lap: 35, intensity: 0.0 },
+    ]));
+    assert_eq!(weather.to_string(), "rain 0.6 from lap 20, dry from lap 35");
+    
+    assert!("drizzle".parse::<Weather>().is_err());
+    assert!("20:1.5".parse::<Weather>().is_err());
+    assert!("random:soon".parse::<Weather>().is_err());
+}
+
+#[test]
+fn test_the_track_wets_and_dries_with_the_rain() {
+    let timeline = WeatherTimeline::from_changes(&[
+        RainChange { lap: 20, intensity: 0.8 },
+        RainChange { lap: 30, intensity: 0.0 },
+    ], 53);
+    
+    // Dry until the rain, which the forecast sees coming
+    assert_eq!(timeline.conditions(10).track_wetness, 0.0);
+    assert_eq!(timeline.conditions(10).rain_probability, 0.0);
+    assert!(timeline.conditions(16).rain_probability > 0.0);
+    assert!(timeline.conditions(18).rain_probability > timeline.conditions(16).rain_probability);
+    assert!(!timeline.conditions(19).is_raining());
+    
+    // The track gets wetter lap by lap while it rains, never wetter than the rain makes it
+    let wetness = |lap| timeline.conditions(lap).track_wetness;
+    assert!(timeline.conditions(20).is_raining());
+    assert!(wetness(20) > 0.0 && wetness(20) < wetness(22));
+    assert!(wetness(29) <= 0.8);
+    assert_eq!(timeline.conditions(25).rain_probability, 1.0);
+    
+    // And dries slowly once it stops
+    assert!(!timeline.conditions(30).is_raining());
+    assert!(wetness(31) < wetness(29) && wetness(31) > 0.5);
+    assert_eq!(wetness(53), 0.0);
+    
+    assert_eq!(timeline.rain_spells(), vec![(20, 29)]);
+    assert_eq!(WeatherTimeline::dry(53).rain_spells(), vec![]);
+}
+
+#[test]
+fn test_random_weather_follows_the_seed_and_the_circuit() {
+    let mut circuit = monza();
+    let mut rng = utils::seeded_rng(Some(1));
+    
+    circuit.weather_risk = 1.0;
+    let weather = Weather::Random { seed: Some(7) };
+    let timeline = weather.timeline(circuit.laps, &circuit, &mut rng);
+    assert_eq!(timeline, weather.timeline(circuit.laps, &circuit, &mut rng));
+    assert_eq!(timeline.rain_spells().len(), 1);
+    
+    circuit.weather_risk = 0.0;
+    for seed in 0..20 {
+        let timeline = Weather::Random { seed: Some(seed) }.timeline(circuit.laps, &circuit, &mut rng);
+        assert!(timeline.rain_spells().is_empty());
+    }
+    
+    // A wet race is wet from the first lap
+    let wet = Weather::Wet.timeline(circuit.laps, &circuit, &mut rng);
+    assert_eq!(wet.rain_spells(), vec![(1, circuit.laps)]);
+    assert!(tyres::is_wet_track(wet.conditions(1).track_wetness));
+}
+
+#[test]
+fn test_the_tyres_cross_over_with_the_wetness() {
+    assert_eq!(tyres::fastest_compound(0.0), Compound::Soft);
+    assert_eq!(tyres::fastest_compound(0.5), Compound::Intermediate);
+    assert_eq!(tyres::fastest_compound(0.9), Compound::Wet);
+    
+    assert!(tyres::suits(Compound::Hard, 0.1));
+    assert!(!tyres::suits(Compound::Intermediate, 0.1));
+    assert!(tyres::suits(Compound::Intermediate, 0.5));
+    assert!(!tyres::suits(Compound::Wet, 0.5));
+    
+    assert_eq!(tyres::starting_compound(0.0, &params("dry")), Compound::Medium);
+    assert_eq!(tyres::starting_compound(0.5, &params("dry")), Compound::Intermediate);
+    assert_eq!(tyres::starting_compound(0.9, &params("dry")), Compound::Wet);
+}
+
+#[test]
+fn test_drivers_count_for_more_in_the_wet() {
+    let ratings = Ratings::new();
+    let driver = &drivers()[0];
+    
+    let dry = simulation::calculate_driver_base_performance(driver, &ratings, 0.0);
+    let wet = simulation::calculate_driver_base_performance(driver, &ratings, 1.0);
+    assert!(wet > 0.0 && wet < dry);
+}
+
+#[test]
+fn test_cars_change_tyres_as_the_rain_comes_and_goes() {
+    let (drivers, circuit) = (drivers(), monza());
+    let params = params("10:0.8,25:0");
+    let race = simulation::run_race(&drivers, &circuit, &params, RaceFormat::GrandPrix, &Ratings::new(), &mut utils::seeded_rng(Some(2)));
+    
+    for car in &race.cars {
+        assert!(car.is_running(), "{:?}", car.retirement);
+        let compounds = car.compounds_used();
+        
+        // Slicks to the wet tyres soon after the rain starts, and back once the track dries
+        assert!(compounds[0].is_dry());
+        let wet_stint = car.stints.iter().find(|stint| !stint.compound.is_dry()).unwrap();
+        assert!((10..=14).contains(&wet_stint.from_lap), "changed on lap {}", wet_stint.from_lap);
+        assert!(compounds.last().unwrap().is_dry());
+        
+        // Laps in the rain are far slower
+        assert!(car.lap_time(20).unwrap().as_secs_f64() > car.lap_time(5).unwrap().as_secs_f64() + 3.0);
+    }
+}
+
+#[test]
+fn test_scenarios_give_the_weather() {
+    let scenario = Scenario::from_json(r#"{ "weather": "random:3" }"#).unwrap();
+    assert_eq!(scenario.weather, Some(Weather::Random { seed: Some(3) }));
+    
+    let scenario = Scenario::from_json(r#"{ "weather": [{ "lap": 12, "rain": 0.4 }, { "lap": 20, "intensity": 0 }] }"#).unwrap();
+    assert_eq!(scenario.weather, Some(Weather::Scripted(vec![
+        RainChange { lap: 12, intensity: 0.4 },
+        RainChange { lap: 20, intensity: 0.0 },
+    ])));
+    
+    assert_eq!(Scenario::from_json("{}").unwrap().weather, None);
+    assert!(Scenario::from_json(r#"{ "weather": "monsoon" }"#).is_err());
+}